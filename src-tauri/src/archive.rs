@@ -0,0 +1,311 @@
+// Portable library archives.
+//
+// An archive is a zip file anyone can open: the SVGs sit under `icons/` in
+// one folder per collection, and `manifest.json` next to them describes the
+// collections, tags, favorites, colors and saved searches. The manifest is
+// what gets imported; the folder layout is only there for people browsing
+// the archive, so file names can be cleaned up freely.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::error::{Error, ErrorCode, Result};
+
+pub const FORMAT: &str = "iconbox-archive";
+pub const VERSION: u32 = 1;
+const MANIFEST: &str = "manifest.json";
+
+#[derive(Serialize, Deserialize)]
+pub struct Manifest {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: String,
+    /// Parents before their subcollections.
+    pub collections: Vec<ArchiveCollection>,
+    #[serde(default)]
+    pub smart_collections: Vec<ArchiveSmartCollection>,
+    /// Tags with a color, or unused ones, which the icons alone would lose.
+    #[serde(default)]
+    pub tags: Vec<ArchiveTag>,
+    pub icons: Vec<ArchiveIcon>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ArchiveCollection {
+    pub id: String,
+    pub name: String,
+    /// `None` for the top of the export.
+    pub parent_id: Option<String>,
+    pub color: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ArchiveSmartCollection {
+    pub name: String,
+    pub query: String,
+    pub color: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ArchiveTag {
+    /// Full path, e.g. `ui/arrows`.
+    pub name: String,
+    pub color: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ArchiveIcon {
+    pub name: String,
+    pub collection_id: String,
+    /// The SVG's entry in the archive.
+    pub file: String,
+    /// Where the icon was originally imported from.
+    pub path: String,
+    pub tags: Vec<String>,
+    pub favorite: bool,
+    pub created_at: String,
+}
+
+/// A manifest with the SVG of each of its icons, in manifest order.
+pub struct Archive {
+    pub manifest: Manifest,
+    pub svgs: Vec<String>,
+}
+
+/// Writes `manifest` and the SVG files (`(entry name, content)`) to a new
+/// archive at `path`. The file only appears once it is complete.
+pub fn write(path: &Path, manifest: &Manifest, files: &[(String, String)]) -> Result<()> {
+    let manifest = serde_json::to_string_pretty(manifest)?;
+    let partial = path.with_extension("partial");
+    let written = (|| {
+        let mut zip = ZipWriter::new(BufWriter::new(File::create(&partial)?));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        zip.start_file(MANIFEST, options)?;
+        zip.write_all(manifest.as_bytes())?;
+        for (name, content) in files {
+            zip.start_file(name.as_str(), options)?;
+            zip.write_all(content.as_bytes())?;
+        }
+        zip.finish()?.flush()?;
+        Ok::<_, zip::result::ZipError>(())
+    })();
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&partial);
+        return Err(Error::io(&partial, "Cannot write", e.into()));
+    }
+    std::fs::rename(&partial, path).map_err(|e| Error::io(&partial, "Cannot rename", e))
+}
+
+/// Reads an archive and checks that its manifest is complete: every icon's
+/// collection and file exist.
+pub fn read(path: &Path) -> Result<Archive> {
+    let file = File::open(path).map_err(|e| Error::io(path, "Cannot read", e))?;
+    let not_archive = |why: &str| {
+        Error::invalid(format!(
+            "{} is not an IconBox archive ({})",
+            path.display(),
+            why
+        ))
+        .with_path(path.to_string_lossy())
+    };
+    let mut zip =
+        ZipArchive::new(BufReader::new(file)).map_err(|_| not_archive("not a zip file"))?;
+    let manifest =
+        read_entry(&mut zip, MANIFEST, MAX_ENTRY_SIZE).map_err(|_| not_archive("no manifest"))?;
+    let manifest: Manifest =
+        serde_json::from_slice(&manifest).map_err(|e| not_archive(&e.to_string()))?;
+    if manifest.format != FORMAT {
+        return Err(not_archive("unknown format"));
+    }
+    if manifest.version > VERSION {
+        return Err(Error::new(
+            ErrorCode::TooNew,
+            format!(
+                "{} was exported by a newer version of IconBox. Update IconBox to import it.",
+                path.display()
+            ),
+        )
+        .with_path(path.to_string_lossy()));
+    }
+
+    let collections: HashSet<&str> = manifest.collections.iter().map(|c| c.id.as_str()).collect();
+    let mut svgs = Vec::with_capacity(manifest.icons.len());
+    for icon in &manifest.icons {
+        if !collections.contains(icon.collection_id.as_str()) {
+            return Err(not_archive(&format!("\"{}\" has no collection", icon.name)));
+        }
+        let svg = read_entry(&mut zip, &icon.file, MAX_ENTRY_SIZE)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or_else(|| not_archive(&format!("{} is missing or damaged", icon.file)))?;
+        svgs.push(svg);
+    }
+    Ok(Archive { manifest, svgs })
+}
+
+/// Turns a collection or icon name into a file name that works on every
+/// system, unique among `taken` (compared case-insensitively).
+pub fn file_name(name: &str, extension: &str, taken: &mut HashSet<String>) -> String {
+    let mut base: String = name
+        .chars()
+        .map(|c| {
+            if c.is_control() || r#"<>:"/\|?*"#.contains(c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    base = base.trim().trim_end_matches('.').to_string();
+    let reserved = [
+        "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "LPT1", "LPT2", "LPT3",
+    ];
+    if base.is_empty() || reserved.contains(&base.to_uppercase().as_str()) {
+        base = format!("_{}", base);
+    }
+    let mut candidate = format!("{}{}", base, extension);
+    let mut n = 2;
+    while !taken.insert(candidate.to_lowercase()) {
+        candidate = format!("{} ({}){}", base, n, extension);
+        n += 1;
+    }
+    candidate
+}
+
+/// Largest entry read, against archives that unpack to far more than
+/// their size.
+const MAX_ENTRY_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Reads one entry of at most `max_size` bytes; the zip reader checks its
+/// CRC once it is read through. The size in the entry's header is only a
+/// claim, so the limit holds for what actually unpacks.
+fn read_entry<R: Read + Seek>(
+    zip: &mut ZipArchive<R>,
+    name: &str,
+    max_size: u64,
+) -> zip::result::ZipResult<Vec<u8>> {
+    let entry = zip.by_name(name)?;
+    if entry.size() > max_size {
+        return Err(zip::result::ZipError::InvalidArchive("entry too large"));
+    }
+    let mut content = Vec::new();
+    entry.take(max_size + 1).read_to_end(&mut content)?;
+    if content.len() as u64 > max_size {
+        return Err(zip::result::ZipError::InvalidArchive("entry too large"));
+    }
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("iconbox-archive-{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn manifest(version: u32) -> Manifest {
+        Manifest {
+            format: FORMAT.to_string(),
+            version,
+            exported_at: "2024-01-01T00:00:00Z".to_string(),
+            collections: vec![ArchiveCollection {
+                id: "c".to_string(),
+                name: "Pack".to_string(),
+                parent_id: None,
+                color: Some("#888".to_string()),
+                created_at: "2024-01-01".to_string(),
+            }],
+            smart_collections: Vec::new(),
+            tags: Vec::new(),
+            icons: vec![ArchiveIcon {
+                name: "home".to_string(),
+                collection_id: "c".to_string(),
+                file: "icons/Pack/home.svg".to_string(),
+                path: "/icons/home.svg".to_string(),
+                tags: vec!["ui".to_string()],
+                favorite: true,
+                created_at: "2024-01-01".to_string(),
+            }],
+        }
+    }
+
+    fn files(svg: &str) -> Vec<(String, String)> {
+        vec![("icons/Pack/home.svg".to_string(), svg.to_string())]
+    }
+
+    #[test]
+    fn archives_round_trip() {
+        let dir = temp_dir("round-trip");
+        let path = dir.join("pack.zip");
+        write(&path, &manifest(VERSION), &files("<svg><path/></svg>")).unwrap();
+        assert!(!path.with_extension("partial").exists());
+
+        let archive = read(&path).unwrap();
+        assert_eq!(archive.svgs, ["<svg><path/></svg>"]);
+        let icon = &archive.manifest.icons[0];
+        assert_eq!((icon.name.as_str(), icon.favorite), ("home", true));
+        assert_eq!(icon.tags, ["ui"]);
+        assert_eq!(archive.manifest.collections[0].name, "Pack");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn damaged_or_incomplete_archives_are_refused() {
+        let dir = temp_dir("damaged");
+        let path = dir.join("pack.zip");
+        let refused = |path: &Path| read(path).err().map(|e| e.code);
+
+        std::fs::write(&path, "just text").unwrap();
+        assert_eq!(refused(&path), Some(ErrorCode::InvalidInput));
+
+        // An icon whose file is not in the archive
+        write(&path, &manifest(VERSION), &[]).unwrap();
+        assert_eq!(refused(&path), Some(ErrorCode::InvalidInput));
+
+        // Cut off partway through
+        write(&path, &manifest(VERSION), &files("<svg/>")).unwrap();
+        let data = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &data[..data.len() / 2]).unwrap();
+        assert_eq!(refused(&path), Some(ErrorCode::InvalidInput));
+
+        write(&path, &manifest(VERSION + 1), &files("<svg/>")).unwrap();
+        assert_eq!(refused(&path), Some(ErrorCode::TooNew));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn entries_over_the_limit_are_not_read() {
+        let dir = temp_dir("limit");
+        let path = dir.join("pack.zip");
+        write(&path, &manifest(VERSION), &files("<svg/>")).unwrap();
+        let mut zip = ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        assert_eq!(
+            read_entry(&mut zip, "icons/Pack/home.svg", 6).unwrap(),
+            b"<svg/>"
+        );
+        assert!(matches!(
+            read_entry(&mut zip, "icons/Pack/home.svg", 5),
+            Err(zip::result::ZipError::InvalidArchive(_))
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn file_names_are_portable_and_unique() {
+        let mut taken = HashSet::new();
+        assert_eq!(file_name("a/b: c?", ".svg", &mut taken), "a_b_ c_.svg");
+        assert_eq!(file_name("A_B_ C_", ".svg", &mut taken), "A_B_ C_ (2).svg");
+        assert_eq!(file_name("con", "", &mut taken), "_con");
+        assert_eq!(file_name(" . ", "", &mut taken), "_");
+    }
+}
@@ -0,0 +1,109 @@
+// Automatic tags derived from what an icon file already tells us.
+//
+// Tags come from the words of the file name (`arrow-left-circle` → arrow,
+// left, circle), the folder the file lives in, the SVG's `<title>`/`<desc>`
+// and any `data-*` attribute values. Numbers, single letters and stop-words
+// (configurable, since every icon set has its own filler like "icon" or
+// "line") are dropped.
+
+use crate::fuzzy::tokenize_name;
+use crate::svg;
+
+pub const DEFAULT_STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "the", "of", "for", "to", "with", "in", "on", "at", "by", "or", "is", "icon",
+    "icons", "svg", "ic", "px", "layer", "group", "path", "copy", "untitled", "created",
+];
+
+/// At most this many tags are derived per icon; name words come first.
+const MAX_TAGS: usize = 16;
+
+/// Parses the stop-word setting: words separated by commas or whitespace.
+pub fn parse_stop_words(text: &str) -> Vec<String> {
+    text.split(|c: char| c == ',' || c.is_whitespace())
+        .map(|w| w.trim().to_lowercase())
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+pub fn default_stop_words() -> String {
+    DEFAULT_STOP_WORDS.join(", ")
+}
+
+/// Derives tags for an icon from its name, its folder's name and its SVG
+/// markup, in that order of priority.
+pub fn derive(
+    name: &str,
+    folder: Option<&str>,
+    svg_content: &str,
+    stop_words: &[String],
+) -> Vec<String> {
+    let text = svg::extract_text(svg_content);
+    let data_values: Vec<String> = svg::data_attributes(svg_content)
+        .into_iter()
+        .map(|(_, value)| value)
+        .collect();
+
+    let sources = [name, folder.unwrap_or(""), &text.title, &text.description]
+        .into_iter()
+        .chain(data_values.iter().map(String::as_str));
+
+    let mut tags: Vec<String> = Vec::new();
+    for source in sources {
+        for word in tokenize_name(source) {
+            let useful = word.chars().count() > 1
+                && !word.chars().all(|c| c.is_ascii_digit())
+                && !stop_words.contains(&word);
+            if useful && !tags.contains(&word) {
+                tags.push(word);
+            }
+        }
+    }
+    tags.truncate(MAX_TAGS);
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn defaults() -> Vec<String> {
+        parse_stop_words(&default_stop_words())
+    }
+
+    #[test]
+    fn stop_words_split_on_commas_and_whitespace() {
+        assert_eq!(
+            parse_stop_words(" Icon,line\n  FILLED ,, "),
+            ["icon", "line", "filled"]
+        );
+        assert!(defaults().contains(&"svg".to_string()));
+    }
+
+    #[test]
+    fn tags_come_from_name_then_folder_then_markup() {
+        let svg = r#"<svg data-category="Navigation"><title>Back arrow</title><desc>Go back</desc></svg>"#;
+        assert_eq!(
+            derive("arrow-left-circle", Some("UI Icons"), svg, &defaults()),
+            ["arrow", "left", "circle", "ui", "back", "go", "navigation"]
+        );
+    }
+
+    #[test]
+    fn filler_numbers_and_single_letters_are_dropped() {
+        assert_eq!(
+            derive("ic_home_24px_2", None, "<svg/>", &defaults()),
+            ["home"]
+        );
+        assert_eq!(derive("x-mark", None, "<svg/>", &[]), ["mark"]);
+        let custom = parse_stop_words("mark");
+        assert!(derive("x-mark", None, "<svg/>", &custom).is_empty());
+    }
+
+    #[test]
+    fn tags_are_capped() {
+        let name: Vec<String> = ('a'..='z').map(|c| c.to_string().repeat(2)).collect();
+        let tags = derive(&name.join("-"), None, "<svg/>", &[]);
+        assert_eq!(tags.len(), MAX_TAGS);
+        assert_eq!(tags[0], "aa");
+    }
+}
@@ -0,0 +1,103 @@
+// Library backups.
+//
+// Backups are written with SQLite's online backup API, which copies the
+// database page by page while it stays in use and yields between steps, so
+// a scheduled backup never stalls the app. Each copy is first written under
+// a temporary name and checked with `Library::verify` before it gets its
+// real name, so a backup that is listed can always be restored.
+//
+// Only files named `iconbox-backup-<time>.db` are rotated; the copies taken
+// before schema upgrades (see migrations.rs) sit in the same folder by
+// default and are left alone.
+
+use rusqlite::backup::Backup;
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::db::Library;
+use crate::error::{Error, Result};
+use crate::DbBackup;
+
+const PREFIX: &str = "iconbox-backup-";
+
+/// Pages copied per step, and the pause between steps that lets other
+/// connections write.
+const PAGES_PER_STEP: std::os::raw::c_int = 256;
+const STEP_PAUSE: Duration = Duration::from_millis(5);
+
+/// Copies the database behind `source` into a new backup file in `dir` and
+/// returns its path.
+pub fn write(source: &Connection, dir: &Path) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir).map_err(|e| Error::io(dir, "Cannot create backup folder", e))?;
+    let stem = format!("{}{}", PREFIX, chrono::Local::now().format("%Y%m%d-%H%M%S"));
+    let mut path = dir.join(format!("{}.db", stem));
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("{}-{}.db", stem, n));
+        n += 1;
+    }
+
+    let partial = path.with_extension("db.partial");
+    let written = (|| {
+        let mut dest = Connection::open(&partial)?;
+        Backup::new(source, &mut dest)?.run_to_completion(PAGES_PER_STEP, STEP_PAUSE, None)?;
+        drop(dest);
+        Library::verify(&partial)
+    })();
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&partial);
+        return Err(e.while_doing("Backing up the library failed"));
+    }
+    std::fs::rename(&partial, &path).map_err(|e| Error::io(&partial, "Cannot rename", e))?;
+    Ok(path)
+}
+
+/// Deletes the oldest rotated backups in `dir` beyond the newest `keep`.
+pub fn rotate(dir: &Path, keep: usize) -> Result<()> {
+    let rotated = list(dir).into_iter().filter(|b| b.name.starts_with(PREFIX));
+    for old in rotated.skip(keep.max(1)) {
+        std::fs::remove_file(&old.path)
+            .map_err(|e| Error::io(Path::new(&old.path), "Cannot delete", e))?;
+    }
+    Ok(())
+}
+
+/// When the newest rotated backup in `dir` was written, if there is one.
+pub fn last_written(dir: &Path) -> Option<SystemTime> {
+    std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .filter(|e| is_backup(&e.path()) && e.file_name().to_string_lossy().starts_with(PREFIX))
+        .filter_map(|e| e.metadata().ok()?.modified().ok())
+        .max()
+}
+
+/// The backups in `dir`, rotated or not, newest first.
+pub fn list(dir: &Path) -> Vec<DbBackup> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<DbBackup> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| is_backup(&e.path()))
+        .filter_map(|e| describe(&e.path()))
+        .collect();
+    backups.sort_by(|a, b| b.modified.cmp(&a.modified));
+    backups
+}
+
+pub fn describe(path: &Path) -> Option<DbBackup> {
+    let meta = std::fs::metadata(path).ok()?;
+    let modified: chrono::DateTime<chrono::Utc> = meta.modified().ok()?.into();
+    Some(DbBackup {
+        path: path.to_string_lossy().to_string(),
+        name: path.file_name()?.to_string_lossy().to_string(),
+        size: meta.len(),
+        modified: modified.to_rfc3339(),
+    })
+}
+
+fn is_backup(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "db")
+}
@@ -1,27 +1,204 @@
-use rusqlite::{Connection, Result};
-use std::path::PathBuf;
-use std::sync::Mutex;
-use tauri::{AppHandle, Manager};
+use rusqlite::types::Value;
+use rusqlite::{Connection, DatabaseName, OpenFlags, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
 
-use crate::{Collection, Icon, Settings};
+use crate::archive;
+use crate::autotag;
+use crate::backup;
+use crate::embedding::{self, EmbeddingModel};
+use crate::error::{Error, ErrorCode, Result};
+use crate::fuzzy::{FuzzyIndex, FuzzyMatch};
+use crate::migrations::{self, Migration};
+use crate::palette;
+use crate::query::{self, Query};
+use crate::synonyms::{self, Thesaurus};
+use crate::{
+    diff, search, svg, visual, ArchiveExportResult, ArchiveImportResult, AutotagResult, BulkResult,
+    Collection, DbBackup, EmbeddingStatus, HistoryState, Icon, IconPage, IconRevision, IconSummary,
+    IconSvg, ImportFailure, ImportResult, MergeResult, RevisionDiff, SearchResults, SemanticMatch,
+    Settings, SimilarIcon, SynonymGroup, Tag, TagNode, TrashItem,
+};
 
-pub struct DbState(pub Mutex<Connection>);
+/// An icon library: the SQLite database plus the in-memory indexes built
+/// from it. Each operation locks the connection only while it needs it, so
+/// one `Library` can be shared between threads.
+pub struct Library {
+    conn: Mutex<Connection>,
+    /// Folder holding the library file; `None` for an in-memory library.
+    data_dir: Option<PathBuf>,
+    /// Copy taken before the schema was upgraded on open, if any.
+    migration_backup: Option<PathBuf>,
+    /// Fuzzy name index, tagged with the connection's `total_changes()` at
+    /// the time it was built so it can be rebuilt lazily after any write.
+    fuzzy: Mutex<Option<(u64, FuzzyIndex)>>,
+    embeddings: Mutex<EmbeddingCache>,
+}
+
+/// Stored icon vectors as `(icon id, vector)` pairs.
+type IconVectors = Vec<(String, Vec<f32>)>;
+
+/// The loaded embedding model plus an in-memory copy of the stored icon
+/// vectors, tagged with `total_changes()` like the fuzzy index.
+#[derive(Default)]
+struct EmbeddingCache {
+    model: Option<Arc<EmbeddingModel>>,
+    vectors: Option<(u64, IconVectors)>,
+    /// The model file that failed to load and why, so a broken or missing
+    /// file isn't read again on every status check or search.
+    failed: Option<(PathBuf, Error)>,
+}
+
+impl Library {
+    /// Opens the library at `path` (or `:memory:`), creating it if needed,
+    /// and brings its schema up to date. An existing file is first copied to
+    /// `backups/` next to it if it needs upgrading.
+    pub fn open(path: impl AsRef<Path>) -> Result<Library> {
+        let path = path.as_ref();
+        if path == Path::new(":memory:") {
+            return Library::open_in_memory();
+        }
+        let data_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let conn = Connection::open(path)?;
+        conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA foreign_keys=ON;")?;
+        // SQLite quietly falls back to read-only when the file is not
+        // writable; every edit would then fail, so refuse to open it.
+        if conn.is_readonly(DatabaseName::Main)? {
+            return Err(Error::new(
+                ErrorCode::ReadOnly,
+                format!("{} is read-only", path.display()),
+            )
+            .with_path(path.to_string_lossy()));
+        }
+        let backup = migrations::migrate(&conn, MIGRATIONS, Some(&data_dir.join("backups")))?;
+        repair_search_index(&conn)?;
+        purge_expired_trash(&conn)?;
+        Ok(Library::new(conn, Some(data_dir), backup))
+    }
+
+    /// An empty library that lives as long as the value, for tests and tools.
+    pub fn open_in_memory() -> Result<Library> {
+        let conn = Connection::open_in_memory()?;
+        conn.execute_batch("PRAGMA foreign_keys=ON;")?;
+        migrations::migrate(&conn, MIGRATIONS, None)?;
+        Ok(Library::new(conn, None, None))
+    }
+
+    /// Opens the library at `path` without ever writing to it, not even to
+    /// upgrade the schema. Every edit fails with a read-only error.
+    pub fn open_read_only(path: impl AsRef<Path>) -> Result<Library> {
+        let path = path.as_ref();
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        conn.execute_batch("PRAGMA foreign_keys=ON;")?;
+        migrations::check_supported(&conn, MIGRATIONS)?;
+        Ok(Library::new(
+            conn,
+            path.parent().map(Path::to_path_buf),
+            None,
+        ))
+    }
+
+    fn new(conn: Connection, data_dir: Option<PathBuf>, migration_backup: Option<PathBuf>) -> Self {
+        Library {
+            conn: Mutex::new(conn),
+            data_dir,
+            migration_backup,
+            fuzzy: Mutex::new(None),
+            embeddings: Mutex::new(EmbeddingCache::default()),
+        }
+    }
+
+    /// The schema version stored in the file at `path`, if it can be read.
+    pub fn schema_version(path: &Path) -> Option<u32> {
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY).ok()?;
+        migrations::user_version(&conn).ok()
+    }
+
+    /// The newest schema version this build understands.
+    pub fn supported_version() -> u32 {
+        migrations::latest(MIGRATIONS)
+    }
+
+    /// Checks that the file at `path` is an intact library this build can
+    /// open, e.g. before restoring it as a backup.
+    pub fn verify(path: &Path) -> Result<()> {
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let check: String = conn.query_row("PRAGMA integrity_check", [], |row| row.get(0))?;
+        if check != "ok" {
+            return Err(Error::new(
+                ErrorCode::Corrupt,
+                format!("{} is damaged: {}", path.display(), check),
+            )
+            .with_path(path.to_string_lossy()));
+        }
+        migrations::check_supported(&conn, MIGRATIONS)?;
+        Ok(())
+    }
+
+    /// The copy of the file taken before its schema was upgraded on open.
+    pub fn migration_backup(&self) -> Option<&Path> {
+        self.migration_backup.as_deref()
+    }
 
-pub fn get_app_data_dir(app: &AppHandle) -> PathBuf {
-    app.path()
-        .app_data_dir()
-        .expect("Failed to get app data dir")
+    fn with_conn<F, T>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&Connection) -> Result<T>,
+    {
+        let conn = self.conn.lock().unwrap();
+        f(&conn)
+    }
 }
 
-pub fn init_db(app: &AppHandle) -> Result<()> {
-    let data_dir = get_app_data_dir(app);
-    std::fs::create_dir_all(&data_dir).expect("Failed to create data dir");
+// ── Schema migrations ────────────────────────────────────────
 
-    let db_path = data_dir.join("iconbox.db");
-    let conn = Connection::open(&db_path)?;
+/// Schema versions, oldest first (see migrations.rs).
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "baseline schema",
+        up: migrate_baseline,
+    },
+    Migration {
+        version: 2,
+        description: "trash",
+        up: migrate_trash,
+    },
+    Migration {
+        version: 3,
+        description: "undo history",
+        up: migrate_history,
+    },
+    Migration {
+        version: 4,
+        description: "icon revisions",
+        up: migrate_revisions,
+    },
+];
 
-    conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA foreign_keys=ON;")?;
+fn has_table(conn: &Connection, name: &str) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        [name],
+        |row| row.get(0),
+    )
+}
+
+fn has_column(conn: &Connection, table: &str, column: &str) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = ?2",
+        [table, column],
+        |row| row.get(0),
+    )
+}
 
+/// Version 1: the schema from before versioning. Databases of that time can
+/// be at any point of the old ad-hoc upgrades, so each step checks what is
+/// already there.
+fn migrate_baseline(conn: &Connection) -> Result<()> {
     conn.execute(
         "CREATE TABLE IF NOT EXISTS collections (
             id TEXT PRIMARY KEY,
@@ -30,6 +207,7 @@ pub fn init_db(app: &AppHandle) -> Result<()> {
             icon_count INTEGER DEFAULT 0,
             color TEXT,
             created_at TEXT NOT NULL,
+            sort_order INTEGER,
             FOREIGN KEY (parent_id) REFERENCES collections(id) ON DELETE CASCADE
         )",
         [],
@@ -41,7 +219,6 @@ pub fn init_db(app: &AppHandle) -> Result<()> {
             name TEXT NOT NULL,
             path TEXT,
             svg_content TEXT NOT NULL,
-            tags TEXT DEFAULT '',
             collection_id TEXT NOT NULL,
             created_at TEXT NOT NULL,
             file_size INTEGER DEFAULT 0,
@@ -66,6 +243,58 @@ pub fn init_db(app: &AppHandle) -> Result<()> {
         [],
     )?;
 
+    init_icon_counts(conn)?;
+
+    // Tags are shared rows linked to icons, so they can be listed, renamed,
+    // merged and colored in one place
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            parent_id INTEGER REFERENCES tags(id) ON DELETE CASCADE,
+            color TEXT
+        );
+        CREATE TABLE IF NOT EXISTS icon_tags (
+            icon_id TEXT NOT NULL,
+            tag_id INTEGER NOT NULL,
+            PRIMARY KEY (icon_id, tag_id),
+            FOREIGN KEY (icon_id) REFERENCES icons(id) ON DELETE CASCADE,
+            FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_icon_tags_tag ON icon_tags(tag_id);",
+    )?;
+
+    // Migrate: tag hierarchy (paths like `category/navigation`)
+    if !has_column(conn, "tags", "parent_id")? {
+        conn.execute(
+            "ALTER TABLE tags ADD COLUMN parent_id INTEGER REFERENCES tags(id) ON DELETE CASCADE",
+            [],
+        )?;
+        link_tag_parents(conn)?;
+    }
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_tags_parent ON tags(parent_id)",
+        [],
+    )?;
+
+    // Migrate: move comma-joined icons.tags into the tag tables
+    let has_tags_column = has_column(conn, "icons", "tags")?;
+    if has_tags_column {
+        migrate_tags_column(conn)?;
+    }
+
+    // Smart collections: saved searches listed alongside real collections
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS smart_collections (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            query TEXT NOT NULL,
+            color TEXT NOT NULL DEFAULT '#6366f1',
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
     conn.execute(
         "CREATE TABLE IF NOT EXISTS settings (
             key TEXT PRIMARY KEY,
@@ -74,336 +303,4635 @@ pub fn init_db(app: &AppHandle) -> Result<()> {
         [],
     )?;
 
+    // Visual fingerprints for similarity search (see visual.rs). `shape` is
+    // empty for icons that couldn't be rendered.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS icon_visuals (
+            icon_id TEXT PRIMARY KEY,
+            phash INTEGER NOT NULL,
+            shape BLOB NOT NULL,
+            FOREIGN KEY (icon_id) REFERENCES icons(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    // Semantic search vectors (see embedding.rs). `source` is the text that
+    // was embedded, so edits to names or tags are picked up on the next search.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS icon_embeddings (
+            icon_id TEXT PRIMARY KEY,
+            model TEXT NOT NULL,
+            source TEXT NOT NULL,
+            vector BLOB NOT NULL,
+            FOREIGN KEY (icon_id) REFERENCES icons(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    // Colors used by each icon (see palette.rs), filled in for existing icons
+    // the first time the table is created
+    let has_palettes = has_table(conn, "icon_colors")?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS icon_colors (
+            icon_id TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            r INTEGER NOT NULL,
+            g INTEGER NOT NULL,
+            b INTEGER NOT NULL,
+            PRIMARY KEY (icon_id, position),
+            FOREIGN KEY (icon_id) REFERENCES icons(id) ON DELETE CASCADE
+        );",
+    )?;
+    if !has_palettes {
+        backfill_palettes(conn)?;
+    }
+
+    let has_synonyms = has_table(conn, "synonym_groups")?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS synonym_groups (
+            id INTEGER PRIMARY KEY AUTOINCREMENT
+        );
+        CREATE TABLE IF NOT EXISTS synonyms (
+            group_id INTEGER NOT NULL,
+            term TEXT NOT NULL,
+            PRIMARY KEY (group_id, term),
+            FOREIGN KEY (group_id) REFERENCES synonym_groups(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_synonyms_term ON synonyms(term);",
+    )?;
+
+    // Seed the default dictionary once; after that it belongs to the user
+    if !has_synonyms {
+        seed_default_synonyms(conn)?;
+    }
+
     // Migrate: add parent_id column if missing (for existing databases)
-    let has_parent_id: bool = conn
-        .prepare("SELECT COUNT(*) FROM pragma_table_info('collections') WHERE name='parent_id'")?
-        .query_row([], |row| row.get::<_, i32>(0))
-        .map(|c| c > 0)
-        .unwrap_or(false);
-    if !has_parent_id {
-        let _ = conn.execute("ALTER TABLE collections ADD COLUMN parent_id TEXT", []);
+    if !has_column(conn, "collections", "parent_id")? {
+        conn.execute("ALTER TABLE collections ADD COLUMN parent_id TEXT", [])?;
+    }
+
+    // Migrate: manual order among sibling collections (NULL sorts by name)
+    if !has_column(conn, "collections", "sort_order")? {
+        conn.execute("ALTER TABLE collections ADD COLUMN sort_order INTEGER", [])?;
     }
 
     // Migrate: add file_size column if missing
-    let has_file_size: bool = conn
-        .prepare("SELECT COUNT(*) FROM pragma_table_info('icons') WHERE name='file_size'")?
-        .query_row([], |row| row.get::<_, i32>(0))
-        .map(|c| c > 0)
-        .unwrap_or(false);
-    if !has_file_size {
-        let _ = conn.execute("ALTER TABLE icons ADD COLUMN file_size INTEGER DEFAULT 0", []);
+    if !has_column(conn, "icons", "file_size")? {
+        conn.execute(
+            "ALTER TABLE icons ADD COLUMN file_size INTEGER DEFAULT 0",
+            [],
+        )?;
+    }
+
+    // Migrate: add title/description columns (text pulled out of the SVG for search)
+    if !has_column(conn, "icons", "title")? {
+        conn.execute("ALTER TABLE icons ADD COLUMN title TEXT DEFAULT ''", [])?;
+        conn.execute(
+            "ALTER TABLE icons ADD COLUMN description TEXT DEFAULT ''",
+            [],
+        )?;
+        backfill_svg_text(conn)?;
     }
 
-    app.manage(DbState(Mutex::new(conn)));
+    init_search_index(conn)?;
+    if has_tags_column {
+        rebuild_search_index(conn)?;
+    }
     Ok(())
 }
 
-pub fn with_db<F, T>(app: &AppHandle, f: F) -> Result<T>
-where
-    F: FnOnce(&Connection) -> Result<T>,
-{
-    let state = app.state::<DbState>();
-    let conn = state.0.lock().unwrap();
-    f(&conn)
+fn seed_default_synonyms(conn: &Connection) -> Result<()> {
+    for group in synonyms::DEFAULT_GROUPS {
+        conn.execute("INSERT INTO synonym_groups DEFAULT VALUES", [])?;
+        let group_id = conn.last_insert_rowid();
+        for term in group.iter() {
+            conn.execute(
+                "INSERT OR IGNORE INTO synonyms (group_id, term) VALUES (?1, ?2)",
+                rusqlite::params![group_id, term],
+            )?;
+        }
+    }
+    Ok(())
 }
 
-// ── Collections ──────────────────────────────────────────────
+fn backfill_svg_text(conn: &Connection) -> Result<()> {
+    let rows: Vec<(String, String)> = conn
+        .prepare("SELECT id, svg_content FROM icons")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .filter_map(|r| r.ok())
+        .collect();
 
-pub fn get_all_collections(app: &AppHandle) -> Result<Vec<Collection>> {
-    with_db(app, |conn| {
-        let mut stmt = conn.prepare(
-            "SELECT id, name, parent_id, icon_count, color, created_at FROM collections ORDER BY name",
-        )?;
+    let mut stmt = conn.prepare("UPDATE icons SET title = ?1, description = ?2 WHERE id = ?3")?;
+    for (id, svg_content) in rows {
+        let text = svg::extract_text(&svg_content);
+        stmt.execute([&text.title, &text.description, &id])?;
+    }
+    Ok(())
+}
 
-        let collections = stmt
-            .query_map([], |row| {
-                let parent_id: Option<String> = row.get(2)?;
-                Ok(Collection {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    parent_id,
-                    icon_count: row.get(3)?,
-                    color: row.get(4)?,
-                    created_at: row.get(5)?,
-                })
-            })?
-            .filter_map(|r| r.ok())
-            .collect();
+fn migrate_tags_column(conn: &Connection) -> Result<()> {
+    let rows: Vec<(String, String)> = conn
+        .prepare("SELECT id, tags FROM icons WHERE tags IS NOT NULL AND tags != ''")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .filter_map(|r| r.ok())
+        .collect();
 
-        Ok(collections)
-    })
+    for (id, tags) in rows {
+        let tags: Vec<String> = tags.split(',').map(|t| t.to_string()).collect();
+        set_icon_tags(conn, &id, &tags)?;
+    }
+    // The old search triggers read the column; init_search_index recreates them
+    conn.execute_batch(
+        "DROP TRIGGER IF EXISTS icons_fts_insert;
+        DROP TRIGGER IF EXISTS icons_fts_update;
+        ALTER TABLE icons DROP COLUMN tags;",
+    )?;
+    Ok(())
 }
 
-pub fn insert_collection(app: &AppHandle, collection: &Collection) -> Result<()> {
-    with_db(app, |conn| {
-        conn.execute(
-            "INSERT INTO collections (id, name, parent_id, icon_count, color, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            rusqlite::params![
-                collection.id,
-                collection.name,
-                collection.parent_id,
-                collection.icon_count,
-                collection.color,
-                collection.created_at,
-            ],
-        )?;
-        Ok(())
-    })
+fn backfill_palettes(conn: &Connection) -> Result<()> {
+    let rows: Vec<(String, String)> = conn
+        .prepare("SELECT id, svg_content FROM icons")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    for (id, svg_content) in rows {
+        save_palette(conn, &id, &palette::extract(&svg_content))?;
+    }
+    Ok(())
 }
 
-pub fn rename_collection(app: &AppHandle, id: &str, new_name: &str) -> Result<()> {
-    with_db(app, |conn| {
-        conn.execute(
-            "UPDATE collections SET name = ?1 WHERE id = ?2",
-            [new_name, id],
-        )?;
-        Ok(())
-    })
+/// Space-separated tag names of the icon whose id is `icon_id` (an SQL
+/// expression), as stored in the `tags` column of the search index.
+fn tag_text_sql(icon_id: &str) -> String {
+    format!(
+        "(SELECT COALESCE(group_concat(t.name, ' '), '') FROM icon_tags it
+          JOIN tags t ON t.id = it.tag_id WHERE it.icon_id = {})",
+        icon_id
+    )
 }
 
-pub fn delete_collection(app: &AppHandle, id: &str) -> Result<()> {
-    with_db(app, |conn| {
-        // Delete icons belonging to this collection
-        conn.execute("DELETE FROM icons WHERE collection_id = ?1", [id])?;
-        // Delete subcollections (and their icons) recursively via a CTE
-        conn.execute(
-            "WITH RECURSIVE sub(id) AS (
-                SELECT id FROM collections WHERE parent_id = ?1
-                UNION ALL
-                SELECT c.id FROM collections c JOIN sub s ON c.parent_id = s.id
-            )
-            DELETE FROM icons WHERE collection_id IN (SELECT id FROM sub)",
-            [id],
-        )?;
-        conn.execute(
-            "WITH RECURSIVE sub(id) AS (
-                SELECT id FROM collections WHERE parent_id = ?1
-                UNION ALL
-                SELECT c.id FROM collections c JOIN sub s ON c.parent_id = s.id
-            )
-            DELETE FROM collections WHERE id IN (SELECT id FROM sub)",
-            [id],
-        )?;
-        conn.execute("DELETE FROM collections WHERE id = ?1", [id])?;
-        Ok(())
-    })
+/// Creates the FTS5 index over icon text and the triggers that keep it in
+/// sync with `icons`, `collections` and tags. FTS rows share the rowid of the
+/// icon they describe.
+fn init_search_index(conn: &Connection) -> Result<()> {
+    let sql = "CREATE VIRTUAL TABLE IF NOT EXISTS icons_fts USING fts5(
+            name, tags, collection, title, description, path,
+            tokenize = 'unicode61 remove_diacritics 2'
+        );
+
+        CREATE TRIGGER IF NOT EXISTS icons_fts_insert AFTER INSERT ON icons BEGIN
+            INSERT INTO icons_fts (rowid, name, tags, collection, title, description, path)
+            VALUES (
+                new.rowid,
+                new.name,
+                {new_tags},
+                COALESCE((SELECT name FROM collections WHERE id = new.collection_id), ''),
+                COALESCE(new.title, ''),
+                COALESCE(new.description, ''),
+                COALESCE(new.path, '')
+            );
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS icons_fts_delete AFTER DELETE ON icons BEGIN
+            DELETE FROM icons_fts WHERE rowid = old.rowid;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS icons_fts_update
+        AFTER UPDATE OF name, collection_id, title, description, path ON icons BEGIN
+            DELETE FROM icons_fts WHERE rowid = old.rowid;
+            INSERT INTO icons_fts (rowid, name, tags, collection, title, description, path)
+            VALUES (
+                new.rowid,
+                new.name,
+                {new_tags},
+                COALESCE((SELECT name FROM collections WHERE id = new.collection_id), ''),
+                COALESCE(new.title, ''),
+                COALESCE(new.description, ''),
+                COALESCE(new.path, '')
+            );
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS collections_fts_rename AFTER UPDATE OF name ON collections BEGIN
+            UPDATE icons_fts SET collection = new.name
+            WHERE rowid IN (SELECT rowid FROM icons WHERE collection_id = new.id);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS icon_tags_fts_insert AFTER INSERT ON icon_tags BEGIN
+            UPDATE icons_fts SET tags = {new_icon_tags}
+            WHERE rowid = (SELECT rowid FROM icons WHERE id = new.icon_id);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS icon_tags_fts_delete AFTER DELETE ON icon_tags BEGIN
+            UPDATE icons_fts SET tags = {old_icon_tags}
+            WHERE rowid = (SELECT rowid FROM icons WHERE id = old.icon_id);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS tags_fts_rename AFTER UPDATE OF name ON tags BEGIN
+            UPDATE icons_fts SET tags = {fts_row_tags}
+            WHERE rowid IN (SELECT i.rowid FROM icons i
+                            JOIN icon_tags it ON it.icon_id = i.id WHERE it.tag_id = new.id);
+        END;"
+        .replace("{new_tags}", &tag_text_sql("new.id"))
+        .replace("{new_icon_tags}", &tag_text_sql("new.icon_id"))
+        .replace("{old_icon_tags}", &tag_text_sql("old.icon_id"))
+        .replace(
+            "{fts_row_tags}",
+            &tag_text_sql("(SELECT id FROM icons WHERE rowid = icons_fts.rowid)"),
+        );
+    conn.execute_batch(&sql)?;
+    repair_search_index(conn)
+}
+
+/// Refills the search index if it ever drifted out of sync with the icons.
+fn repair_search_index(conn: &Connection) -> Result<()> {
+    let indexed: i64 = conn.query_row("SELECT COUNT(*) FROM icons_fts", [], |row| row.get(0))?;
+    let total: i64 = conn.query_row("SELECT COUNT(*) FROM icons", [], |row| row.get(0))?;
+    if indexed != total {
+        rebuild_search_index(conn)?;
+    }
+    Ok(())
 }
 
-pub fn update_collection_count(app: &AppHandle, id: &str, count: i32) -> Result<()> {
-    with_db(app, |conn| {
+/// Keeps `collections.icon_count` (icons directly in the collection) in step
+/// with the icons table. Counts are recomputed once when the triggers are
+/// first created, since earlier versions only wrote them on import.
+fn init_icon_counts(conn: &Connection) -> Result<()> {
+    let has_triggers: bool = conn
+        .prepare(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type='trigger' AND name='icons_count_insert'",
+        )?
+        .query_row([], |row| row.get::<_, i32>(0))
+        .map(|c| c > 0)
+        .unwrap_or(false);
+
+    conn.execute_batch(
+        "CREATE TRIGGER IF NOT EXISTS icons_count_insert AFTER INSERT ON icons BEGIN
+            UPDATE collections SET icon_count = icon_count + 1 WHERE id = new.collection_id;
+        END;
+        CREATE TRIGGER IF NOT EXISTS icons_count_delete AFTER DELETE ON icons BEGIN
+            UPDATE collections SET icon_count = icon_count - 1 WHERE id = old.collection_id;
+        END;
+        CREATE TRIGGER IF NOT EXISTS icons_count_move AFTER UPDATE OF collection_id ON icons
+        WHEN old.collection_id != new.collection_id BEGIN
+            UPDATE collections SET icon_count = icon_count - 1 WHERE id = old.collection_id;
+            UPDATE collections SET icon_count = icon_count + 1 WHERE id = new.collection_id;
+        END;",
+    )?;
+
+    if !has_triggers {
         conn.execute(
-            "UPDATE collections SET icon_count = ?1 WHERE id = ?2",
-            [&count.to_string(), id],
+            "UPDATE collections SET icon_count =
+                (SELECT COUNT(*) FROM icons WHERE icons.collection_id = collections.id)",
+            [],
         )?;
-        Ok(())
-    })
+    }
+    Ok(())
 }
 
-// ── Icons ────────────────────────────────────────────────────
+fn rebuild_search_index(conn: &Connection) -> Result<()> {
+    conn.execute_batch(&format!(
+        "DELETE FROM icons_fts;
+        INSERT INTO icons_fts (rowid, name, tags, collection, title, description, path)
+        SELECT i.rowid, i.name, {}, COALESCE(c.name, ''),
+               COALESCE(i.title, ''), COALESCE(i.description, ''), COALESCE(i.path, '')
+        FROM icons i LEFT JOIN collections c ON c.id = i.collection_id;",
+        tag_text_sql("i.id")
+    ))?;
+    Ok(())
+}
 
-pub fn get_icons_by_collection(app: &AppHandle, collection_id: &str) -> Result<Vec<Icon>> {
-    with_db(app, |conn| {
-        let mut stmt = conn.prepare(
-            "SELECT id, name, path, svg_content, tags, collection_id, created_at, file_size, favorite
-             FROM icons WHERE collection_id = ?1 ORDER BY name",
-        )?;
+// ── Collections ──────────────────────────────────────────────
 
-        let icons = stmt
-            .query_map([collection_id], |row| {
-                let tags_str: String = row.get(4)?;
-                let tags: Vec<String> = if tags_str.is_empty() {
-                    vec![]
-                } else {
-                    tags_str.split(',').map(|s| s.trim().to_string()).collect()
-                };
+impl Library {
+    pub fn get_all_collections(&self) -> Result<Vec<Collection>> {
+        self.with_conn(load_collections)
+    }
 
-                Ok(Icon {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    path: row.get(2)?,
-                    svg_content: row.get(3)?,
-                    tags,
-                    collection_id: row.get(5)?,
-                    created_at: row.get(6)?,
-                    file_size: row.get(7)?,
-                    favorite: row.get::<_, i32>(8)? == 1,
-                })
-            })?
-            .filter_map(|r| r.ok())
-            .collect();
+    /// Creates an empty regular collection with a random color.
+    pub fn create_collection(&self, name: &str, parent_id: Option<&str>) -> Result<Collection> {
+        let collection = new_collection(name, parent_id);
+        self.tracked("Create collection", |conn, tracker| {
+            tracker.collections(conn, [collection.id.as_str()])?;
+            insert_collection(conn, &collection)
+        })?;
+        Ok(collection)
+    }
 
-        Ok(icons)
-    })
-}
+    pub fn insert_collection(&self, collection: &Collection) -> Result<()> {
+        self.with_conn(|conn| insert_collection(conn, collection))
+    }
 
-pub fn get_all_icons(app: &AppHandle) -> Result<Vec<Icon>> {
-    with_db(app, |conn| {
-        let mut stmt = conn.prepare(
-            "SELECT id, name, path, svg_content, tags, collection_id, created_at, file_size, favorite
-             FROM icons ORDER BY name",
-        )?;
+    pub fn rename_collection(&self, id: &str, new_name: &str) -> Result<()> {
+        self.tracked("Rename collection", |conn, tracker| {
+            tracker.collections(conn, [id])?;
+            tracker.smart_collections(conn, id)?;
+            conn.execute(
+                "UPDATE collections SET name = ?1 WHERE id = ?2",
+                [new_name, id],
+            )?;
+            conn.execute(
+                "UPDATE smart_collections SET name = ?1 WHERE id = ?2",
+                [new_name, id],
+            )?;
+            Ok(())
+        })
+    }
 
-        let icons = stmt
-            .query_map([], |row| {
-                let tags_str: String = row.get(4)?;
-                let tags: Vec<String> = if tags_str.is_empty() {
-                    vec![]
-                } else {
-                    tags_str.split(',').map(|s| s.trim().to_string()).collect()
+    /// Moves a collection, its subcollections and all their icons to the
+    /// trash. Smart collections are only saved searches and are deleted
+    /// outright.
+    pub fn delete_collection(&self, id: &str) -> Result<()> {
+        self.tracked("Delete collection", |conn, tracker| {
+            tracker.smart_collections(conn, id)?;
+            let trash_id = trash_collection(conn, id)?;
+            tracker.trashed("collection", id, trash_id);
+            conn.execute("DELETE FROM smart_collections WHERE id = ?1", [id])?;
+            Ok(())
+        })
+    }
+
+    /// Moves a collection under another one, or to the top level with `None`.
+    /// It goes after its new siblings' manual order, among the name-sorted ones.
+    pub fn move_collection(&self, id: &str, parent_id: Option<&str>) -> Result<()> {
+        self.tracked("Move collection", |conn, tracker| {
+            ensure_collection_exists(conn, id)?;
+            if let Some(parent) = parent_id {
+                ensure_collection_exists(conn, parent)?;
+                if collection_subtree(conn, id)?.contains(parent) {
+                    return Err(Error::conflict(
+                        "Cannot move a collection into itself or one of its subcollections",
+                    )
+                    .with_collection(id));
+                }
+            }
+            tracker.collections(conn, [id])?;
+            conn.execute(
+                "UPDATE collections SET parent_id = ?1, sort_order = NULL WHERE id = ?2",
+                rusqlite::params![parent_id, id],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Sets the manual order of the subcollections of `parent_id` (top-level
+    /// collections for `None`). Ids that aren't children of it are ignored.
+    pub fn reorder_collections(
+        &self,
+        parent_id: Option<&str>,
+        ordered_ids: &[String],
+    ) -> Result<()> {
+        self.tracked("Reorder collections", |conn, tracker| {
+            tracker.collections(conn, ordered_ids.iter().map(String::as_str))?;
+            for (position, id) in ordered_ids.iter().enumerate() {
+                conn.execute(
+                    "UPDATE collections SET sort_order = ?1 WHERE id = ?2 AND parent_id IS ?3",
+                    rusqlite::params![position as i64, id, parent_id],
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Merges `source_id` into `target_id` and deletes it. Icons move over;
+    /// subcollections move too, merging with same-named ones in the target.
+    /// Icons that duplicate one in the target go to the trash.
+    pub fn merge_collections(&self, source_id: &str, target_id: &str) -> Result<MergeResult> {
+        self.tracked("Merge collections", |conn, tracker| {
+            ensure_collection_exists(conn, source_id)?;
+            ensure_collection_exists(conn, target_id)?;
+            let source_tree = collection_subtree(conn, source_id)?;
+            if source_tree.contains(target_id) {
+                return Err(Error::conflict(
+                    "Cannot merge a collection into itself or one of its subcollections",
+                )
+                .with_collection(source_id));
+            }
+            let touched: Vec<String> = source_tree
+                .into_iter()
+                .chain(collection_subtree(conn, target_id)?)
+                .collect();
+            tracker.collections(conn, touched.iter().map(String::as_str))?;
+            for collection in &touched {
+                let icons: Vec<String> = conn
+                    .prepare("SELECT id FROM icons WHERE collection_id = ?1")?
+                    .query_map([collection], |row| row.get(0))?
+                    .filter_map(|r| r.ok())
+                    .collect();
+                tracker.icons(conn, icons.iter().map(String::as_str))?;
+            }
+
+            let mut result = MergeResult::default();
+            merge_collection_into(conn, source_id, target_id, &mut result, tracker)?;
+            Ok(result)
+        })
+    }
+
+    /// Creates a smart collection (`id` is `None`) or updates its name and
+    /// query, and returns it with its live count. The query uses the search
+    /// bar syntax, e.g. `tag:nav size:<2kb fav:yes`.
+    pub fn save_smart_collection(
+        &self,
+        id: Option<&str>,
+        name: &str,
+        query: &str,
+    ) -> Result<Collection> {
+        query::parse(query)?;
+
+        let label = if id.is_some() {
+            "Edit smart collection"
+        } else {
+            "Create smart collection"
+        };
+        let id = self.tracked(label, |conn, tracker| match id {
+            Some(id) => {
+                tracker.smart_collections(conn, id)?;
+                Ok(update_smart_collection(conn, id, name, query)?.id)
+            }
+            None => {
+                let collection = Collection {
+                    id: Uuid::new_v4().to_string(),
+                    name: name.to_string(),
+                    parent_id: None,
+                    icon_count: 0,
+                    color: random_color(),
+                    created_at: chrono::Utc::now().to_rfc3339(),
+                    query: Some(query.to_string()),
+                    sort_order: None,
+                    total_icon_count: 0,
                 };
+                tracker.smart_collections(conn, &collection.id)?;
+                insert_smart_collection(conn, &collection)?;
+                Ok(collection.id)
+            }
+        })?;
+        self.get_all_collections()?
+            .into_iter()
+            .find(|c| c.id == id)
+            .ok_or_else(|| collection_not_found(&id))
+    }
 
-                Ok(Icon {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    path: row.get(2)?,
-                    svg_content: row.get(3)?,
-                    tags,
-                    collection_id: row.get(5)?,
-                    created_at: row.get(6)?,
-                    file_size: row.get(7)?,
-                    favorite: row.get::<_, i32>(8)? == 1,
-                })
-            })?
-            .filter_map(|r| r.ok())
-            .collect();
+    pub fn insert_smart_collection(&self, collection: &Collection) -> Result<()> {
+        self.with_conn(|conn| insert_smart_collection(conn, collection))
+    }
 
-        Ok(icons)
-    })
+    pub fn update_smart_collection(&self, id: &str, name: &str, query: &str) -> Result<Collection> {
+        self.with_conn(|conn| update_smart_collection(conn, id, name, query))
+    }
 }
 
-pub fn get_favorite_icons(app: &AppHandle) -> Result<Vec<Icon>> {
-    with_db(app, |conn| {
-        let mut stmt = conn.prepare(
-            "SELECT id, name, path, svg_content, tags, collection_id, created_at, file_size, favorite
-             FROM icons WHERE favorite = 1 ORDER BY name",
-        )?;
+fn insert_smart_collection(conn: &Connection, collection: &Collection) -> Result<()> {
+    conn.execute(
+        "INSERT INTO smart_collections (id, name, query, color, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![
+            collection.id,
+            collection.name,
+            collection.query,
+            collection.color,
+            collection.created_at,
+        ],
+    )?;
+    Ok(())
+}
 
-        let icons = stmt
-            .query_map([], |row| {
-                let tags_str: String = row.get(4)?;
-                let tags: Vec<String> = if tags_str.is_empty() {
-                    vec![]
-                } else {
-                    tags_str.split(',').map(|s| s.trim().to_string()).collect()
-                };
+fn update_smart_collection(
+    conn: &Connection,
+    id: &str,
+    name: &str,
+    query: &str,
+) -> Result<Collection> {
+    conn.execute(
+        "UPDATE smart_collections SET name = ?1, query = ?2 WHERE id = ?3",
+        [name, query, id],
+    )?;
+    conn.query_row(
+        "SELECT id, name, query, color, created_at FROM smart_collections WHERE id = ?1",
+        [id],
+        |row| {
+            Ok(Collection {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                parent_id: None,
+                icon_count: 0,
+                color: row.get(3)?,
+                created_at: row.get(4)?,
+                query: Some(row.get(2)?),
+                sort_order: None,
+                total_icon_count: 0,
+            })
+        },
+    )
+    .optional()?
+    .ok_or_else(|| collection_not_found(id))
+}
 
-                Ok(Icon {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    path: row.get(2)?,
-                    svg_content: row.get(3)?,
-                    tags,
-                    collection_id: row.get(5)?,
-                    created_at: row.get(6)?,
-                    file_size: row.get(7)?,
-                    favorite: row.get::<_, i32>(8)? == 1,
-                })
-            })?
-            .filter_map(|r| r.ok())
-            .collect();
+/// Ids of a collection and all its subcollections.
+fn load_collections(conn: &Connection) -> Result<Vec<Collection>> {
+    // Totals add up the direct counts of each collection's whole subtree
+    let mut stmt = conn.prepare(
+        "WITH RECURSIVE tree(root, id) AS (
+            SELECT id, id FROM collections
+            UNION ALL
+            SELECT t.root, c.id FROM collections c JOIN tree t ON c.parent_id = t.id
+        )
+        SELECT c.id, c.name, c.parent_id, c.icon_count, c.color, c.created_at, c.sort_order,
+               SUM(d.icon_count)
+        FROM collections c
+        JOIN tree t ON t.root = c.id
+        JOIN collections d ON d.id = t.id
+        GROUP BY c.id
+        ORDER BY c.sort_order IS NULL, c.sort_order, c.name",
+    )?;
 
-        Ok(icons)
-    })
+    let mut collections: Vec<Collection> = stmt
+        .query_map([], |row| {
+            let parent_id: Option<String> = row.get(2)?;
+            Ok(Collection {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                parent_id,
+                icon_count: row.get(3)?,
+                color: row.get(4)?,
+                created_at: row.get(5)?,
+                query: None,
+                sort_order: row.get(6)?,
+                total_icon_count: row.get(7)?,
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut stmt = conn.prepare(
+        "SELECT id, name, query, color, created_at FROM smart_collections ORDER BY name",
+    )?;
+    let smart: Vec<Collection> = stmt
+        .query_map([], |row| {
+            Ok(Collection {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                parent_id: None,
+                icon_count: 0,
+                color: row.get(3)?,
+                created_at: row.get(4)?,
+                query: Some(row.get(2)?),
+                sort_order: None,
+                total_icon_count: 0,
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    // Smart collections are counted live, exactly as the search would
+    // list them, with one COUNT per collection
+    let thesaurus = load_thesaurus(conn)?;
+    for mut collection in smart {
+        if let Some(Ok(query)) = collection.query.as_deref().map(query::parse) {
+            collection.icon_count = count_matches(conn, &query, &thesaurus)? as i32;
+            collection.total_icon_count = collection.icon_count;
+        }
+        collections.push(collection);
+    }
+    Ok(collections)
 }
 
-pub fn insert_icon(app: &AppHandle, icon: &Icon) -> Result<()> {
-    with_db(app, |conn| {
-        let tags_str = icon.tags.join(",");
-        conn.execute(
-            "INSERT INTO icons (id, name, path, svg_content, tags, collection_id, created_at, file_size, favorite)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
-            rusqlite::params![
-                icon.id,
-                icon.name,
-                icon.path,
-                icon.svg_content,
-                tags_str,
-                icon.collection_id,
-                icon.created_at,
-                icon.file_size,
-                if icon.favorite { 1 } else { 0 },
-            ],
-        )?;
-        Ok(())
-    })
+fn new_collection(name: &str, parent_id: Option<&str>) -> Collection {
+    Collection {
+        id: Uuid::new_v4().to_string(),
+        name: name.to_string(),
+        parent_id: parent_id.map(str::to_string),
+        icon_count: 0,
+        color: random_color(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        query: None,
+        sort_order: None,
+        total_icon_count: 0,
+    }
 }
 
-pub fn delete_icon(app: &AppHandle, icon_id: &str) -> Result<()> {
-    with_db(app, |conn| {
-        conn.execute("DELETE FROM icons WHERE id = ?1", [icon_id])?;
-        Ok(())
-    })
+fn insert_collection(conn: &Connection, collection: &Collection) -> Result<()> {
+    conn.execute(
+        "INSERT INTO collections (id, name, parent_id, icon_count, color, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        rusqlite::params![
+            collection.id,
+            collection.name,
+            collection.parent_id,
+            collection.icon_count,
+            collection.color,
+            collection.created_at,
+        ],
+    )?;
+    Ok(())
+}
+
+fn collection_subtree(conn: &Connection, id: &str) -> Result<HashSet<String>> {
+    let mut stmt = conn.prepare(
+        "WITH RECURSIVE sub(id) AS (
+            SELECT ?1
+            UNION ALL
+            SELECT c.id FROM collections c JOIN sub s ON c.parent_id = s.id
+        )
+        SELECT id FROM sub",
+    )?;
+    let ids = stmt
+        .query_map([id], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(ids)
 }
 
-pub fn toggle_favorite(app: &AppHandle, icon_id: &str) -> Result<bool> {
-    with_db(app, |conn| {
+fn merge_collection_into(
+    conn: &Connection,
+    source: &str,
+    target: &str,
+    result: &mut MergeResult,
+    tracker: &mut Tracker,
+) -> Result<()> {
+    let children: Vec<(String, String)> = conn
+        .prepare("SELECT id, name FROM collections WHERE parent_id = ?1")?
+        .query_map([source], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for (child, name) in children {
+        let existing: Option<String> = conn
+            .query_row(
+                "SELECT id FROM collections WHERE parent_id = ?1 AND name = ?2 COLLATE NOCASE",
+                [target, name.as_str()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        match existing {
+            Some(same_name) => merge_collection_into(conn, &child, &same_name, result, tracker)?,
+            None => {
+                conn.execute(
+                    "UPDATE collections SET parent_id = ?1, sort_order = NULL WHERE id = ?2",
+                    [target, child.as_str()],
+                )?;
+            }
+        }
+    }
+
+    // Icons already in the target, by lowercased name
+    let mut names: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    let target_icons: Vec<(String, String, String)> = conn
+        .prepare("SELECT id, name, svg_content FROM icons WHERE collection_id = ?1")?
+        .query_map([target], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for (id, name, svg) in target_icons {
+        names
+            .entry(name.to_lowercase())
+            .or_default()
+            .push((id, svg));
+    }
+
+    let source_icons: Vec<(String, String, String)> = conn
+        .prepare("SELECT id, name, svg_content FROM icons WHERE collection_id = ?1")?
+        .query_map([source], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for (id, name, svg) in source_icons {
+        let same_name = names.get(&name.to_lowercase());
+        let duplicate = same_name.and_then(|icons| icons.iter().find(|(_, s)| *s == svg));
+        if let Some((kept, _)) = duplicate {
+            // Same name and content: keep one, with the tags and favorite of both
+            conn.execute(
+                "INSERT OR IGNORE INTO icon_tags (icon_id, tag_id)
+                 SELECT ?1, tag_id FROM icon_tags WHERE icon_id = ?2 ORDER BY rowid",
+                [kept.as_str(), id.as_str()],
+            )?;
+            conn.execute(
+                "UPDATE icons SET favorite = MAX(favorite, (SELECT favorite FROM icons WHERE id = ?2))
+                 WHERE id = ?1",
+                [kept.as_str(), id.as_str()],
+            )?;
+            let trash_id = trash_icon(conn, &id)?;
+            tracker.trashed("icon", &id, trash_id);
+            result.duplicates_merged += 1;
+            continue;
+        }
+
+        let new_name = if same_name.is_some() {
+            let unique = (2..)
+                .map(|n| format!("{} ({})", name, n))
+                .find(|candidate| !names.contains_key(&candidate.to_lowercase()))
+                .unwrap_or_default();
+            result.icons_renamed += 1;
+            unique
+        } else {
+            name
+        };
         conn.execute(
-            "UPDATE icons SET favorite = 1 - favorite WHERE id = ?1",
-            [icon_id],
+            "UPDATE icons SET collection_id = ?1, name = ?2 WHERE id = ?3",
+            [target, new_name.as_str(), id.as_str()],
         )?;
+        names
+            .entry(new_name.to_lowercase())
+            .or_default()
+            .push((id, svg));
+        result.icons_moved += 1;
+    }
 
-        let mut stmt = conn.prepare("SELECT favorite FROM icons WHERE id = ?1")?;
-        let favorite: i32 = stmt.query_row([icon_id], |row| row.get(0))?;
-        Ok(favorite == 1)
-    })
+    conn.execute("DELETE FROM collections WHERE id = ?1", [source])?;
+    result.collections_merged += 1;
+    Ok(())
 }
 
-pub fn update_icon_tags(app: &AppHandle, icon_id: &str, tags: &[String]) -> Result<()> {
-    with_db(app, |conn| {
-        let tags_str = tags.join(",");
-        conn.execute(
-            "UPDATE icons SET tags = ?1 WHERE id = ?2",
-            [&tags_str, icon_id],
-        )?;
-        Ok(())
-    })
+fn random_color() -> String {
+    let colors = [
+        "#6366f1", "#8b5cf6", "#a855f7", "#ec4899", "#f43f5e", "#f97316", "#eab308", "#22c55e",
+        "#14b8a6", "#3b82f6",
+    ];
+    colors[rand::random::<usize>() % colors.len()].to_string()
 }
 
-// ── Settings ─────────────────────────────────────────────────
+// ── Icons ────────────────────────────────────────────────────
 
-pub fn get_settings(app: &AppHandle) -> Result<Settings> {
-    with_db(app, |conn| {
-        let get = |key: &str, default: &str| -> String {
-            conn.prepare("SELECT value FROM settings WHERE key = ?1")
-                .and_then(|mut s| s.query_row([key], |row| row.get(0)))
-                .unwrap_or_else(|_| default.to_string())
-        };
+impl Library {
+    pub fn get_icons_by_collection(&self, collection_id: &str) -> Result<Vec<Icon>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM icons i WHERE i.collection_id = ?1 ORDER BY i.name",
+                ICON_COLUMNS
+            ))?;
 
-        Ok(Settings {
-            theme: get("theme", "light"),
-            icon_size: get("icon_size", "64").parse().unwrap_or(64),
-            tint_color: {
-                let v = get("tint_color", "");
-                if v.is_empty() { None } else { Some(v) }
-            },
+            let icons = stmt
+                .query_map([collection_id], row_to_icon)?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            Ok(icons)
         })
-    })
-}
+    }
 
-pub fn save_setting(app: &AppHandle, key: &str, value: &str) -> Result<()> {
-    with_db(app, |conn| {
-        conn.execute(
-            "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
-            [key, value],
-        )?;
-        Ok(())
-    })
-}
+    pub fn get_all_icons(&self) -> Result<Vec<Icon>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM icons i ORDER BY i.name",
+                ICON_COLUMNS
+            ))?;
 
-// ── Stats ────────────────────────────────────────────────────
+            let icons = stmt
+                .query_map([], row_to_icon)?
+                .filter_map(|r| r.ok())
+                .collect();
 
-pub fn get_total_icon_count(app: &AppHandle) -> Result<i32> {
-    with_db(app, |conn| {
-        let mut stmt = conn.prepare("SELECT COUNT(*) FROM icons")?;
-        let count: i32 = stmt.query_row([], |row| row.get(0))?;
-        Ok(count)
-    })
-}
+            Ok(icons)
+        })
+    }
 
-pub fn get_favorite_count(app: &AppHandle) -> Result<i32> {
-    with_db(app, |conn| {
-        let mut stmt = conn.prepare("SELECT COUNT(*) FROM icons WHERE favorite = 1")?;
-        let count: i32 = stmt.query_row([], |row| row.get(0))?;
-        Ok(count)
-    })
+    pub fn get_favorite_icons(&self) -> Result<Vec<Icon>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM icons i WHERE i.favorite = 1 ORDER BY i.name",
+                ICON_COLUMNS
+            ))?;
+
+            let icons = stmt
+                .query_map([], row_to_icon)?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            Ok(icons)
+        })
+    }
+
+    /// One page of icons without their SVG bodies, ordered by name. Pass the
+    /// returned `next_cursor` back to get the following page; the cursor
+    /// stays valid while icons are added or removed around it.
+    pub fn list_icons(
+        &self,
+        collection_id: Option<&str>,
+        favorites_only: bool,
+        cursor: Option<&str>,
+        limit: i64,
+    ) -> Result<IconPage> {
+        let mut filters: Vec<&str> = Vec::new();
+        let mut params: Vec<Value> = Vec::new();
+        if let Some(collection_id) = collection_id {
+            filters.push("i.collection_id = ?");
+            params.push(Value::Text(collection_id.to_string()));
+        }
+        if favorites_only {
+            filters.push("i.favorite = 1");
+        }
+        let scope = if filters.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", filters.join(" AND "))
+        };
+
+        let after = cursor.map(decode_cursor).transpose()?;
+        let limit = limit.clamp(1, MAX_PAGE_SIZE);
+
+        self.with_conn(|conn| {
+            let total: i64 = conn.query_row(
+                &format!("SELECT COUNT(*) FROM icons i {}", scope),
+                rusqlite::params_from_iter(params.iter()),
+                |row| row.get(0),
+            )?;
+
+            let mut page_params = params.clone();
+            let mut page_filters = filters.clone();
+            if let Some((name, id)) = after {
+                page_filters.push("(i.name, i.id) > (?, ?)");
+                page_params.push(Value::Text(name));
+                page_params.push(Value::Text(id));
+            }
+            let page_scope = if page_filters.is_empty() {
+                String::new()
+            } else {
+                format!("WHERE {}", page_filters.join(" AND "))
+            };
+            // One extra row tells whether there is a next page
+            page_params.push(Value::Integer(limit + 1));
+
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM icons i {} ORDER BY i.name, i.id LIMIT ?",
+                SUMMARY_COLUMNS, page_scope
+            ))?;
+            let mut icons: Vec<IconSummary> = stmt
+                .query_map(rusqlite::params_from_iter(page_params), row_to_summary)?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            let next_cursor = if icons.len() as i64 > limit {
+                icons.truncate(limit as usize);
+                icons.last().map(|last| encode_cursor(&last.name, &last.id))
+            } else {
+                None
+            };
+            Ok(IconPage {
+                icons,
+                total,
+                next_cursor,
+            })
+        })
+    }
+
+    /// SVG bodies of the given icons, in the order asked for. Unknown ids
+    /// are left out.
+    pub fn get_icon_svgs(&self, icon_ids: &[String]) -> Result<Vec<IconSvg>> {
+        self.with_conn(|conn| {
+            let mut found: HashMap<String, String> = HashMap::new();
+            for chunk in icon_ids.chunks(MAX_PAGE_SIZE as usize) {
+                let placeholders = vec!["?"; chunk.len()].join(", ");
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT id, svg_content FROM icons WHERE id IN ({})",
+                    placeholders
+                ))?;
+                let rows = stmt
+                    .query_map(rusqlite::params_from_iter(chunk), |row| {
+                        Ok((row.get(0)?, row.get(1)?))
+                    })?
+                    .filter_map(|r| r.ok());
+                found.extend(rows);
+            }
+
+            Ok(icon_ids
+                .iter()
+                .filter_map(|id| {
+                    found.remove(id).map(|svg_content| IconSvg {
+                        id: id.clone(),
+                        svg_content,
+                    })
+                })
+                .collect())
+        })
+    }
+
+    pub fn insert_icon(&self, icon: &Icon) -> Result<()> {
+        // Rasterizing is the slow part, keep it outside the lock
+        let fingerprint = visual::fingerprint(&icon.svg_content);
+        self.with_conn(|conn| insert_icon(conn, icon, fingerprint.as_ref()))
+    }
+
+    /// Moves an icon to the trash.
+    pub fn delete_icon(&self, icon_id: &str) -> Result<()> {
+        self.tracked("Delete icon", |conn, tracker| {
+            let trash_id = trash_icon(conn, icon_id)?;
+            tracker.trashed("icon", icon_id, trash_id);
+            Ok(())
+        })
+    }
+
+    /// Moves an icon to another collection, keeping both collections' counts
+    /// right. Returns the updated icon.
+    pub fn move_icon(&self, icon_id: &str, collection_id: &str) -> Result<Icon> {
+        self.tracked("Move icon", |conn, tracker| {
+            tracker.icons(conn, [icon_id])?;
+            move_icon_to(conn, icon_id, collection_id)?;
+            get_icon(conn, icon_id)
+        })
+    }
+
+    /// Copies an icon into a collection. Returns the new icon.
+    pub fn copy_icon(&self, icon_id: &str, collection_id: &str) -> Result<Icon> {
+        self.tracked("Copy icon", |conn, tracker| {
+            let new_id = copy_icon_to(
+                conn,
+                icon_id,
+                collection_id,
+                &chrono::Utc::now().to_rfc3339(),
+            )?;
+            tracker.added("icon", &new_id);
+            get_icon(conn, &new_id)
+        })
+    }
+
+    pub fn toggle_favorite(&self, icon_id: &str) -> Result<bool> {
+        self.tracked("Change favorite", |conn, tracker| {
+            tracker.icons(conn, [icon_id])?;
+            conn.execute(
+                "UPDATE icons SET favorite = 1 - favorite WHERE id = ?1",
+                [icon_id],
+            )?;
+
+            let mut stmt = conn.prepare("SELECT favorite FROM icons WHERE id = ?1")?;
+            let favorite: i32 = stmt.query_row([icon_id], |row| row.get(0))?;
+            Ok(favorite == 1)
+        })
+    }
+
+    pub fn update_icon_tags(&self, icon_id: &str, tags: &[String]) -> Result<()> {
+        self.tracked("Edit tags", |conn, tracker| {
+            tracker.icons(conn, [icon_id])?;
+            tracker.tags(conn)?;
+            set_icon_tags(conn, icon_id, tags)
+        })
+    }
+
+    /// Runs a search-bar query: free text plus `field:value` filters (see
+    /// `query.rs` for the syntax). With `exact`, text that finds nothing
+    /// finds nothing, as smart collections need, instead of falling back to
+    /// fuzzy name matches.
+    pub fn search_icons(
+        &self,
+        query: &str,
+        collection_id: Option<&str>,
+        favorites_only: bool,
+        exact: bool,
+        limit: i64,
+        offset: i64,
+    ) -> Result<SearchResults> {
+        let query = query::parse(query)?;
+        self.search(&query, collection_id, favorites_only, exact, limit, offset)
+    }
+
+    /// Completions for the search bar: field names, tags and collections.
+    pub fn suggest_search(&self, query: &str) -> Result<Vec<query::Suggestion>> {
+        let tags = self.get_tag_names()?;
+        let collections: Vec<String> = self
+            .get_all_collections()?
+            .into_iter()
+            .filter(|c| c.query.is_none())
+            .map(|c| c.name)
+            .collect();
+        Ok(query::suggest(query, &tags, &collections))
+    }
+
+    /// Searches icons with a parsed structured query. Free-text terms go through
+    /// the FTS5 index over names, tags, collection names, SVG `<title>`/`<desc>`
+    /// and source paths (prefix-matched, ranked by BM25 with name and tag hits
+    /// weighted highest); field filters are applied as plain SQL conditions.
+    ///
+    /// When the text finds nothing in the index (typos, `ArrowLeft` vs
+    /// `arrow left`), icon names are matched fuzzily instead and the results are
+    /// flagged with `fuzzy: true`, unless `exact` is set.
+    fn search(
+        &self,
+        query: &Query,
+        collection_id: Option<&str>,
+        favorites_only: bool,
+        exact: bool,
+        limit: i64,
+        offset: i64,
+    ) -> Result<SearchResults> {
+        let (scope, scope_params) = search_scope(query, collection_id, favorites_only);
+        let text = query.text.join(" ");
+
+        self.with_conn(|conn| {
+            let expr = search::match_expression_with(&text, &load_thesaurus(conn)?);
+            let (from, order) = search_source(expr.is_some());
+            let params: Vec<Value> = expr
+                .iter()
+                .cloned()
+                .map(Value::Text)
+                .chain(scope_params.iter().cloned())
+                .collect();
+
+            let total: i64 = conn.query_row(
+                &format!("SELECT COUNT(*) {}{}", from, scope),
+                rusqlite::params_from_iter(params.iter()),
+                |row| row.get(0),
+            )?;
+
+            if total == 0 && expr.is_some() && !exact {
+                return self.fuzzy_search(conn, &text, &scope, &scope_params, limit, offset);
+            }
+
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {}
+                 {}{}
+                 ORDER BY {}
+                 LIMIT ? OFFSET ?",
+                ICON_COLUMNS, from, scope, order
+            ))?;
+
+            let icons = stmt
+                .query_map(
+                    rusqlite::params_from_iter(
+                        params
+                            .iter()
+                            .cloned()
+                            .chain([Value::Integer(limit), Value::Integer(offset)]),
+                    ),
+                    row_to_icon,
+                )?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            Ok(SearchResults {
+                icons,
+                total,
+                fuzzy: false,
+            })
+        })
+    }
+
+    fn fuzzy_search(
+        &self,
+        conn: &Connection,
+        text: &str,
+        scope: &str,
+        scope_params: &[Value],
+        limit: i64,
+        offset: i64,
+    ) -> Result<SearchResults> {
+        let matches = self.fuzzy_matches(conn, text, FUZZY_CANDIDATES)?;
+        if matches.is_empty() {
+            return Ok(SearchResults {
+                icons: vec![],
+                total: 0,
+                fuzzy: true,
+            });
+        }
+
+        let placeholders = vec!["?"; matches.len()].join(", ");
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM icons i WHERE i.id IN ({}){}",
+            ICON_COLUMNS, placeholders, scope
+        ))?;
+        let params = matches
+            .iter()
+            .map(|m| Value::Text(m.id.clone()))
+            .chain(scope_params.iter().cloned());
+        let mut found: HashMap<String, Icon> = stmt
+            .query_map(rusqlite::params_from_iter(params), row_to_icon)?
+            .filter_map(|r| r.ok())
+            .map(|icon| (icon.id.clone(), icon))
+            .collect();
+
+        // Keep the fuzzy ranking order
+        let total = found.len() as i64;
+        let icons = matches
+            .iter()
+            .filter_map(|m| found.remove(&m.id))
+            .skip(offset.max(0) as usize)
+            .take(limit.max(0) as usize)
+            .collect();
+
+        Ok(SearchResults {
+            icons,
+            total,
+            fuzzy: true,
+        })
+    }
+
+    /// Looks up `text` in the fuzzy name index, rebuilding the index first if
+    /// anything was written since it was built.
+    fn fuzzy_matches(
+        &self,
+        conn: &Connection,
+        text: &str,
+        limit: usize,
+    ) -> Result<Vec<FuzzyMatch>> {
+        let mut cache = self.fuzzy.lock().unwrap();
+
+        let version = conn.total_changes();
+        if !matches!(&*cache, Some((v, _)) if *v == version) {
+            let mut stmt = conn.prepare("SELECT id, name FROM icons")?;
+            let names: Vec<(String, String)> = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .filter_map(|r| r.ok())
+                .collect();
+            *cache = Some((version, FuzzyIndex::build(names)));
+        }
+
+        Ok(cache
+            .as_ref()
+            .map(|(_, index)| index.search(text, limit))
+            .unwrap_or_default())
+    }
+}
+fn insert_icon(
+    conn: &Connection,
+    icon: &Icon,
+    fingerprint: Option<&visual::Fingerprint>,
+) -> Result<()> {
+    let text = svg::extract_text(&icon.svg_content);
+    conn.execute(
+        "INSERT INTO icons (id, name, path, svg_content, collection_id, created_at, file_size, favorite, title, description)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        rusqlite::params![
+            icon.id,
+            icon.name,
+            icon.path,
+            icon.svg_content,
+            icon.collection_id,
+            icon.created_at,
+            icon.file_size,
+            if icon.favorite { 1 } else { 0 },
+            text.title,
+            text.description,
+        ],
+    )?;
+    set_icon_tags(conn, &icon.id, &icon.tags)?;
+    save_fingerprint(conn, &icon.id, fingerprint)?;
+    save_palette(conn, &icon.id, &palette::extract(&icon.svg_content))?;
+    let revision_id = add_revision(
+        conn,
+        &icon.id,
+        &icon.svg_content,
+        "import",
+        &icon.created_at,
+    )?;
+    conn.execute(
+        "UPDATE icons SET revision_id = ?1 WHERE id = ?2",
+        rusqlite::params![revision_id, icon.id],
+    )?;
+    Ok(())
+}
+
+fn get_icon(conn: &Connection, icon_id: &str) -> Result<Icon> {
+    conn.query_row(
+        &format!("SELECT {} FROM icons i WHERE i.id = ?1", ICON_COLUMNS),
+        [icon_id],
+        row_to_icon,
+    )
+    .optional()?
+    .ok_or_else(|| icon_not_found(icon_id))
+}
+
+fn icon_not_found(icon_id: &str) -> Error {
+    Error::not_found(format!("Icon '{}' not found", icon_id)).with_icon(icon_id)
+}
+
+fn collection_not_found(collection_id: &str) -> Error {
+    Error::not_found(format!("Collection '{}' not found", collection_id))
+        .with_collection(collection_id)
+}
+
+/// Columns read by `row_to_icon`, selected from `icons i`. Tags come back
+/// joined with `TAG_SEPARATOR` in the order they were added.
+const ICON_COLUMNS: &str = "i.id, i.name, i.path, i.svg_content,
+    (SELECT group_concat(t.name, char(31) ORDER BY it.rowid) FROM icon_tags it
+     JOIN tags t ON t.id = it.tag_id WHERE it.icon_id = i.id),
+    i.collection_id, i.created_at, i.file_size, i.favorite";
+
+const TAG_SEPARATOR: char = '\u{1f}';
+
+/// Like `ICON_COLUMNS` without the SVG body.
+const SUMMARY_COLUMNS: &str = "i.id, i.name, i.path,
+    (SELECT group_concat(t.name, char(31) ORDER BY it.rowid) FROM icon_tags it
+     JOIN tags t ON t.id = it.tag_id WHERE it.icon_id = i.id),
+    i.collection_id, i.created_at, i.file_size, i.favorite";
+
+/// Largest page `list_icons` returns, and the batch size of `get_icon_svgs`.
+const MAX_PAGE_SIZE: i64 = 1000;
+
+fn row_to_summary(row: &rusqlite::Row) -> rusqlite::Result<IconSummary> {
+    let tags: Vec<String> = row
+        .get::<_, Option<String>>(3)?
+        .map(|t| t.split(TAG_SEPARATOR).map(|s| s.to_string()).collect())
+        .unwrap_or_default();
+
+    Ok(IconSummary {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        path: row.get(2)?,
+        tags,
+        collection_id: row.get(4)?,
+        created_at: row.get(5)?,
+        file_size: row.get(6)?,
+        favorite: row.get::<_, i32>(7)? == 1,
+    })
+}
+
+/// Page cursors are the `(name, id)` of the last icon, JSON-encoded. The
+/// frontend treats them as opaque strings.
+fn encode_cursor(name: &str, id: &str) -> String {
+    serde_json::to_string(&(name, id)).unwrap_or_default()
+}
+
+fn decode_cursor(cursor: &str) -> Result<(String, String)> {
+    serde_json::from_str(cursor).map_err(|_| Error::invalid("Invalid page cursor"))
+}
+
+fn row_to_icon(row: &rusqlite::Row) -> rusqlite::Result<Icon> {
+    let tags: Vec<String> = row
+        .get::<_, Option<String>>(4)?
+        .map(|t| t.split(TAG_SEPARATOR).map(|s| s.to_string()).collect())
+        .unwrap_or_default();
+
+    Ok(Icon {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        path: row.get(2)?,
+        svg_content: row.get(3)?,
+        tags,
+        collection_id: row.get(5)?,
+        created_at: row.get(6)?,
+        file_size: row.get(7)?,
+        favorite: row.get::<_, i32>(8)? == 1,
+    })
+}
+
+/// Conditions (appended to a `WHERE`) that narrow a search to a collection,
+/// to favorites and to the query's field filters.
+fn search_scope(
+    query: &Query,
+    collection_id: Option<&str>,
+    favorites_only: bool,
+) -> (String, Vec<Value>) {
+    let mut scope = String::new();
+    let mut params: Vec<Value> = Vec::new();
+    if let Some(collection_id) = collection_id {
+        scope.push_str(" AND i.collection_id = ?");
+        params.push(Value::Text(collection_id.to_string()));
+    }
+    if favorites_only {
+        scope.push_str(" AND i.favorite = 1");
+    }
+    let filter = query.sql_filter();
+    scope.push_str(&format!(" AND ({})", filter.sql));
+    params.extend(filter.params);
+    (scope, params)
+}
+
+/// The `FROM ... WHERE` clause and ordering of a search, going through the
+/// full-text index when the query has text to match (bound as the first
+/// parameter).
+fn search_source(has_text: bool) -> (&'static str, &'static str) {
+    if has_text {
+        (
+            "FROM icons_fts f JOIN icons i ON i.rowid = f.rowid WHERE icons_fts MATCH ?",
+            "bm25(icons_fts, 10.0, 5.0, 2.0, 3.0, 1.0, 0.5), i.name",
+        )
+    } else {
+        ("FROM icons i WHERE 1", "i.name")
+    }
+}
+
+/// Counts the icons a query matches exactly, without loading them.
+fn count_matches(conn: &Connection, query: &Query, thesaurus: &Thesaurus) -> Result<i64> {
+    let (scope, scope_params) = search_scope(query, None, false);
+    let expr = search::match_expression_with(&query.text.join(" "), thesaurus);
+    let (from, _) = search_source(expr.is_some());
+    let params = expr.map(Value::Text).into_iter().chain(scope_params);
+    Ok(conn.query_row(
+        &format!("SELECT COUNT(*) {}{}", from, scope),
+        rusqlite::params_from_iter(params),
+        |row| row.get(0),
+    )?)
+}
+
+/// Most fuzzy candidates considered before applying the query's filters.
+const FUZZY_CANDIDATES: usize = 2000;
+
+// ── Bulk operations ──────────────────────────────────────────
+//
+// Multi-select actions run over a list of icon ids in a single transaction,
+// so the lock is taken once and the batch applies completely or not at all.
+// Ids that don't match an icon are reported back instead of failing it. The
+// whole batch is one history entry, so it is undone in one step.
+
+impl Library {
+    /// Runs `op` on each distinct existing icon in one transaction, logged
+    /// under `label`. `op` returns whether it changed that icon.
+    fn bulk<F>(&self, label: &str, icon_ids: &[String], mut op: F) -> Result<BulkResult>
+    where
+        F: FnMut(&Connection, &str, &mut Tracker) -> Result<bool>,
+    {
+        self.tracked(label, |conn, tracker| {
+            let mut result = BulkResult::default();
+            let mut seen = HashSet::new();
+            for id in icon_ids {
+                if !seen.insert(id.as_str()) {
+                    continue;
+                }
+                let exists = conn
+                    .query_row("SELECT 1 FROM icons WHERE id = ?1", [id], |_| Ok(()))
+                    .optional()?
+                    .is_some();
+                if !exists {
+                    result.missing.push(id.clone());
+                    continue;
+                }
+                result.processed += 1;
+                tracker.icons(conn, [id.as_str()])?;
+                if op(conn, id, tracker)? {
+                    result.changed += 1;
+                }
+            }
+            Ok(result)
+        })
+    }
+
+    pub fn bulk_add_tags(&self, icon_ids: &[String], tags: &[String]) -> Result<BulkResult> {
+        self.bulk("Add tags", icon_ids, |conn, id, tracker| {
+            tracker.tags(conn)?;
+            Ok(add_icon_tags(conn, id, tags)? > 0)
+        })
+    }
+
+    /// Removes the given tags (exact paths, case-insensitive) from the icons.
+    pub fn bulk_remove_tags(&self, icon_ids: &[String], tags: &[String]) -> Result<BulkResult> {
+        let tags = clean_tags(tags);
+        self.bulk("Remove tags", icon_ids, |conn, id, _| {
+            let mut removed = 0;
+            for tag in &tags {
+                removed += conn.execute(
+                    "DELETE FROM icon_tags
+                     WHERE icon_id = ?1 AND tag_id IN (SELECT id FROM tags WHERE name = ?2)",
+                    [id, tag.as_str()],
+                )?;
+            }
+            Ok(removed > 0)
+        })
+    }
+
+    pub fn bulk_set_favorite(&self, icon_ids: &[String], favorite: bool) -> Result<BulkResult> {
+        let label = if favorite {
+            "Add to favorites"
+        } else {
+            "Remove from favorites"
+        };
+        let favorite = if favorite { 1 } else { 0 };
+        self.bulk(label, icon_ids, |conn, id, _| {
+            let updated = conn.execute(
+                "UPDATE icons SET favorite = ?2 WHERE id = ?1 AND favorite != ?2",
+                rusqlite::params![id, favorite],
+            )?;
+            Ok(updated > 0)
+        })
+    }
+
+    pub fn bulk_move_icons(&self, icon_ids: &[String], collection_id: &str) -> Result<BulkResult> {
+        self.bulk("Move icons", icon_ids, |conn, id, _| {
+            move_icon_to(conn, id, collection_id)
+        })
+    }
+
+    /// Duplicates icons into a collection, along with their tags and derived
+    /// data (fingerprints, palettes, embeddings). New ids are returned in
+    /// `created_ids`, in request order.
+    pub fn bulk_copy_icons(&self, icon_ids: &[String], collection_id: &str) -> Result<BulkResult> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let mut created_ids = Vec::new();
+        let mut result = self.bulk("Copy icons", icon_ids, |conn, id, tracker| {
+            let new_id = copy_icon_to(conn, id, collection_id, &now)?;
+            tracker.added("icon", &new_id);
+            created_ids.push(new_id);
+            Ok(true)
+        })?;
+        result.created_ids = created_ids;
+        Ok(result)
+    }
+
+    /// Moves the icons to the trash, each as its own entry.
+    pub fn bulk_delete_icons(&self, icon_ids: &[String]) -> Result<BulkResult> {
+        self.bulk("Delete icons", icon_ids, |conn, id, tracker| {
+            let trash_id = trash_icon(conn, id)?;
+            tracker.trashed("icon", id, trash_id);
+            Ok(true)
+        })
+    }
+}
+fn icon_collection(conn: &Connection, icon_id: &str) -> Result<String> {
+    conn.query_row(
+        "SELECT collection_id FROM icons WHERE id = ?1",
+        [icon_id],
+        |row| row.get(0),
+    )
+    .optional()?
+    .ok_or_else(|| icon_not_found(icon_id))
+}
+
+fn ensure_collection_exists(conn: &Connection, collection_id: &str) -> Result<()> {
+    conn.query_row(
+        "SELECT 1 FROM collections WHERE id = ?1",
+        [collection_id],
+        |_| Ok(()),
+    )
+    .optional()?
+    .ok_or_else(|| collection_not_found(collection_id))
+}
+
+/// Returns false if the icon already was in `collection_id`.
+fn move_icon_to(conn: &Connection, icon_id: &str, collection_id: &str) -> Result<bool> {
+    ensure_collection_exists(conn, collection_id)?;
+    let source = icon_collection(conn, icon_id)?;
+    if source == collection_id {
+        return Ok(false);
+    }
+    conn.execute(
+        "UPDATE icons SET collection_id = ?1 WHERE id = ?2",
+        [collection_id, icon_id],
+    )?;
+    Ok(true)
+}
+
+/// Duplicates an icon with its tags, derived data and revisions up to the
+/// current one. Returns the new id.
+fn copy_icon_to(
+    conn: &Connection,
+    icon_id: &str,
+    collection_id: &str,
+    created_at: &str,
+) -> Result<String> {
+    ensure_collection_exists(conn, collection_id)?;
+    let new_id = Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO icons (id, name, path, svg_content, collection_id, created_at, file_size, favorite, title, description)
+         SELECT ?1, name, path, svg_content, ?2, ?3, file_size, favorite, title, description
+         FROM icons WHERE id = ?4",
+        [new_id.as_str(), collection_id, created_at, icon_id],
+    )?;
+    conn.execute(
+        "INSERT INTO icon_tags (icon_id, tag_id)
+         SELECT ?1, tag_id FROM icon_tags WHERE icon_id = ?2 ORDER BY rowid",
+        [&new_id, icon_id],
+    )?;
+    conn.execute(
+        "INSERT INTO icon_visuals (icon_id, phash, shape)
+         SELECT ?1, phash, shape FROM icon_visuals WHERE icon_id = ?2",
+        [&new_id, icon_id],
+    )?;
+    conn.execute(
+        "INSERT INTO icon_colors (icon_id, position, r, g, b)
+         SELECT ?1, position, r, g, b FROM icon_colors WHERE icon_id = ?2",
+        [&new_id, icon_id],
+    )?;
+    conn.execute(
+        "INSERT INTO icon_embeddings (icon_id, model, source, vector)
+         SELECT ?1, model, source, vector FROM icon_embeddings WHERE icon_id = ?2",
+        [&new_id, icon_id],
+    )?;
+    conn.execute(
+        "INSERT INTO icon_revisions (icon_id, svg_content, reason, created_at)
+         SELECT ?1, r.svg_content, r.reason, r.created_at
+         FROM icon_revisions r JOIN icons i ON i.id = r.icon_id
+         WHERE r.icon_id = ?2 AND r.id <= i.revision_id ORDER BY r.id",
+        [&new_id, icon_id],
+    )?;
+    conn.execute(
+        "UPDATE icons SET revision_id = (SELECT MAX(id) FROM icon_revisions WHERE icon_id = ?1)
+         WHERE id = ?1",
+        [&new_id],
+    )?;
+    Ok(new_id)
+}
+
+// ── Tags ─────────────────────────────────────────────────────
+//
+// Tags form a hierarchy written as paths, e.g. `category/navigation`. A tag's
+// `name` holds its full path (unique, case-insensitive) and `parent_id`
+// points at the tag for the parent path, which always exists.
+
+/// Normalizes a tag path: segments trimmed, empty segments dropped.
+fn normalize_tag_path(tag: &str) -> String {
+    tag.split('/')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn parent_path(path: &str) -> Option<&str> {
+    path.rsplit_once('/').map(|(parent, _)| parent)
+}
+
+fn leaf_name(path: &str) -> &str {
+    path.rsplit_once('/').map_or(path, |(_, leaf)| leaf)
+}
+
+/// True if `path` is `ancestor` itself or lies below it (case-insensitive).
+fn is_within(path: &str, ancestor: &str) -> bool {
+    let (path, ancestor) = (path.to_lowercase(), ancestor.to_lowercase());
+    path == ancestor || path.starts_with(&format!("{}/", ancestor))
+}
+
+/// Cleans up user-entered tag paths: normalized, non-empty, without
+/// case-insensitive duplicates, in their original order.
+fn clean_tags(tags: &[String]) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = normalize_tag_path(tag);
+        if !tag.is_empty() && !out.iter().any(|t| t.to_lowercase() == tag.to_lowercase()) {
+            out.push(tag);
+        }
+    }
+    out
+}
+
+/// Adds tags to an icon, keeping the ones it already has. Returns how many
+/// were actually new.
+fn add_icon_tags(conn: &Connection, icon_id: &str, tags: &[String]) -> Result<usize> {
+    let mut added = 0;
+    for tag in clean_tags(tags) {
+        let tag_id = ensure_tag(conn, &tag)?;
+        added += conn.execute(
+            "INSERT OR IGNORE INTO icon_tags (icon_id, tag_id) VALUES (?1, ?2)",
+            rusqlite::params![icon_id, tag_id],
+        )?;
+    }
+    Ok(added)
+}
+
+/// Returns the id of the tag at `path` (case-insensitive), creating it and
+/// any missing ancestors. New tags are named below their parent as it is
+/// stored, so `style/outline` under an existing `Style` becomes
+/// `Style/outline`.
+fn ensure_tag(conn: &Connection, path: &str) -> Result<i64> {
+    let mut parent: Option<i64> = None;
+    let mut current = String::new();
+    for segment in normalize_tag_path(path)
+        .split('/')
+        .filter(|s| !s.is_empty())
+    {
+        if !current.is_empty() {
+            current.push('/');
+        }
+        current.push_str(segment);
+        conn.execute(
+            "INSERT OR IGNORE INTO tags (name, parent_id) VALUES (?1, ?2)",
+            rusqlite::params![current, parent],
+        )?;
+        let (id, name) = conn.query_row(
+            "SELECT id, name FROM tags WHERE name = ?1",
+            [&current],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        parent = Some(id);
+        current = name;
+    }
+    parent.ok_or_else(|| Error::invalid("Tag name cannot be empty"))
+}
+
+/// Replaces the tags of an icon. Callers wrap this in a transaction.
+fn set_icon_tags(conn: &Connection, icon_id: &str, tags: &[String]) -> Result<()> {
+    conn.execute("DELETE FROM icon_tags WHERE icon_id = ?1", [icon_id])?;
+    add_icon_tags(conn, icon_id, tags)?;
+    Ok(())
+}
+
+/// Gives tags created before the hierarchy existed the parent their path
+/// names.
+fn link_tag_parents(conn: &Connection) -> Result<()> {
+    let orphans: Vec<(i64, String)> = conn
+        .prepare("SELECT id, name FROM tags WHERE parent_id IS NULL AND name LIKE '%/%'")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .filter_map(|r| r.ok())
+        .collect();
+    for (id, name) in orphans {
+        if let Some(parent) = parent_path(&name) {
+            let parent_id = ensure_tag(conn, parent)?;
+            conn.execute(
+                "UPDATE tags SET parent_id = ?1 WHERE id = ?2",
+                [parent_id, id],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn tag_path(conn: &Connection, id: i64) -> Result<String> {
+    conn.query_row("SELECT name FROM tags WHERE id = ?1", [id], |row| {
+        row.get(0)
+    })
+    .optional()?
+    .ok_or_else(|| Error::not_found(format!("Tag {} not found", id)).with_tag(id))
+}
+
+fn subtags(conn: &Connection, id: i64) -> Result<Vec<(i64, String)>> {
+    let mut stmt = conn.prepare("SELECT id, name FROM tags WHERE parent_id = ?1")?;
+    let tags = stmt
+        .query_map([id], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(tags)
+}
+
+impl Library {
+    pub fn get_tags(&self) -> Result<Vec<Tag>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT t.id, t.name, t.parent_id, t.color,
+                        (SELECT COUNT(*) FROM icon_tags it WHERE it.tag_id = t.id),
+                        (SELECT COUNT(DISTINCT it.icon_id) FROM icon_tags it
+                         JOIN tags d ON d.id = it.tag_id
+                         WHERE d.id = t.id OR substr(d.name, 1, length(t.name) + 1) = t.name || '/' COLLATE NOCASE)
+                 FROM tags t
+                 ORDER BY t.name COLLATE NOCASE",
+            )?;
+            let tags = stmt
+                .query_map([], |row| {
+                    Ok(Tag {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        parent_id: row.get(2)?,
+                        color: row.get(3)?,
+                        icon_count: row.get(4)?,
+                        total_count: row.get(5)?,
+                    })
+                })?
+                .filter_map(|r| r.ok())
+                .collect();
+            Ok(tags)
+        })
+    }
+
+    /// All tags nested under their parents, siblings sorted by name.
+    pub fn get_tag_tree(&self) -> Result<Vec<TagNode>> {
+        fn build(
+            parent: Option<i64>,
+            children: &mut HashMap<Option<i64>, Vec<Tag>>,
+        ) -> Vec<TagNode> {
+            children
+                .remove(&parent)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|tag| TagNode {
+                    label: leaf_name(&tag.name).to_string(),
+                    children: build(Some(tag.id), children),
+                    tag,
+                })
+                .collect()
+        }
+
+        let mut children: HashMap<Option<i64>, Vec<Tag>> = HashMap::new();
+        for tag in self.get_tags()? {
+            children.entry(tag.parent_id).or_default().push(tag);
+        }
+        Ok(build(None, &mut children))
+    }
+
+    /// Paths of the tags in use, plus their ancestors, sorted case-insensitively.
+    pub fn get_tag_names(&self) -> Result<Vec<String>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT t.name FROM tags t
+                 WHERE EXISTS (SELECT 1 FROM icon_tags it JOIN tags d ON d.id = it.tag_id
+                               WHERE d.id = t.id OR substr(d.name, 1, length(t.name) + 1) = t.name || '/' COLLATE NOCASE)
+                 ORDER BY t.name COLLATE NOCASE",
+            )?;
+            let tags = stmt
+                .query_map([], |row| row.get(0))?
+                .filter_map(|r| r.ok())
+                .collect();
+            Ok(tags)
+        })
+    }
+
+    /// Renames a tag everywhere. A plain name renames it in place; a path with
+    /// `/` moves it. Renaming onto an existing tag merges the two.
+    pub fn rename_tag(&self, id: i64, new_name: &str) -> Result<()> {
+        if new_name.trim().is_empty() {
+            return Err(Error::invalid("Tag name cannot be empty").with_tag(id));
+        }
+        self.tracked("Rename tag", |conn, tracker| {
+            let new_path = match parent_path(&tag_path(conn, id)?) {
+                Some(parent) if !new_name.contains('/') => format!("{}/{}", parent, new_name),
+                _ => new_name.to_string(),
+            };
+            track_tags(conn, tracker, &[id])?;
+            move_tag(conn, id, &new_path)
+        })
+    }
+
+    /// Moves a tag and its subtags under `parent_id`, or to the top level.
+    pub fn set_tag_parent(&self, id: i64, parent_id: Option<i64>) -> Result<()> {
+        self.tracked("Move tag", |conn, tracker| {
+            let leaf = leaf_name(&tag_path(conn, id)?).to_string();
+            let new_path = match parent_id {
+                Some(parent) => format!("{}/{}", tag_path(conn, parent)?, leaf),
+                None => leaf,
+            };
+            track_tags(conn, tracker, &[id])?;
+            move_tag(conn, id, &new_path)
+        })
+    }
+
+    pub fn merge_tags(&self, source_ids: &[i64], target_id: i64) -> Result<()> {
+        self.tracked("Merge tags", |conn, tracker| {
+            track_tags(conn, tracker, source_ids)?;
+            merge_tags_into(conn, source_ids, target_id)
+        })
+    }
+
+    /// Deletes a tag from all icons. Its subtags move up to its parent.
+    pub fn delete_tag(&self, id: i64) -> Result<()> {
+        self.tracked("Delete tag", |conn, tracker| {
+            let path = tag_path(conn, id)?;
+            track_tags(conn, tracker, &[id])?;
+            for (subtag, name) in subtags(conn, id)? {
+                let new_path = match parent_path(&path) {
+                    Some(parent) => format!("{}/{}", parent, leaf_name(&name)),
+                    None => leaf_name(&name).to_string(),
+                };
+                move_tag(conn, subtag, &new_path)?;
+            }
+            conn.execute("DELETE FROM tags WHERE id = ?1", [id])?;
+            Ok(())
+        })
+    }
+
+    pub fn set_tag_color(&self, id: i64, color: Option<&str>) -> Result<()> {
+        self.tracked("Change tag color", |conn, tracker| {
+            tracker.tags(conn)?;
+            conn.execute(
+                "UPDATE tags SET color = ?1 WHERE id = ?2",
+                rusqlite::params![color, id],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Derives tags for existing icons (see autotag.rs) and adds them.
+    pub fn autotag_icons(&self, collection_ids: Option<&[String]>) -> Result<AutotagResult> {
+        let stop_words = autotag::parse_stop_words(&self.get_settings()?.autotag_stop_words);
+
+        let icons: Vec<(String, String, Option<String>, String, String)> =
+            self.with_conn(|conn| {
+                let (scope, params): (String, Vec<Value>) = match collection_ids {
+                    Some(ids) => (
+                        format!(
+                            "WHERE i.collection_id IN (
+                            WITH RECURSIVE sub(id) AS (
+                                SELECT id FROM collections WHERE id IN ({})
+                                UNION
+                                SELECT c.id FROM collections c JOIN sub s ON c.parent_id = s.id
+                            )
+                            SELECT id FROM sub
+                        )",
+                            vec!["?"; ids.len()].join(", ")
+                        ),
+                        ids.iter().map(|id| Value::Text(id.clone())).collect(),
+                    ),
+                    None => (String::new(), vec![]),
+                };
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT i.id, i.name, i.path, i.svg_content, COALESCE(c.name, '')
+                 FROM icons i LEFT JOIN collections c ON c.id = i.collection_id {}",
+                    scope
+                ))?;
+                let rows = stmt
+                    .query_map(rusqlite::params_from_iter(params), |row| {
+                        Ok((
+                            row.get(0)?,
+                            row.get(1)?,
+                            row.get(2)?,
+                            row.get(3)?,
+                            row.get(4)?,
+                        ))
+                    })?
+                    .filter_map(|r| r.ok())
+                    .collect();
+                Ok(rows)
+            })?;
+
+        let derived: Vec<(String, Vec<String>)> = icons
+            .into_iter()
+            .map(|(id, name, path, svg_content, collection)| {
+                // The folder the file came from, falling back to its collection
+                let folder = path
+                    .as_deref()
+                    .and_then(|p| std::path::Path::new(p).parent())
+                    .and_then(|p| p.file_name())
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or(collection);
+                let tags = autotag::derive(&name, Some(&folder), &svg_content, &stop_words);
+                (id, tags)
+            })
+            .collect();
+
+        self.tracked("Auto-tag icons", |conn, tracker| {
+            tracker.tags(conn)?;
+            tracker.icons(conn, derived.iter().map(|(id, _)| id.as_str()))?;
+            let mut result = AutotagResult {
+                icons_updated: 0,
+                tags_added: 0,
+            };
+            for (id, tags) in &derived {
+                let added = add_icon_tags(conn, id, tags)?;
+                if added > 0 {
+                    result.icons_updated += 1;
+                    result.tags_added += added;
+                }
+            }
+            Ok(result)
+        })
+    }
+}
+/// Snapshots all tags plus the icons tagged with `ids` or their subtags,
+/// before an edit that can rename, merge or delete them.
+fn track_tags(conn: &Connection, tracker: &mut Tracker, ids: &[i64]) -> Result<()> {
+    tracker.tags(conn)?;
+    let icons = icons_tagged_within(conn, ids)?;
+    tracker.icons(conn, icons.iter().map(String::as_str))
+}
+
+/// Moves every use of the `sources` tags onto `target` and deletes them.
+/// Their subtags move under `target`, merging with same-named ones there.
+fn merge_tags_into(conn: &Connection, sources: &[i64], target: i64) -> Result<()> {
+    let target_path = tag_path(conn, target)?;
+    for &source in sources.iter().filter(|&&s| s != target) {
+        let source_path = tag_path(conn, source)?;
+        if is_within(&target_path, &source_path) {
+            return Err(Error::conflict(format!(
+                "Cannot merge '{}' into its own subtag '{}'",
+                source_path, target_path
+            ))
+            .with_tag(source));
+        }
+        for (id, name) in subtags(conn, source)? {
+            move_tag(conn, id, &format!("{}/{}", target_path, leaf_name(&name)))?;
+        }
+        conn.execute(
+            "INSERT OR IGNORE INTO icon_tags (icon_id, tag_id)
+             SELECT icon_id, ?2 FROM icon_tags WHERE tag_id = ?1",
+            [source, target],
+        )?;
+        conn.execute("DELETE FROM tags WHERE id = ?1", [source])?;
+    }
+    Ok(())
+}
+
+/// Gives a tag a new path, carrying its subtags along. Moving onto the path
+/// of another tag merges the two.
+fn move_tag(conn: &Connection, id: i64, new_path: &str) -> Result<()> {
+    let old_path = tag_path(conn, id)?;
+    let new_path = normalize_tag_path(new_path);
+    if new_path.is_empty() {
+        return Err(Error::invalid("Tag name cannot be empty").with_tag(id));
+    }
+    if new_path == old_path {
+        return Ok(());
+    }
+    // A case-only rename is fine; anything else inside the tag is a cycle
+    if new_path.to_lowercase() != old_path.to_lowercase() && is_within(&new_path, &old_path) {
+        return Err(
+            Error::conflict(format!("Cannot move '{}' under itself", old_path)).with_tag(id),
+        );
+    }
+
+    let existing: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM tags WHERE name = ?1 AND id != ?2",
+            rusqlite::params![new_path, id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(target) = existing {
+        return merge_tags_into(conn, &[id], target);
+    }
+
+    // Named below the parent as it is stored, like `ensure_tag` does
+    let parent_id = parent_path(&new_path)
+        .map(|parent| ensure_tag(conn, parent))
+        .transpose()?;
+    let new_path = match parent_id {
+        Some(parent) => format!("{}/{}", tag_path(conn, parent)?, leaf_name(&new_path)),
+        None => new_path,
+    };
+    conn.execute(
+        "UPDATE tags SET name = ?1, parent_id = ?2 WHERE id = ?3",
+        rusqlite::params![new_path, parent_id, id],
+    )?;
+    conn.execute(
+        "UPDATE tags SET name = ?1 || substr(name, length(?2) + 1)
+         WHERE substr(name, 1, length(?2) + 1) = ?2 || '/' COLLATE NOCASE",
+        [&new_path, &old_path],
+    )?;
+    Ok(())
+}
+
+// ── Visual similarity ────────────────────────────────────────
+
+fn save_fingerprint(
+    conn: &Connection,
+    icon_id: &str,
+    fingerprint: Option<&visual::Fingerprint>,
+) -> Result<()> {
+    let (phash, shape) = match fingerprint {
+        Some(fp) => (fp.phash as i64, fp.shape.as_slice()),
+        None => (0, &[][..]),
+    };
+    conn.execute(
+        "INSERT OR REPLACE INTO icon_visuals (icon_id, phash, shape) VALUES (?1, ?2, ?3)",
+        rusqlite::params![icon_id, phash, shape],
+    )?;
+    Ok(())
+}
+
+impl Library {
+    /// Computes fingerprints for icons imported before similarity search
+    /// existed. Rendering happens without holding the database lock.
+    fn index_missing_fingerprints(&self) -> Result<()> {
+        let missing: Vec<(String, String)> = self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT i.id, i.svg_content FROM icons i
+                 LEFT JOIN icon_visuals v ON v.icon_id = i.id
+                 WHERE v.icon_id IS NULL",
+            )?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .filter_map(|r| r.ok())
+                .collect();
+            Ok(rows)
+        })?;
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let fingerprints: Vec<(String, Option<visual::Fingerprint>)> = missing
+            .into_iter()
+            .map(|(id, svg_content)| (id, visual::fingerprint(&svg_content)))
+            .collect();
+
+        self.with_conn(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            for (id, fingerprint) in &fingerprints {
+                save_fingerprint(&tx, id, fingerprint.as_ref())?;
+            }
+            Ok(tx.commit()?)
+        })
+    }
+
+    /// Returns the icons that look most like `icon_id`, closest first.
+    pub fn find_similar(&self, icon_id: &str, limit: usize) -> Result<Vec<SimilarIcon>> {
+        self.index_missing_fingerprints()?;
+
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare("SELECT icon_id, phash, shape FROM icon_visuals")?;
+            let fingerprints: Vec<(String, visual::Fingerprint)> = stmt
+                .query_map([], |row| {
+                    Ok((
+                        row.get(0)?,
+                        visual::Fingerprint {
+                            phash: row.get::<_, i64>(1)? as u64,
+                            shape: row.get(2)?,
+                        },
+                    ))
+                })?
+                .filter_map(|r| r.ok())
+                .filter(|(_, fp)| !fp.shape.is_empty())
+                .collect();
+
+            let Some((_, target)) = fingerprints.iter().find(|(id, _)| id == icon_id) else {
+                return Ok(vec![]);
+            };
+
+            let mut ranked: Vec<(&str, f64)> = fingerprints
+                .iter()
+                .filter(|(id, _)| id != icon_id)
+                .map(|(id, fp)| (id.as_str(), visual::distance(target, fp)))
+                .collect();
+            ranked.sort_by(|a, b| a.1.total_cmp(&b.1));
+            ranked.truncate(limit);
+
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM icons i WHERE i.id = ?1",
+                ICON_COLUMNS
+            ))?;
+            let mut similar = Vec::with_capacity(ranked.len());
+            for (id, distance) in ranked {
+                let icon = stmt.query_row([id], row_to_icon)?;
+                similar.push(SimilarIcon { icon, distance });
+            }
+            Ok(similar)
+        })
+    }
+}
+// ── Palettes ─────────────────────────────────────────────────
+
+fn save_palette(conn: &Connection, icon_id: &str, colors: &[palette::Rgb]) -> Result<()> {
+    conn.execute("DELETE FROM icon_colors WHERE icon_id = ?1", [icon_id])?;
+    let mut stmt = conn.prepare(
+        "INSERT INTO icon_colors (icon_id, position, r, g, b) VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    for (position, rgb) in colors.iter().enumerate() {
+        stmt.execute(rusqlite::params![
+            icon_id,
+            position as i64,
+            rgb.r,
+            rgb.g,
+            rgb.b
+        ])?;
+    }
+    Ok(())
+}
+
+impl Library {
+    /// Returns the icon's colors as `#rrggbb`, most used first. Empty for icons
+    /// that draw with `currentColor` only.
+    pub fn get_icon_palette(&self, icon_id: &str) -> Result<Vec<String>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn
+                .prepare("SELECT r, g, b FROM icon_colors WHERE icon_id = ?1 ORDER BY position")?;
+            let colors = stmt
+                .query_map([icon_id], |row| {
+                    Ok(palette::Rgb::new(row.get(0)?, row.get(1)?, row.get(2)?).hex())
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(colors)
+        })
+    }
+}
+// ── Semantic search ──────────────────────────────────────────
+
+const EMBEDDING_MODEL_KEY: &str = "embedding_model";
+
+fn model_load_error(path: &Path, e: std::io::Error) -> Error {
+    Error::new(
+        ErrorCode::Model,
+        format!("Failed to load embedding model: {}", e),
+    )
+    .with_path(path.to_string_lossy())
+}
+
+impl Library {
+    /// Where the embedding model is read from: the path chosen by the user, or
+    /// `models/embeddings.vec` next to the library file if that exists.
+    fn embedding_model_path(&self) -> Result<Option<PathBuf>> {
+        let configured = self.with_conn(|conn| Ok(get_setting(conn, EMBEDDING_MODEL_KEY)))?;
+        Ok(match configured {
+            Some(path) => Some(PathBuf::from(path)),
+            None => self
+                .data_dir
+                .as_ref()
+                .map(|dir| dir.join("models").join("embeddings.vec"))
+                .filter(|default| default.exists()),
+        })
+    }
+
+    /// Returns the embedding model, loading it from disk on first use. `None`
+    /// means no model is configured or it failed to load; see
+    /// `embedding_model_error` for why.
+    fn embedding_model(&self) -> Result<Option<Arc<EmbeddingModel>>> {
+        if let Some(model) = &self.embeddings.lock().unwrap().model {
+            return Ok(Some(model.clone()));
+        }
+
+        let Some(path) = self.embedding_model_path()? else {
+            return Ok(None);
+        };
+        if self.embedding_model_error(&path).is_some() {
+            return Ok(None);
+        }
+        let model = match EmbeddingModel::load(&path) {
+            Ok(model) => Arc::new(model),
+            Err(e) => {
+                let error = model_load_error(&path, e);
+                self.embeddings.lock().unwrap().failed = Some((path, error));
+                return Ok(None);
+            }
+        };
+
+        let mut cache = self.embeddings.lock().unwrap();
+        cache.model = Some(model.clone());
+        cache.vectors = None;
+        Ok(Some(model))
+    }
+
+    /// Why the model at `path` failed to load, if it did.
+    fn embedding_model_error(&self, path: &Path) -> Option<Error> {
+        match &self.embeddings.lock().unwrap().failed {
+            Some((failed, error)) if failed == path => Some(error.clone()),
+            _ => None,
+        }
+    }
+
+    /// Loads a word-vector file (fastText/GloVe text format), makes it the
+    /// active embedding model and remembers its path. Loading can take a
+    /// while; the library stays usable meanwhile.
+    pub fn load_embedding_model(&self, path: &Path) -> Result<EmbeddingStatus> {
+        let model = EmbeddingModel::load(path).map_err(|e| model_load_error(path, e))?;
+        self.save_setting(EMBEDDING_MODEL_KEY, &path.to_string_lossy())?;
+        {
+            let mut cache = self.embeddings.lock().unwrap();
+            cache.model = Some(Arc::new(model));
+            cache.vectors = None;
+            cache.failed = None;
+        }
+        self.get_embedding_status()
+    }
+
+    pub fn get_embedding_status(&self) -> Result<EmbeddingStatus> {
+        let path = self.embedding_model_path()?;
+        let model = self.embedding_model()?;
+        let indexed: i64 = match &model {
+            Some(model) => self.with_conn(|conn| {
+                Ok(conn.query_row(
+                    "SELECT COUNT(*) FROM icon_embeddings WHERE model = ?1 AND length(vector) > 0",
+                    [&model.id],
+                    |row| row.get(0),
+                )?)
+            })?,
+            None => 0,
+        };
+
+        Ok(EmbeddingStatus {
+            error: path.as_deref().and_then(|p| self.embedding_model_error(p)),
+            model_path: path.map(|p| p.to_string_lossy().to_string()),
+            loaded: model.is_some(),
+            dimensions: model.as_ref().map_or(0, |m| m.dimensions),
+            vocabulary: model.as_ref().map_or(0, |m| m.vocabulary_size()),
+            indexed,
+        })
+    }
+
+    /// Embeds icons that are new, were edited, or were embedded with another
+    /// model. Embedding runs without holding the database lock.
+    fn refresh_embeddings(&self, model: &EmbeddingModel) -> Result<()> {
+        let stale: Vec<(String, String)> = self.with_conn(|conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT i.id, i.name, {}, COALESCE(i.title, ''), COALESCE(i.description, ''),
+                            e.model, e.source
+                     FROM icons i LEFT JOIN icon_embeddings e ON e.icon_id = i.id",
+                tag_text_sql("i.id")
+            ))?;
+            let rows = stmt
+                .query_map([], |row| {
+                    let source = [
+                        row.get::<_, String>(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                    ]
+                    .join(" ");
+                    let model_id: Option<String> = row.get(5)?;
+                    let stored: Option<String> = row.get(6)?;
+                    let fresh = model_id.as_deref() == Some(model.id.as_str())
+                        && stored.as_deref() == Some(source.as_str());
+                    Ok((row.get::<_, String>(0)?, source, fresh))
+                })?
+                .filter_map(|r| r.ok())
+                .filter(|(_, _, fresh)| !fresh)
+                .map(|(id, source, _)| (id, source))
+                .collect();
+            Ok(rows)
+        })?;
+        if stale.is_empty() {
+            return Ok(());
+        }
+
+        let vectors: Vec<(String, String, Vec<u8>)> = stale
+            .into_iter()
+            .map(|(id, source)| {
+                // Icons with no known words get an empty vector so they aren't
+                // re-embedded on every search
+                let blob = model
+                    .embed(&source)
+                    .map(|v| embedding::to_blob(&v))
+                    .unwrap_or_default();
+                (id, source, blob)
+            })
+            .collect();
+
+        self.with_conn(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            for (id, source, blob) in &vectors {
+                tx.execute(
+                    "INSERT OR REPLACE INTO icon_embeddings (icon_id, model, source, vector)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    rusqlite::params![id, model.id, source, blob],
+                )?;
+            }
+            Ok(tx.commit()?)
+        })
+    }
+
+    /// Finds icons whose meaning is closest to `query`, best first.
+    pub fn semantic_search(&self, query: &str, limit: usize) -> Result<Vec<SemanticMatch>> {
+        let Some(model) = self.embedding_model()? else {
+            let failed = self
+                .embedding_model_path()?
+                .and_then(|p| self.embedding_model_error(&p));
+            return Err(
+                failed.unwrap_or_else(|| Error::new(ErrorCode::Model, "No embedding model loaded"))
+            );
+        };
+        let Some(target) = model.embed(query) else {
+            return Ok(vec![]);
+        };
+        self.refresh_embeddings(&model)?;
+
+        self.with_conn(|conn| {
+            let mut cache = self.embeddings.lock().unwrap();
+
+            let version = conn.total_changes();
+            if !matches!(&cache.vectors, Some((v, _)) if *v == version) {
+                let mut stmt = conn.prepare(
+                    "SELECT icon_id, vector FROM icon_embeddings WHERE model = ?1 AND length(vector) > 0",
+                )?;
+                let vectors = stmt
+                    .query_map([&model.id], |row| {
+                        Ok((row.get(0)?, embedding::from_blob(&row.get::<_, Vec<u8>>(1)?)))
+                    })?
+                    .filter_map(|r| r.ok())
+                    .collect();
+                cache.vectors = Some((version, vectors));
+            }
+
+            let mut ranked: Vec<(&str, f32)> = cache
+                .vectors
+                .iter()
+                .flat_map(|(_, vectors)| vectors)
+                .map(|(id, v)| (id.as_str(), embedding::similarity(&target, v)))
+                .filter(|(_, score)| *score > 0.0)
+                .collect();
+            ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+            ranked.truncate(limit);
+
+            let mut stmt =
+                conn.prepare(&format!("SELECT {} FROM icons i WHERE i.id = ?1", ICON_COLUMNS))?;
+            let mut matches = Vec::with_capacity(ranked.len());
+            for (id, score) in ranked {
+                let icon = stmt.query_row([id], row_to_icon)?;
+                matches.push(SemanticMatch { icon, score });
+            }
+            Ok(matches)
+        })
+    }
+}
+// ── Synonyms ─────────────────────────────────────────────────
+//
+// Edits to the synonym groups are not part of the undo history (see
+// History below).
+
+fn load_thesaurus(conn: &Connection) -> Result<Thesaurus> {
+    let groups = load_synonym_groups(conn)?;
+    Ok(Thesaurus::new(groups.into_iter().map(|g| g.terms)))
+}
+
+fn load_synonym_groups(conn: &Connection) -> Result<Vec<SynonymGroup>> {
+    let mut stmt = conn.prepare(
+        "SELECT g.id, s.term FROM synonym_groups g
+         JOIN synonyms s ON s.group_id = g.id
+         ORDER BY g.id, s.rowid",
+    )?;
+    let rows: Vec<(i64, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut groups: Vec<SynonymGroup> = Vec::new();
+    for (id, term) in rows {
+        match groups.last_mut() {
+            Some(group) if group.id == id => group.terms.push(term),
+            _ => groups.push(SynonymGroup {
+                id,
+                terms: vec![term],
+            }),
+        }
+    }
+    Ok(groups)
+}
+
+impl Library {
+    pub fn get_synonym_groups(&self) -> Result<Vec<SynonymGroup>> {
+        self.with_conn(load_synonym_groups)
+    }
+
+    /// Creates a group (`id` is `None`) or replaces the terms of an existing
+    /// one. Blank and duplicate terms are dropped; a group left with fewer than
+    /// two terms is deleted since it can't expand anything.
+    pub fn save_synonym_group(
+        &self,
+        id: Option<i64>,
+        terms: &[String],
+    ) -> Result<Option<SynonymGroup>> {
+        let mut cleaned: Vec<String> = Vec::new();
+        for term in terms {
+            let term = term.trim();
+            if !term.is_empty()
+                && !cleaned
+                    .iter()
+                    .any(|t| synonyms::normalize(t) == synonyms::normalize(term))
+            {
+                cleaned.push(term.to_string());
+            }
+        }
+
+        self.with_conn(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            let id = match id {
+                Some(id) => {
+                    tx.execute("DELETE FROM synonyms WHERE group_id = ?1", [id])?;
+                    id
+                }
+                None => {
+                    tx.execute("INSERT INTO synonym_groups DEFAULT VALUES", [])?;
+                    tx.last_insert_rowid()
+                }
+            };
+
+            if cleaned.len() < 2 {
+                tx.execute("DELETE FROM synonym_groups WHERE id = ?1", [id])?;
+                tx.commit()?;
+                return Ok(None);
+            }
+
+            for term in &cleaned {
+                tx.execute(
+                    "INSERT INTO synonyms (group_id, term) VALUES (?1, ?2)",
+                    rusqlite::params![id, term],
+                )?;
+            }
+            tx.commit()?;
+            Ok(Some(SynonymGroup { id, terms: cleaned }))
+        })
+    }
+
+    pub fn delete_synonym_group(&self, id: i64) -> Result<()> {
+        self.with_conn(|conn| {
+            conn.execute("DELETE FROM synonym_groups WHERE id = ?1", [id])?;
+            Ok(())
+        })
+    }
+
+    /// Drops all user edits and restores the built-in dictionary.
+    pub fn reset_synonyms(&self) -> Result<()> {
+        self.with_conn(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            tx.execute("DELETE FROM synonyms", [])?;
+            tx.execute("DELETE FROM synonym_groups", [])?;
+            seed_default_synonyms(&tx)?;
+            Ok(tx.commit()?)
+        })
+    }
+}
+// ── Import ───────────────────────────────────────────────────
+
+impl Library {
+    /// Imports the SVG files directly inside `folder` as a new collection
+    /// named after it, tagging each icon automatically. The collection and
+    /// its icons are stored in one transaction; files that cannot be read or
+    /// stored are left out and reported.
+    pub fn import_folder(&self, folder: &Path, parent_id: Option<&str>) -> Result<ImportResult> {
+        let folder_name = folder
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("Imported");
+        let mut collection = new_collection(folder_name, parent_id);
+
+        let stop_words = autotag::parse_stop_words(&self.get_settings()?.autotag_stop_words);
+
+        // Scan for SVG files
+        let entries = std::fs::read_dir(folder).map_err(|e| Error::io(folder, "Cannot read", e))?;
+        let mut icons = Vec::new();
+        let mut failed = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|e| e != "svg") {
+                continue;
+            }
+            let content = match std::fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) => {
+                    failed.push(ImportFailure {
+                        path: path.to_string_lossy().to_string(),
+                        error: Error::io(&path, "Cannot read", e),
+                    });
+                    continue;
+                }
+            };
+            let file_size = std::fs::metadata(&path)
+                .map(|m| m.len() as i64)
+                .unwrap_or(0);
+            let name = path
+                .file_stem()
+                .and_then(|n| n.to_str())
+                .unwrap_or("icon")
+                .to_string();
+            let tags = autotag::derive(&name, Some(&collection.name), &content, &stop_words);
+            icons.push(Icon {
+                id: Uuid::new_v4().to_string(),
+                name,
+                path: path.to_string_lossy().to_string(),
+                svg_content: content,
+                tags,
+                collection_id: collection.id.clone(),
+                created_at: chrono::Utc::now().to_rfc3339(),
+                file_size,
+                favorite: false,
+            });
+        }
+
+        // Rasterizing is the slow part, keep it outside the lock
+        let fingerprints: Vec<_> = icons
+            .iter()
+            .map(|icon| visual::fingerprint(&icon.svg_content))
+            .collect();
+
+        // Logged as a whole, so undoing it moves the new collection to the trash
+        let not_stored = self.tracked("Import folder", |conn, tracker| {
+            insert_collection(conn, &collection)?;
+            tracker.added("collection", &collection.id);
+            let mut not_stored = Vec::new();
+            for (icon, fingerprint) in icons.iter().zip(&fingerprints) {
+                // Rolling back to the savepoint drops a half-stored icon only
+                conn.execute_batch("SAVEPOINT import_icon")?;
+                match insert_icon(conn, icon, fingerprint.as_ref()) {
+                    Ok(()) => conn.execute_batch("RELEASE import_icon")?,
+                    Err(e) => {
+                        conn.execute_batch("ROLLBACK TO import_icon; RELEASE import_icon")?;
+                        not_stored.push(ImportFailure {
+                            path: icon.path.clone(),
+                            error: e,
+                        });
+                    }
+                }
+            }
+            Ok(not_stored)
+        })?;
+
+        collection.icon_count = (icons.len() - not_stored.len()) as i32;
+        collection.total_icon_count = collection.icon_count;
+        failed.extend(not_stored);
+        Ok(ImportResult { collection, failed })
+    }
+}
+
+// ── Trash ────────────────────────────────────────────────────
+//
+// Deleted icons and collections are copied into the trash tables and then
+// removed from the live ones, so nothing else has to know about the trash.
+// Each entry is one deleted icon or one deleted collection with everything
+// that was under it. Entries older than the retention setting are purged
+// when the library is opened.
+
+const TRASH_RETENTION_KEY: &str = "trash_retention_days";
+const DEFAULT_TRASH_RETENTION_DAYS: i64 = 30;
+
+/// Version 2: trash tables.
+fn migrate_trash(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE trash (
+            id TEXT PRIMARY KEY,
+            kind TEXT NOT NULL,
+            item_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            parent_id TEXT,
+            deleted_at TEXT NOT NULL
+        );
+        CREATE INDEX idx_trash_deleted ON trash(deleted_at);
+        CREATE TABLE trash_collections (
+            trash_id TEXT NOT NULL REFERENCES trash(id) ON DELETE CASCADE,
+            id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            parent_id TEXT,
+            color TEXT,
+            created_at TEXT NOT NULL,
+            sort_order INTEGER,
+            depth INTEGER NOT NULL
+        );
+        CREATE INDEX idx_trash_collections_trash ON trash_collections(trash_id);
+        CREATE TABLE trash_icons (
+            trash_id TEXT NOT NULL REFERENCES trash(id) ON DELETE CASCADE,
+            id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            path TEXT,
+            svg_content TEXT NOT NULL,
+            collection_id TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            file_size INTEGER DEFAULT 0,
+            favorite INTEGER DEFAULT 0,
+            title TEXT,
+            description TEXT,
+            tags TEXT,
+            phash INTEGER,
+            shape BLOB
+        );
+        CREATE INDEX idx_trash_icons_trash ON trash_icons(trash_id);",
+    )?;
+    Ok(())
+}
+
+/// Retention in days; 0 keeps the trash until it is emptied by hand.
+fn trash_retention_days(conn: &Connection) -> i64 {
+    get_setting(conn, TRASH_RETENTION_KEY)
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS)
+        .max(0)
+}
+
+fn purge_expired_trash(conn: &Connection) -> Result<usize> {
+    let days = trash_retention_days(conn);
+    if days == 0 {
+        return Ok(0);
+    }
+    let cutoff = (chrono::Utc::now() - chrono::Duration::days(days)).to_rfc3339();
+    let purged = conn.execute("DELETE FROM trash WHERE deleted_at < ?1", [cutoff])?;
+    delete_orphan_revisions(conn)?;
+    Ok(purged)
+}
+
+fn new_trash_entry(
+    conn: &Connection,
+    kind: &str,
+    item_id: &str,
+    name: &str,
+    parent_id: Option<&str>,
+) -> Result<String> {
+    let id = Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO trash (id, kind, item_id, name, parent_id, deleted_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        rusqlite::params![
+            id,
+            kind,
+            item_id,
+            name,
+            parent_id,
+            chrono::Utc::now().to_rfc3339()
+        ],
+    )?;
+    Ok(id)
+}
+
+/// Copies the icons matching `filter` (a condition on `i`) into a trash
+/// entry, with their tags and visual fingerprints.
+fn copy_icons_to_trash(
+    conn: &Connection,
+    trash_id: &str,
+    with: &str,
+    filter: &str,
+    param: &str,
+) -> Result<()> {
+    conn.execute(
+        &format!(
+            "{} INSERT INTO trash_icons (trash_id, id, name, path, svg_content, collection_id,
+                 created_at, file_size, favorite, title, description, tags, phash, shape,
+                 revision_id)
+             SELECT ?2, i.id, i.name, i.path, i.svg_content, i.collection_id, i.created_at,
+                 i.file_size, i.favorite, i.title, i.description,
+                 (SELECT group_concat(t.name, char(31) ORDER BY it.rowid) FROM icon_tags it
+                  JOIN tags t ON t.id = it.tag_id WHERE it.icon_id = i.id),
+                 v.phash, v.shape, i.revision_id
+             FROM icons i LEFT JOIN icon_visuals v ON v.icon_id = i.id
+             WHERE {}",
+            with, filter
+        ),
+        [param, trash_id],
+    )?;
+    Ok(())
+}
+
+/// Moves an icon to the trash and returns the trash entry. Unknown ids are
+/// ignored.
+fn trash_icon(conn: &Connection, icon_id: &str) -> Result<Option<String>> {
+    let icon: Option<(String, String)> = conn
+        .query_row(
+            "SELECT name, collection_id FROM icons WHERE id = ?1",
+            [icon_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let Some((name, collection_id)) = icon else {
+        return Ok(None);
+    };
+    let trash_id = new_trash_entry(conn, "icon", icon_id, &name, Some(&collection_id))?;
+    copy_icons_to_trash(conn, &trash_id, "", "i.id = ?1", icon_id)?;
+    conn.execute("DELETE FROM icons WHERE id = ?1", [icon_id])?;
+    Ok(Some(trash_id))
+}
+
+/// Moves a collection with its subcollections and their icons to the trash
+/// and returns the trash entry. Unknown ids are ignored.
+fn trash_collection(conn: &Connection, id: &str) -> Result<Option<String>> {
+    let collection: Option<(String, Option<String>)> = conn
+        .query_row(
+            "SELECT name, parent_id FROM collections WHERE id = ?1",
+            [id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let Some((name, parent_id)) = collection else {
+        return Ok(None);
+    };
+    let trash_id = new_trash_entry(conn, "collection", id, &name, parent_id.as_deref())?;
+
+    let subtree = "WITH RECURSIVE sub(id, depth) AS (
+            SELECT id, 0 FROM collections WHERE id = ?1
+            UNION ALL
+            SELECT c.id, s.depth + 1 FROM collections c JOIN sub s ON c.parent_id = s.id
+        )";
+    conn.execute(
+        &format!(
+            "{} INSERT INTO trash_collections
+                 (trash_id, id, name, parent_id, color, created_at, sort_order, depth)
+             SELECT ?2, c.id, c.name, c.parent_id, c.color, c.created_at, c.sort_order, s.depth
+             FROM collections c JOIN sub s ON s.id = c.id",
+            subtree
+        ),
+        [id, trash_id.as_str()],
+    )?;
+    copy_icons_to_trash(
+        conn,
+        &trash_id,
+        subtree,
+        "i.collection_id IN (SELECT id FROM sub)",
+        id,
+    )?;
+
+    conn.execute(
+        &format!(
+            "{} DELETE FROM icons WHERE collection_id IN (SELECT id FROM sub)",
+            subtree
+        ),
+        [id],
+    )?;
+    conn.execute(
+        &format!(
+            "{} DELETE FROM collections WHERE id IN (SELECT id FROM sub)",
+            subtree
+        ),
+        [id],
+    )?;
+    Ok(Some(trash_id))
+}
+
+/// Puts a trash entry back where it was and removes it from the trash. A
+/// collection whose parent is gone comes back at the top level; an icon
+/// whose collection is gone cannot be restored until the collection is.
+fn restore_trash_entry(conn: &Connection, trash_id: &str) -> Result<()> {
+    let (kind, name, parent_id): (String, String, Option<String>) = conn.query_row(
+        "SELECT kind, name, parent_id FROM trash WHERE id = ?1",
+        [trash_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )?;
+    let parent_exists = match &parent_id {
+        Some(parent) => conn
+            .query_row("SELECT 1 FROM collections WHERE id = ?1", [parent], |_| {
+                Ok(())
+            })
+            .optional()?
+            .is_some(),
+        None => false,
+    };
+
+    if kind == "collection" {
+        conn.execute(
+            "INSERT INTO collections (id, name, parent_id, icon_count, color, created_at, sort_order)
+             SELECT id, name,
+                 CASE WHEN depth = 0 AND ?2 = 0 THEN NULL ELSE parent_id END,
+                 0, color, created_at,
+                 CASE WHEN depth = 0 AND ?2 = 0 THEN NULL ELSE sort_order END
+             FROM trash_collections WHERE trash_id = ?1 ORDER BY depth",
+            rusqlite::params![trash_id, parent_exists],
+        )?;
+    } else if !parent_exists {
+        return Err(Error::conflict(format!(
+            "\"{}\" cannot be restored because its collection is gone; restore the collection first",
+            name
+        ))
+        .with_collection(parent_id.unwrap_or_default()));
+    }
+
+    conn.execute(
+        "INSERT INTO icons (id, name, path, svg_content, collection_id, created_at, file_size,
+             favorite, title, description, revision_id)
+         SELECT id, name, path, svg_content, collection_id, created_at, file_size, favorite,
+             title, description, revision_id
+         FROM trash_icons WHERE trash_id = ?1",
+        [trash_id],
+    )?;
+    conn.execute(
+        "INSERT OR REPLACE INTO icon_visuals (icon_id, phash, shape)
+         SELECT id, phash, shape FROM trash_icons WHERE trash_id = ?1 AND phash IS NOT NULL",
+        [trash_id],
+    )?;
+    let icons: Vec<(String, String, Option<String>)> = conn
+        .prepare("SELECT id, svg_content, tags FROM trash_icons WHERE trash_id = ?1")?
+        .query_map([trash_id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+    for (id, svg_content, tags) in icons {
+        let tags: Vec<String> = tags
+            .map(|t| t.split(TAG_SEPARATOR).map(|s| s.to_string()).collect())
+            .unwrap_or_default();
+        set_icon_tags(conn, &id, &tags)?;
+        save_palette(conn, &id, &palette::extract(&svg_content))?;
+    }
+
+    conn.execute("DELETE FROM trash WHERE id = ?1", [trash_id])?;
+    Ok(())
+}
+
+impl Library {
+    /// Trash entries, most recently deleted first.
+    pub fn get_trash(&self) -> Result<Vec<TrashItem>> {
+        self.with_conn(|conn| {
+            let days = trash_retention_days(conn);
+            let mut stmt = conn.prepare(
+                "SELECT t.id, t.kind, t.name, c.name, t.deleted_at,
+                     (SELECT COUNT(*) FROM trash_icons WHERE trash_id = t.id),
+                     (SELECT COUNT(*) FROM trash_collections WHERE trash_id = t.id),
+                     CASE WHEN t.kind = 'icon'
+                         THEN (SELECT svg_content FROM trash_icons WHERE trash_id = t.id) END
+                 FROM trash t LEFT JOIN collections c ON c.id = t.parent_id
+                 ORDER BY t.deleted_at DESC",
+            )?;
+            let items = stmt
+                .query_map([], |row| {
+                    let deleted_at: String = row.get(4)?;
+                    let expires_at = (days > 0)
+                        .then(|| chrono::DateTime::parse_from_rfc3339(&deleted_at).ok())
+                        .flatten()
+                        .map(|d| (d + chrono::Duration::days(days)).to_rfc3339());
+                    Ok(TrashItem {
+                        id: row.get(0)?,
+                        kind: row.get(1)?,
+                        name: row.get(2)?,
+                        location: row.get(3)?,
+                        deleted_at,
+                        expires_at,
+                        icon_count: row.get(5)?,
+                        collection_count: row.get(6)?,
+                        svg_content: row.get(7)?,
+                    })
+                })?
+                .filter_map(|r| r.ok())
+                .collect();
+            Ok(items)
+        })
+    }
+
+    /// Restores trash entries, in the order given. Either all of them come
+    /// back or none does.
+    pub fn restore_from_trash(&self, trash_ids: &[String]) -> Result<BulkResult> {
+        self.tracked("Restore from trash", |conn, tracker| {
+            for_trash_entries(conn, trash_ids, |conn, id| {
+                let (kind, item_id): (String, String) = conn.query_row(
+                    "SELECT kind, item_id FROM trash WHERE id = ?1",
+                    [id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )?;
+                restore_trash_entry(conn, id)?;
+                tracker.added(&kind, &item_id);
+                Ok(())
+            })
+        })
+    }
+
+    /// Deletes trash entries for good.
+    pub fn delete_from_trash(&self, trash_ids: &[String]) -> Result<BulkResult> {
+        self.with_conn(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            let result = for_trash_entries(&tx, trash_ids, |conn, id| {
+                conn.execute("DELETE FROM trash WHERE id = ?1", [id])?;
+                Ok(())
+            })?;
+            delete_orphan_revisions(&tx)?;
+            tx.commit()?;
+            Ok(result)
+        })
+    }
+
+    /// Deletes everything in the trash for good. Returns the number of
+    /// entries removed.
+    pub fn empty_trash(&self) -> Result<usize> {
+        self.with_conn(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            let removed = tx.execute("DELETE FROM trash", [])?;
+            delete_orphan_revisions(&tx)?;
+            tx.commit()?;
+            Ok(removed)
+        })
+    }
+}
+
+/// Runs `op` on each distinct existing entry, like `Library::bulk` does for
+/// icons. Callers provide the transaction.
+fn for_trash_entries<F>(conn: &Connection, trash_ids: &[String], mut op: F) -> Result<BulkResult>
+where
+    F: FnMut(&Connection, &str) -> Result<()>,
+{
+    let mut result = BulkResult::default();
+    let mut seen = HashSet::new();
+    for id in trash_ids {
+        if !seen.insert(id.as_str()) {
+            continue;
+        }
+        let exists = conn
+            .query_row("SELECT 1 FROM trash WHERE id = ?1", [id], |_| Ok(()))
+            .optional()?
+            .is_some();
+        if !exists {
+            result.missing.push(id.clone());
+            continue;
+        }
+        result.processed += 1;
+        op(conn, id)?;
+        result.changed += 1;
+    }
+    Ok(result)
+}
+
+// ── History ──────────────────────────────────────────────────
+//
+// Every edit to the library is logged with the state of the rows it touched
+// before and after it, so it can be undone and redone. The snapshots are
+// taken inside the edit's transaction (see `Library::tracked`). Deletes go
+// through the trash: undoing one restores the trash entry, and undoing a
+// copy or an import moves the new items to the trash. A new edit drops
+// whatever was undone, and only the last `HISTORY_LIMIT` edits are kept.
+//
+// Settings and the synonym dictionary are preferences rather than library
+// content and are not logged; their editors show every change as it is
+// made, and the synonyms can be reset to the built-in dictionary.
+
+const HISTORY_LIMIT: i64 = 100;
+
+/// Version 3: undo history.
+fn migrate_history(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            label TEXT NOT NULL,
+            changes TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            undone INTEGER NOT NULL DEFAULT 0
+        );",
+    )?;
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+struct IconState {
+    collection_id: String,
+    name: String,
+    favorite: bool,
+    /// Tag ids, in the order they were added.
+    tags: Vec<i64>,
+    /// Current revision of the artwork; absent in entries logged before
+    /// revisions existed.
+    #[serde(default)]
+    revision_id: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+struct CollectionState {
+    name: String,
+    parent_id: Option<String>,
+    color: Option<String>,
+    created_at: String,
+    sort_order: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+struct SmartCollectionState {
+    name: String,
+    query: String,
+    color: String,
+    created_at: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+struct TagState {
+    name: String,
+    parent_id: Option<i64>,
+    color: Option<String>,
+}
+
+/// The rows an edit changed, as they were before and after it. `None` means
+/// the row did not exist.
+#[derive(Serialize, Deserialize)]
+struct Diff<K, T> {
+    before: Vec<(K, Option<T>)>,
+    after: Vec<(K, Option<T>)>,
+}
+
+impl<K, T> Default for Diff<K, T> {
+    fn default() -> Self {
+        Diff {
+            before: Vec::new(),
+            after: Vec::new(),
+        }
+    }
+}
+
+impl<K: Clone + Ord + std::hash::Hash, T: PartialEq> Diff<K, T> {
+    fn new(mut before: HashMap<K, Option<T>>, mut after: HashMap<K, Option<T>>) -> Self {
+        let mut keys: Vec<K> = before.keys().chain(after.keys()).cloned().collect();
+        keys.sort();
+        keys.dedup();
+        let mut diff = Diff::default();
+        for key in keys {
+            let (old, new) = (before.remove(&key).flatten(), after.remove(&key).flatten());
+            if old != new {
+                diff.before.push((key.clone(), old));
+                diff.after.push((key, new));
+            }
+        }
+        diff
+    }
+
+    fn is_empty(&self) -> bool {
+        self.before.is_empty()
+    }
+
+    /// The rows as undoing (`before`) or redoing (`after`) leaves them.
+    fn target(&self, undo: bool) -> &[(K, Option<T>)] {
+        if undo {
+            &self.before
+        } else {
+            &self.after
+        }
+    }
+}
+
+/// An icon or collection that an edit moved into or out of the trash.
+#[derive(Serialize, Deserialize)]
+struct TrashRef {
+    kind: String,
+    item_id: String,
+    /// Its trash entry, while it is in the trash.
+    trash_id: Option<String>,
+}
+
+/// What one history entry changed.
+#[derive(Serialize, Deserialize, Default)]
+struct Changes {
+    icons: Diff<String, IconState>,
+    collections: Diff<String, CollectionState>,
+    smart_collections: Diff<String, SmartCollectionState>,
+    tags: Diff<i64, TagState>,
+    /// Items the edit deleted.
+    trashed: Vec<TrashRef>,
+    /// Items the edit created or brought back from the trash.
+    added: Vec<TrashRef>,
+}
+
+impl Changes {
+    fn is_empty(&self) -> bool {
+        self.icons.is_empty()
+            && self.collections.is_empty()
+            && self.smart_collections.is_empty()
+            && self.tags.is_empty()
+            && self.trashed.is_empty()
+            && self.added.is_empty()
+    }
+}
+
+/// Snapshots of the rows an edit is about to touch. `record` compares them
+/// with the rows afterwards and logs the difference.
+#[derive(Default)]
+struct Tracker {
+    icons: HashMap<String, Option<IconState>>,
+    collections: HashMap<String, Option<CollectionState>>,
+    smart_collections: HashMap<String, Option<SmartCollectionState>>,
+    /// All tags, if the edit can change any of them.
+    tags: Option<HashMap<i64, Option<TagState>>>,
+    trashed: Vec<TrashRef>,
+    added: Vec<TrashRef>,
+}
+
+impl Tracker {
+    fn icons<'a>(
+        &mut self,
+        conn: &Connection,
+        ids: impl IntoIterator<Item = &'a str>,
+    ) -> Result<()> {
+        for id in ids {
+            if !self.icons.contains_key(id) {
+                self.icons.insert(id.to_string(), icon_state(conn, id)?);
+            }
+        }
+        Ok(())
+    }
+
+    fn collections<'a>(
+        &mut self,
+        conn: &Connection,
+        ids: impl IntoIterator<Item = &'a str>,
+    ) -> Result<()> {
+        for id in ids {
+            if !self.collections.contains_key(id) {
+                self.collections
+                    .insert(id.to_string(), collection_state(conn, id)?);
+            }
+        }
+        Ok(())
+    }
+
+    fn smart_collections(&mut self, conn: &Connection, id: &str) -> Result<()> {
+        if !self.smart_collections.contains_key(id) {
+            self.smart_collections
+                .insert(id.to_string(), smart_collection_state(conn, id)?);
+        }
+        Ok(())
+    }
+
+    fn tags(&mut self, conn: &Connection) -> Result<()> {
+        if self.tags.is_none() {
+            self.tags = Some(tag_states(conn)?);
+        }
+        Ok(())
+    }
+
+    /// Notes an item the edit moved to the trash as `trash_id`.
+    fn trashed(&mut self, kind: &str, item_id: &str, trash_id: Option<String>) {
+        if trash_id.is_some() {
+            self.trashed.push(TrashRef {
+                kind: kind.to_string(),
+                item_id: item_id.to_string(),
+                trash_id,
+            });
+        }
+    }
+
+    /// Notes an item the edit created or restored.
+    fn added(&mut self, kind: &str, item_id: &str) {
+        self.added.push(TrashRef {
+            kind: kind.to_string(),
+            item_id: item_id.to_string(),
+            trash_id: None,
+        });
+    }
+
+    /// Logs what changed since the snapshots were taken, unless nothing did.
+    fn record(self, conn: &Connection, label: &str) -> Result<()> {
+        // Icons that appeared or went away are covered by the trash refs
+        let mut icons_before = HashMap::new();
+        let mut icons_after = HashMap::new();
+        for (id, before) in self.icons {
+            let after = icon_state(conn, &id)?;
+            if before.is_some() && after.is_some() {
+                icons_after.insert(id.clone(), after);
+                icons_before.insert(id, before);
+            }
+        }
+        let collections_after = self
+            .collections
+            .keys()
+            .map(|id| Ok((id.clone(), collection_state(conn, id)?)))
+            .collect::<Result<_>>()?;
+        let smart_after = self
+            .smart_collections
+            .keys()
+            .map(|id| Ok((id.clone(), smart_collection_state(conn, id)?)))
+            .collect::<Result<_>>()?;
+        let tags = match self.tags {
+            Some(before) => Diff::new(before, tag_states(conn)?),
+            None => Diff::default(),
+        };
+
+        let changes = Changes {
+            icons: Diff::new(icons_before, icons_after),
+            collections: Diff::new(self.collections, collections_after),
+            smart_collections: Diff::new(self.smart_collections, smart_after),
+            tags,
+            trashed: self.trashed,
+            added: self.added,
+        };
+        if changes.is_empty() {
+            return Ok(());
+        }
+        conn.execute("DELETE FROM history WHERE undone = 1", [])?;
+        conn.execute(
+            "INSERT INTO history (label, changes, created_at) VALUES (?1, ?2, ?3)",
+            [
+                label,
+                &serde_json::to_string(&changes)?,
+                &chrono::Utc::now().to_rfc3339(),
+            ],
+        )?;
+        conn.execute(
+            "DELETE FROM history WHERE id <= (SELECT MAX(id) FROM history) - ?1",
+            [HISTORY_LIMIT],
+        )?;
+        Ok(())
+    }
+}
+
+fn icon_state(conn: &Connection, id: &str) -> Result<Option<IconState>> {
+    let row: Option<(String, String, i32, Option<i64>)> = conn
+        .prepare_cached(
+            "SELECT collection_id, name, favorite, revision_id FROM icons WHERE id = ?1",
+        )?
+        .query_row([id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })
+        .optional()?;
+    let Some((collection_id, name, favorite, revision_id)) = row else {
+        return Ok(None);
+    };
+    let tags = conn
+        .prepare_cached("SELECT tag_id FROM icon_tags WHERE icon_id = ?1 ORDER BY rowid")?
+        .query_map([id], |row| row.get(0))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(Some(IconState {
+        collection_id,
+        name,
+        favorite: favorite == 1,
+        tags,
+        revision_id,
+    }))
+}
+
+fn collection_state(conn: &Connection, id: &str) -> Result<Option<CollectionState>> {
+    Ok(conn
+        .query_row(
+            "SELECT name, parent_id, color, created_at, sort_order FROM collections WHERE id = ?1",
+            [id],
+            |row| {
+                Ok(CollectionState {
+                    name: row.get(0)?,
+                    parent_id: row.get(1)?,
+                    color: row.get(2)?,
+                    created_at: row.get(3)?,
+                    sort_order: row.get(4)?,
+                })
+            },
+        )
+        .optional()?)
+}
+
+fn smart_collection_state(conn: &Connection, id: &str) -> Result<Option<SmartCollectionState>> {
+    Ok(conn
+        .query_row(
+            "SELECT name, query, color, created_at FROM smart_collections WHERE id = ?1",
+            [id],
+            |row| {
+                Ok(SmartCollectionState {
+                    name: row.get(0)?,
+                    query: row.get(1)?,
+                    color: row.get(2)?,
+                    created_at: row.get(3)?,
+                })
+            },
+        )
+        .optional()?)
+}
+
+fn tag_states(conn: &Connection) -> Result<HashMap<i64, Option<TagState>>> {
+    let mut stmt = conn.prepare("SELECT id, name, parent_id, color FROM tags")?;
+    let tags = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get(0)?,
+                Some(TagState {
+                    name: row.get(1)?,
+                    parent_id: row.get(2)?,
+                    color: row.get(3)?,
+                }),
+            ))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(tags)
+}
+
+/// Icons carrying any of the given tags or their subtags.
+fn icons_tagged_within(conn: &Connection, tag_ids: &[i64]) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT DISTINCT it.icon_id FROM icon_tags it
+         JOIN tags d ON d.id = it.tag_id
+         JOIN tags t ON t.id = ?1
+         WHERE d.id = t.id OR substr(d.name, 1, length(t.name) + 1) = t.name || '/' COLLATE NOCASE",
+    )?;
+    let mut icons = Vec::new();
+    for id in tag_ids {
+        for icon in stmt.query_map([id], |row| row.get(0))? {
+            icons.push(icon?);
+        }
+    }
+    Ok(icons)
+}
+
+/// Puts the rows an entry changed back as they were before it (`undo`) or
+/// after it. Foreign keys are checked at commit, so rows can be written in
+/// any order; the phases only make sure the trash and the icons find the
+/// collections they live in, and nothing is deleted while still in use.
+fn apply_changes(conn: &Connection, changes: &mut Changes, undo: bool) -> Result<()> {
+    conn.pragma_update(None, "defer_foreign_keys", true)?;
+
+    for (id, tag) in changes.tags.target(undo) {
+        if let Some(tag) = tag {
+            conn.execute(
+                "INSERT INTO tags (id, name, parent_id, color) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(id) DO UPDATE SET
+                     name = excluded.name, parent_id = excluded.parent_id, color = excluded.color",
+                rusqlite::params![id, tag.name, tag.parent_id, tag.color],
+            )?;
+        }
+    }
+    for (id, collection) in changes.collections.target(undo) {
+        if let Some(c) = collection {
+            conn.execute(
+                "INSERT INTO collections (id, name, parent_id, icon_count, color, created_at, sort_order)
+                 VALUES (?1, ?2, ?3, 0, ?4, ?5, ?6)
+                 ON CONFLICT(id) DO UPDATE SET
+                     name = excluded.name, parent_id = excluded.parent_id,
+                     color = excluded.color, sort_order = excluded.sort_order",
+                rusqlite::params![id, c.name, c.parent_id, c.color, c.created_at, c.sort_order],
+            )?;
+        }
+    }
+    for (id, collection) in changes.smart_collections.target(undo) {
+        if let Some(c) = collection {
+            conn.execute(
+                "INSERT INTO smart_collections (id, name, query, color, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)
+                 ON CONFLICT(id) DO UPDATE SET
+                     name = excluded.name, query = excluded.query, color = excluded.color",
+                rusqlite::params![id, c.name, c.query, c.color, c.created_at],
+            )?;
+        }
+    }
+
+    let (restore, remove) = if undo {
+        (&mut changes.trashed, &mut changes.added)
+    } else {
+        (&mut changes.added, &mut changes.trashed)
+    };
+    for item in restore.iter_mut() {
+        let Some(trash_id) = item.trash_id.take() else {
+            continue;
+        };
+        let in_trash = conn
+            .query_row("SELECT 1 FROM trash WHERE id = ?1", [&trash_id], |_| Ok(()))
+            .optional()?
+            .is_some();
+        if !in_trash {
+            return Err(Error::conflict(format!(
+                "the {} is no longer in the trash",
+                item.kind
+            )));
+        }
+        restore_trash_entry(conn, &trash_id)?;
+    }
+
+    for (id, icon) in changes.icons.target(undo) {
+        if let Some(icon) = icon {
+            set_icon_state(conn, id, icon)?;
+        }
+    }
+
+    // In reverse, so icons leave before a collection holding them does
+    for item in remove.iter_mut().rev() {
+        item.trash_id = match item.kind.as_str() {
+            "collection" => trash_collection(conn, &item.item_id)?,
+            _ => trash_icon(conn, &item.item_id)?,
+        };
+    }
+
+    for (id, collection) in changes.collections.target(undo) {
+        if collection.is_none() {
+            // Whatever is still inside goes to the trash rather than away
+            let in_use = conn
+                .query_row(
+                    "SELECT 1 FROM icons WHERE collection_id = ?1
+                     UNION ALL SELECT 1 FROM collections WHERE parent_id = ?1",
+                    [id],
+                    |_| Ok(()),
+                )
+                .optional()?
+                .is_some();
+            if in_use {
+                trash_collection(conn, id)?;
+            } else {
+                conn.execute("DELETE FROM collections WHERE id = ?1", [id])?;
+            }
+        }
+    }
+    for (id, collection) in changes.smart_collections.target(undo) {
+        if collection.is_none() {
+            conn.execute("DELETE FROM smart_collections WHERE id = ?1", [id])?;
+        }
+    }
+    for (id, tag) in changes.tags.target(undo) {
+        if tag.is_none() {
+            conn.execute("DELETE FROM tags WHERE id = ?1", [id])?;
+        }
+    }
+    Ok(())
+}
+
+fn set_icon_state(conn: &Connection, id: &str, icon: &IconState) -> Result<()> {
+    let updated = conn.execute(
+        "UPDATE icons SET collection_id = ?1, name = ?2, favorite = ?3 WHERE id = ?4",
+        rusqlite::params![icon.collection_id, icon.name, icon.favorite as i32, id],
+    )?;
+    if updated == 0 {
+        return Err(Error::conflict(format!("\"{}\" no longer exists", icon.name)).with_icon(id));
+    }
+    conn.execute("DELETE FROM icon_tags WHERE icon_id = ?1", [id])?;
+    for tag in &icon.tags {
+        conn.execute(
+            "INSERT OR IGNORE INTO icon_tags (icon_id, tag_id) SELECT ?1, id FROM tags WHERE id = ?2",
+            rusqlite::params![id, tag],
+        )?;
+    }
+    if let Some(revision_id) = icon.revision_id {
+        let current: Option<i64> =
+            conn.query_row("SELECT revision_id FROM icons WHERE id = ?1", [id], |row| {
+                row.get(0)
+            })?;
+        if current != Some(revision_id) {
+            let svg_content = revision_svg(conn, revision_id)?;
+            let fingerprint = visual::fingerprint(&svg_content);
+            write_icon_svg(conn, id, &svg_content, revision_id, fingerprint.as_ref())?;
+        }
+    }
+    Ok(())
+}
+
+/// The entry `undo` (or redo) would replay next: `(id, label, changes)`.
+fn next_history_entry(conn: &Connection, undo: bool) -> Result<Option<(i64, String, String)>> {
+    let sql = if undo {
+        "SELECT id, label, changes FROM history WHERE undone = 0 ORDER BY id DESC LIMIT 1"
+    } else {
+        "SELECT id, label, changes FROM history WHERE undone = 1 ORDER BY id LIMIT 1"
+    };
+    Ok(conn
+        .query_row(sql, [], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .optional()?)
+}
+
+impl Library {
+    /// Runs an edit in a transaction and logs it under `label`. The edit
+    /// tells the tracker which rows it is about to touch.
+    fn tracked<F, T>(&self, label: &str, f: F) -> Result<T>
+    where
+        F: FnOnce(&Connection, &mut Tracker) -> Result<T>,
+    {
+        self.with_conn(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            let mut tracker = Tracker::default();
+            let value = f(&tx, &mut tracker)?;
+            tracker.record(&tx, label)?;
+            tx.commit()?;
+            Ok(value)
+        })
+    }
+
+    /// Labels of the edits `undo` and `redo` would replay next.
+    pub fn get_history(&self) -> Result<HistoryState> {
+        self.with_conn(|conn| {
+            Ok(HistoryState {
+                undo: next_history_entry(conn, true)?.map(|(_, label, _)| label),
+                redo: next_history_entry(conn, false)?.map(|(_, label, _)| label),
+            })
+        })
+    }
+
+    /// Reverts the last edit that is not undone yet and returns its label,
+    /// or `None` if there is nothing to undo.
+    pub fn undo(&self) -> Result<Option<String>> {
+        self.replay(true)
+    }
+
+    /// Applies the last undone edit again and returns its label, or `None`
+    /// if there is nothing to redo.
+    pub fn redo(&self) -> Result<Option<String>> {
+        self.replay(false)
+    }
+
+    /// If an entry cannot be replayed because what it needs is gone (e.g.
+    /// emptied from the trash), neither can the ones beyond it, so they are
+    /// dropped together with it.
+    fn replay(&self, undo: bool) -> Result<Option<String>> {
+        self.with_conn(|conn| {
+            let Some((id, label, changes)) = next_history_entry(conn, undo)? else {
+                return Ok(None);
+            };
+            let mut changes: Changes = serde_json::from_str(&changes)?;
+
+            let tx = conn.unchecked_transaction()?;
+            let applied = apply_changes(&tx, &mut changes, undo).and_then(|_| {
+                tx.execute(
+                    "UPDATE history SET undone = ?1, changes = ?2 WHERE id = ?3",
+                    rusqlite::params![undo, serde_json::to_string(&changes)?, id],
+                )?;
+                Ok(())
+            });
+            if let Err(e) = applied {
+                drop(tx);
+                let sql = if undo {
+                    "DELETE FROM history WHERE undone = 0 AND id <= ?1"
+                } else {
+                    "DELETE FROM history WHERE undone = 1 AND id >= ?1"
+                };
+                conn.execute(sql, [id])?;
+                let action = if undo { "undo" } else { "redo" };
+                return Err(e.while_doing(&format!("Cannot {} \"{}\"", action, label)));
+            }
+            tx.commit()?;
+            Ok(Some(label))
+        })
+    }
+}
+
+// ── Revisions ────────────────────────────────────────────────
+//
+// Every version of an icon's artwork is kept in `icon_revisions`, with why
+// it changed; `icons.revision_id` points at the one in use. Editing the SVG
+// adds a revision, and restoring an old one adds a copy of it, so the list
+// only ever grows. Revisions are not tied to the `icons` row: they follow
+// an icon into the trash and are deleted once it is deleted for good.
+
+/// Why an icon's artwork changed.
+const REVISION_REASONS: &[&str] = &["import", "optimize", "edit", "sync", "restore"];
+
+/// Version 4: icon revisions. Existing icons start with their current
+/// artwork as the imported revision.
+fn migrate_revisions(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE icon_revisions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            icon_id TEXT NOT NULL,
+            svg_content TEXT NOT NULL,
+            reason TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        CREATE INDEX idx_icon_revisions_icon ON icon_revisions(icon_id);
+        ALTER TABLE icons ADD COLUMN revision_id INTEGER;
+        ALTER TABLE trash_icons ADD COLUMN revision_id INTEGER;
+        INSERT INTO icon_revisions (icon_id, svg_content, reason, created_at)
+            SELECT id, svg_content, 'import', created_at FROM icons;
+        INSERT INTO icon_revisions (icon_id, svg_content, reason, created_at)
+            SELECT id, svg_content, 'import', created_at FROM trash_icons;
+        UPDATE icons SET revision_id =
+            (SELECT MAX(id) FROM icon_revisions WHERE icon_id = icons.id);
+        UPDATE trash_icons SET revision_id =
+            (SELECT MAX(id) FROM icon_revisions WHERE icon_id = trash_icons.id);",
+    )?;
+    Ok(())
+}
+
+fn add_revision(
+    conn: &Connection,
+    icon_id: &str,
+    svg_content: &str,
+    reason: &str,
+    created_at: &str,
+) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO icon_revisions (icon_id, svg_content, reason, created_at)
+         VALUES (?1, ?2, ?3, ?4)",
+        [icon_id, svg_content, reason, created_at],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+fn revision_not_found(revision_id: i64) -> Error {
+    Error::not_found(format!("Revision {} not found", revision_id))
+}
+
+fn revision_svg(conn: &Connection, revision_id: i64) -> Result<String> {
+    conn.query_row(
+        "SELECT svg_content FROM icon_revisions WHERE id = ?1",
+        [revision_id],
+        |row| row.get(0),
+    )
+    .optional()?
+    .ok_or_else(|| revision_not_found(revision_id))
+}
+
+/// Makes `svg_content` (stored as `revision_id`) the icon's artwork and
+/// refreshes what is derived from it.
+fn write_icon_svg(
+    conn: &Connection,
+    icon_id: &str,
+    svg_content: &str,
+    revision_id: i64,
+    fingerprint: Option<&visual::Fingerprint>,
+) -> Result<()> {
+    let text = svg::extract_text(svg_content);
+    let updated = conn.execute(
+        "UPDATE icons SET svg_content = ?1, file_size = ?2, title = ?3, description = ?4,
+             revision_id = ?5
+         WHERE id = ?6",
+        rusqlite::params![
+            svg_content,
+            svg_content.len() as i64,
+            text.title,
+            text.description,
+            revision_id,
+            icon_id,
+        ],
+    )?;
+    if updated == 0 {
+        return Err(icon_not_found(icon_id));
+    }
+    save_fingerprint(conn, icon_id, fingerprint)?;
+    save_palette(conn, icon_id, &palette::extract(svg_content))?;
+    Ok(())
+}
+
+/// Deletes the revisions of icons that are neither in the library nor in
+/// the trash.
+fn delete_orphan_revisions(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "DELETE FROM icon_revisions
+         WHERE icon_id NOT IN (SELECT id FROM icons)
+           AND icon_id NOT IN (SELECT id FROM trash_icons)",
+        [],
+    )?;
+    Ok(())
+}
+
+const REVISION_COLUMNS: &str = "r.id, r.icon_id, r.svg_content, r.reason, r.created_at,
+    COALESCE(r.id = (SELECT revision_id FROM icons WHERE id = r.icon_id), 0)";
+
+fn row_to_revision(row: &rusqlite::Row) -> rusqlite::Result<IconRevision> {
+    let svg_content: String = row.get(2)?;
+    Ok(IconRevision {
+        id: row.get(0)?,
+        icon_id: row.get(1)?,
+        file_size: svg_content.len() as i64,
+        svg_content,
+        reason: row.get(3)?,
+        created_at: row.get(4)?,
+        current: row.get(5)?,
+    })
+}
+
+fn get_revision(conn: &Connection, revision_id: i64) -> Result<IconRevision> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM icon_revisions r WHERE r.id = ?1",
+            REVISION_COLUMNS
+        ),
+        [revision_id],
+        row_to_revision,
+    )
+    .optional()?
+    .ok_or_else(|| revision_not_found(revision_id))
+}
+
+impl Library {
+    /// An icon's revisions, newest first.
+    pub fn get_icon_revisions(&self, icon_id: &str) -> Result<Vec<IconRevision>> {
+        self.with_conn(|conn| {
+            get_icon(conn, icon_id)?;
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM icon_revisions r WHERE r.icon_id = ?1 ORDER BY r.id DESC",
+                REVISION_COLUMNS
+            ))?;
+            let revisions = stmt
+                .query_map([icon_id], row_to_revision)?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(revisions)
+        })
+    }
+
+    /// Replaces an icon's artwork, keeping the old one as a revision.
+    /// `reason` is one of `REVISION_REASONS`. Returns the updated icon;
+    /// nothing is recorded if the SVG did not change.
+    pub fn update_icon_svg(&self, icon_id: &str, svg_content: &str, reason: &str) -> Result<Icon> {
+        let label = match reason {
+            "import" => "Re-import icon",
+            "optimize" => "Optimize icon",
+            "edit" => "Edit icon",
+            "sync" => "Sync icon",
+            "restore" => "Restore icon version",
+            _ => {
+                return Err(Error::invalid(format!(
+                    "Unknown revision reason \"{}\" (expected one of: {})",
+                    reason,
+                    REVISION_REASONS.join(", ")
+                )))
+            }
+        };
+        if !svg_content.contains("<svg") {
+            return Err(Error::invalid("The new artwork is not an SVG").with_icon(icon_id));
+        }
+        // Rasterizing is the slow part, keep it outside the lock
+        let fingerprint = visual::fingerprint(svg_content);
+
+        self.tracked(label, |conn, tracker| {
+            if get_icon(conn, icon_id)?.svg_content != svg_content {
+                tracker.icons(conn, [icon_id])?;
+                let now = chrono::Utc::now().to_rfc3339();
+                let revision_id = add_revision(conn, icon_id, svg_content, reason, &now)?;
+                write_icon_svg(
+                    conn,
+                    icon_id,
+                    svg_content,
+                    revision_id,
+                    fingerprint.as_ref(),
+                )?;
+            }
+            get_icon(conn, icon_id)
+        })
+    }
+
+    /// Makes an earlier revision the icon's artwork again, as a new
+    /// revision. Returns the updated icon.
+    pub fn restore_icon_revision(&self, revision_id: i64) -> Result<Icon> {
+        let revision = self.with_conn(|conn| get_revision(conn, revision_id))?;
+        self.update_icon_svg(&revision.icon_id, &revision.svg_content, "restore")
+    }
+
+    /// Compares two revisions of the same icon, markup and looks.
+    pub fn diff_icon_revisions(&self, from_id: i64, to_id: i64) -> Result<RevisionDiff> {
+        let (from, to) =
+            self.with_conn(|conn| Ok((get_revision(conn, from_id)?, get_revision(conn, to_id)?)))?;
+        if from.icon_id != to.icon_id {
+            return Err(
+                Error::invalid("Revisions of different icons cannot be compared")
+                    .with_icon(from.icon_id),
+            );
+        }
+        let lines = diff::diff_svg(&from.svg_content, &to.svg_content);
+        let visual_distance = visual::fingerprint(&from.svg_content)
+            .zip(visual::fingerprint(&to.svg_content))
+            .map(|(a, b)| visual::distance(&a, &b));
+        Ok(RevisionDiff {
+            from,
+            to,
+            lines,
+            visual_distance,
+        })
+    }
+}
+
+// ── Archives ─────────────────────────────────────────────────
+//
+// Export collects everything under the lock and writes the zip outside it;
+// the format itself is in archive.rs. Import merges into the library the way
+// `merge_collections` does: collections with the same name under the same
+// parent are merged, icons with the same name and SVG count as duplicates,
+// and `on_conflict` decides what happens to an icon whose name is taken by
+// different artwork.
+
+/// What to do with an imported icon whose name is taken by a different one.
+const ARCHIVE_CONFLICT_POLICIES: &[&str] = &["skip", "replace", "keep_both"];
+
+/// The manifest and SVG files of the given collections and their
+/// subcollections, or of the whole library for `None`.
+fn archive_contents(
+    conn: &Connection,
+    collection_ids: Option<&[String]>,
+) -> Result<(archive::Manifest, Vec<(String, String)>)> {
+    let all: Vec<archive::ArchiveCollection> = conn
+        .prepare(
+            "SELECT id, name, parent_id, color, created_at FROM collections
+             ORDER BY sort_order IS NULL, sort_order, name",
+        )?
+        .query_map([], |row| {
+            Ok(archive::ArchiveCollection {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                parent_id: row.get(2)?,
+                color: row.get(3)?,
+                created_at: row.get(4)?,
+            })
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+
+    let included: HashSet<String> = match collection_ids {
+        Some([]) => return Err(Error::invalid("Choose at least one collection to export")),
+        Some(ids) => {
+            let mut included = HashSet::new();
+            for id in ids {
+                ensure_collection_exists(conn, id)?;
+                included.extend(collection_subtree(conn, id)?);
+            }
+            included
+        }
+        None => all.iter().map(|c| c.id.clone()).collect(),
+    };
+
+    // Parents first, each collection in a folder named after it
+    let mut children: HashMap<Option<String>, Vec<archive::ArchiveCollection>> = HashMap::new();
+    for mut collection in all.into_iter().filter(|c| included.contains(&c.id)) {
+        if !collection
+            .parent_id
+            .as_ref()
+            .is_some_and(|p| included.contains(p))
+        {
+            collection.parent_id = None;
+        }
+        children
+            .entry(collection.parent_id.clone())
+            .or_default()
+            .push(collection);
+    }
+    let mut taken: HashMap<String, HashSet<String>> = HashMap::new();
+    let mut folders: HashMap<String, String> = HashMap::new();
+    let mut collections = Vec::new();
+    let mut pending = vec![None];
+    while let Some(parent) = pending.pop() {
+        let parent_folder = match &parent {
+            Some(id) => folders[id].clone(),
+            None => "icons".to_string(),
+        };
+        for collection in children.remove(&parent).unwrap_or_default() {
+            let name = archive::file_name(
+                &collection.name,
+                "",
+                taken.entry(parent_folder.clone()).or_default(),
+            );
+            folders.insert(collection.id.clone(), format!("{}/{}", parent_folder, name));
+            pending.push(Some(collection.id.clone()));
+            collections.push(collection);
+        }
+    }
+
+    let mut icons = Vec::new();
+    let mut files = Vec::new();
+    let mut used_tags = HashSet::new();
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM icons i WHERE i.collection_id = ?1 ORDER BY i.name COLLATE NOCASE",
+        ICON_COLUMNS
+    ))?;
+    for collection in &collections {
+        let folder = &folders[&collection.id];
+        for icon in stmt.query_map([&collection.id], row_to_icon)? {
+            let icon = icon?;
+            let taken = taken.entry(folder.clone()).or_default();
+            let name = archive::file_name(&icon.name, ".svg", taken);
+            let file = format!("{}/{}", folder, name);
+            for tag in &icon.tags {
+                let mut path = Some(tag.as_str());
+                while let Some(p) = path {
+                    used_tags.insert(p.to_lowercase());
+                    path = parent_path(p);
+                }
+            }
+            icons.push(archive::ArchiveIcon {
+                name: icon.name,
+                collection_id: icon.collection_id,
+                file: file.clone(),
+                path: icon.path,
+                tags: icon.tags,
+                favorite: icon.favorite,
+                created_at: icon.created_at,
+            });
+            files.push((file, icon.svg_content));
+        }
+    }
+
+    // Tag colors, and for the whole library the tags no icon uses
+    let whole = collection_ids.is_none();
+    let mut tags = Vec::new();
+    let mut stmt = conn.prepare(
+        "SELECT name, color, EXISTS (SELECT 1 FROM icon_tags WHERE tag_id = tags.id)
+         FROM tags ORDER BY name",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, Option<String>>(1)?,
+            row.get::<_, bool>(2)?,
+        ))
+    })?;
+    for row in rows {
+        let (name, color, used) = row?;
+        let wanted = if whole {
+            color.is_some() || !used
+        } else {
+            color.is_some() && used_tags.contains(&name.to_lowercase())
+        };
+        if wanted {
+            tags.push(archive::ArchiveTag { name, color });
+        }
+    }
+
+    let smart_collections = if whole {
+        conn.prepare("SELECT name, query, color FROM smart_collections ORDER BY name")?
+            .query_map([], |row| {
+                Ok(archive::ArchiveSmartCollection {
+                    name: row.get(0)?,
+                    query: row.get(1)?,
+                    color: row.get(2)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?
+    } else {
+        Vec::new()
+    };
+
+    let manifest = archive::Manifest {
+        format: archive::FORMAT.to_string(),
+        version: archive::VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        collections,
+        smart_collections,
+        tags,
+        icons,
+    };
+    Ok((manifest, files))
+}
+
+/// Merges an archive into the library under `parent_id` (the top level for
+/// `None`). `fingerprints` holds one entry per icon, in manifest order.
+fn import_archive_into(
+    conn: &Connection,
+    tracker: &mut Tracker,
+    archive: &archive::Archive,
+    fingerprints: &[Option<visual::Fingerprint>],
+    parent_id: Option<&str>,
+    on_conflict: &str,
+) -> Result<ArchiveImportResult> {
+    if let Some(parent) = parent_id {
+        ensure_collection_exists(conn, parent)?;
+    }
+    let manifest = &archive.manifest;
+    let mut result = ArchiveImportResult::default();
+
+    tracker.tags(conn)?;
+    for tag in &manifest.tags {
+        let id = ensure_tag(conn, &tag.name)?;
+        // A color already chosen in this library wins
+        if let Some(color) = &tag.color {
+            conn.execute(
+                "UPDATE tags SET color = ?1 WHERE id = ?2 AND color IS NULL",
+                rusqlite::params![color, id],
+            )?;
+        }
+    }
+
+    // Archive collection id -> library collection id
+    let mut mapped: HashMap<&str, String> = HashMap::new();
+    let mut created: HashSet<String> = HashSet::new();
+    for collection in &manifest.collections {
+        let parent = match &collection.parent_id {
+            Some(id) => Some(mapped.get(id.as_str()).cloned().ok_or_else(|| {
+                Error::invalid(format!("\"{}\" comes before its parent", collection.name))
+            })?),
+            None => parent_id.map(str::to_string),
+        };
+        let existing: Option<String> = conn
+            .query_row(
+                "SELECT id FROM collections WHERE parent_id IS ?1 AND name = ?2 COLLATE NOCASE",
+                rusqlite::params![parent, collection.name],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let id = match existing {
+            Some(id) => {
+                result.collections_merged += 1;
+                id
+            }
+            None => {
+                let mut new = new_collection(&collection.name, parent.as_deref());
+                if let Some(color) = &collection.color {
+                    new.color = color.clone();
+                }
+                new.created_at = collection.created_at.clone();
+                insert_collection(conn, &new)?;
+                // Undoing the import trashes the top-most new collections
+                // along with everything inside them
+                if !parent.as_ref().is_some_and(|p| created.contains(p)) {
+                    tracker.added("collection", &new.id);
+                }
+                created.insert(new.id.clone());
+                result.collections_created += 1;
+                new.id
+            }
+        };
+        mapped.insert(&collection.id, id);
+    }
+
+    // Icons already in each collection, by lowercased name
+    let mut names: HashMap<String, HashMap<String, Vec<(String, String)>>> = HashMap::new();
+    let now = chrono::Utc::now().to_rfc3339();
+    for ((icon, svg), fingerprint) in manifest.icons.iter().zip(&archive.svgs).zip(fingerprints) {
+        let collection_id = &mapped[icon.collection_id.as_str()];
+        let names = match names.entry(collection_id.clone()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let mut existing: HashMap<String, Vec<(String, String)>> = HashMap::new();
+                let rows: Vec<(String, String, String)> = conn
+                    .prepare("SELECT id, name, svg_content FROM icons WHERE collection_id = ?1")?
+                    .query_map([collection_id], |row| {
+                        Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+                    })?
+                    .collect::<rusqlite::Result<_>>()?;
+                for (id, name, svg) in rows {
+                    existing
+                        .entry(name.to_lowercase())
+                        .or_default()
+                        .push((id, svg));
+                }
+                entry.insert(existing)
+            }
+        };
+
+        let same_name = names.get(&icon.name.to_lowercase());
+        let duplicate = same_name.and_then(|icons| icons.iter().find(|(_, s)| s == svg));
+        if let Some((kept, _)) = duplicate {
+            // Same name and content: keep the existing one, with the archive's
+            // tags and favorite added
+            tracker.icons(conn, [kept.as_str()])?;
+            add_icon_tags(conn, kept, &icon.tags)?;
+            if icon.favorite {
+                conn.execute("UPDATE icons SET favorite = 1 WHERE id = ?1", [kept])?;
+            }
+            result.duplicates_merged += 1;
+            continue;
+        }
+
+        let mut name = icon.name.clone();
+        if let Some(same_name) = same_name {
+            match on_conflict {
+                "replace" => {
+                    let target = same_name[0].0.clone();
+                    tracker.icons(conn, [target.as_str()])?;
+                    let revision_id = add_revision(conn, &target, svg, "import", &now)?;
+                    write_icon_svg(conn, &target, svg, revision_id, fingerprint.as_ref())?;
+                    add_icon_tags(conn, &target, &icon.tags)?;
+                    if icon.favorite {
+                        conn.execute("UPDATE icons SET favorite = 1 WHERE id = ?1", [&target])?;
+                    }
+                    if let Some(icons) = names.get_mut(&icon.name.to_lowercase()) {
+                        icons[0].1 = svg.clone();
+                    }
+                    result.icons_replaced += 1;
+                    continue;
+                }
+                "keep_both" => {
+                    name = (2..)
+                        .map(|n| format!("{} ({})", icon.name, n))
+                        .find(|candidate| !names.contains_key(&candidate.to_lowercase()))
+                        .unwrap_or_default();
+                    result.icons_renamed += 1;
+                }
+                _ => {
+                    result.icons_skipped += 1;
+                    continue;
+                }
+            }
+        }
+
+        let new = Icon {
+            id: Uuid::new_v4().to_string(),
+            name,
+            path: icon.path.clone(),
+            svg_content: svg.clone(),
+            tags: icon.tags.clone(),
+            collection_id: collection_id.clone(),
+            created_at: icon.created_at.clone(),
+            file_size: svg.len() as i64,
+            favorite: icon.favorite,
+        };
+        insert_icon(conn, &new, fingerprint.as_ref())?;
+        if !created.contains(collection_id) {
+            tracker.added("icon", &new.id);
+        }
+        names
+            .entry(new.name.to_lowercase())
+            .or_default()
+            .push((new.id, new.svg_content));
+        result.icons_added += 1;
+    }
+
+    for smart in &manifest.smart_collections {
+        let exists: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM smart_collections WHERE name = ?1 COLLATE NOCASE)",
+            [&smart.name],
+            |row| row.get(0),
+        )?;
+        if exists || query::parse(&smart.query).is_err() {
+            continue;
+        }
+        let collection = Collection {
+            id: Uuid::new_v4().to_string(),
+            name: smart.name.clone(),
+            parent_id: None,
+            icon_count: 0,
+            color: smart.color.clone(),
+            created_at: now.clone(),
+            query: Some(smart.query.clone()),
+            sort_order: None,
+            total_icon_count: 0,
+        };
+        tracker.smart_collections(conn, &collection.id)?;
+        insert_smart_collection(conn, &collection)?;
+        result.smart_collections_created += 1;
+    }
+    Ok(result)
+}
+
+fn check_conflict_policy(on_conflict: &str) -> Result<()> {
+    if ARCHIVE_CONFLICT_POLICIES.contains(&on_conflict) {
+        return Ok(());
+    }
+    Err(Error::invalid(format!(
+        "Unknown conflict policy \"{}\" (expected one of: {})",
+        on_conflict,
+        ARCHIVE_CONFLICT_POLICIES.join(", ")
+    )))
+}
+
+impl Library {
+    /// Exports the given collections with their subcollections, or the whole
+    /// library for `None`, as an archive at `path`. Smart collections and
+    /// unused tags only come along with the whole library.
+    pub fn export_archive(
+        &self,
+        path: &Path,
+        collection_ids: Option<&[String]>,
+    ) -> Result<ArchiveExportResult> {
+        let (manifest, files) = self.with_conn(|conn| archive_contents(conn, collection_ids))?;
+        archive::write(path, &manifest, &files)?;
+        Ok(ArchiveExportResult {
+            path: path.to_string_lossy().to_string(),
+            collections: manifest.collections.len(),
+            icons: manifest.icons.len(),
+        })
+    }
+
+    /// Imports an archive under `parent_id` (the top level for `None`).
+    /// `on_conflict` is one of `ARCHIVE_CONFLICT_POLICIES`. Undoing the
+    /// import removes what it added and puts back what it changed.
+    pub fn import_archive(
+        &self,
+        path: &Path,
+        parent_id: Option<&str>,
+        on_conflict: &str,
+    ) -> Result<ArchiveImportResult> {
+        check_conflict_policy(on_conflict)?;
+        let archive = archive::read(path)?;
+        // Rasterizing is the slow part, keep it outside the lock
+        let fingerprints: Vec<_> = archive
+            .svgs
+            .iter()
+            .map(|svg| visual::fingerprint(svg))
+            .collect();
+        self.tracked("Import archive", |conn, tracker| {
+            import_archive_into(
+                conn,
+                tracker,
+                &archive,
+                &fingerprints,
+                parent_id,
+                on_conflict,
+            )
+        })
+    }
+
+    /// What importing an archive with `on_conflict` would do, without
+    /// changing anything.
+    pub fn preview_archive_import(
+        &self,
+        path: &Path,
+        parent_id: Option<&str>,
+        on_conflict: &str,
+    ) -> Result<ArchiveImportResult> {
+        check_conflict_policy(on_conflict)?;
+        let archive = archive::read(path)?;
+        let fingerprints: Vec<_> = archive.svgs.iter().map(|_| None).collect();
+        self.with_conn(|conn| {
+            // Dropped without committing, so it rolls back
+            let _tx = conn.unchecked_transaction()?;
+            let mut tracker = Tracker::default();
+            import_archive_into(
+                conn,
+                &mut tracker,
+                &archive,
+                &fingerprints,
+                parent_id,
+                on_conflict,
+            )
+        })
+    }
+}
+
+// ── Backups ──────────────────────────────────────────────────
+//
+// Where and how often the library is backed up is kept in the settings; the
+// copying itself is in backup.rs. The schedule is driven from startup.rs,
+// which asks `backup_if_due` every few minutes.
+
+const BACKUP_FOLDER_KEY: &str = "backup_folder";
+const BACKUP_INTERVAL_KEY: &str = "backup_interval_hours";
+const BACKUP_KEEP_KEY: &str = "backup_keep";
+const DEFAULT_BACKUP_INTERVAL_HOURS: i64 = 24;
+const DEFAULT_BACKUP_KEEP: i64 = 10;
+
+/// Hours between scheduled backups; 0 turns them off.
+fn backup_interval_hours(conn: &Connection) -> i64 {
+    get_setting(conn, BACKUP_INTERVAL_KEY)
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_BACKUP_INTERVAL_HOURS)
+        .max(0)
+}
+
+/// How many scheduled or manual backups are kept; at least one.
+fn backup_keep(conn: &Connection) -> i64 {
+    get_setting(conn, BACKUP_KEEP_KEY)
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_BACKUP_KEEP)
+        .max(1)
+}
+
+impl Library {
+    /// The folder backups go to: the one chosen in the settings, or
+    /// `backups/` next to the library file. `None` for an in-memory library
+    /// without a chosen folder.
+    pub fn backup_dir(&self) -> Result<Option<PathBuf>> {
+        let configured = self
+            .with_conn(|conn| Ok(get_setting(conn, BACKUP_FOLDER_KEY).filter(|f| !f.is_empty())))?;
+        Ok(configured
+            .map(PathBuf::from)
+            .or_else(|| self.data_dir.as_ref().map(|dir| dir.join("backups"))))
+    }
+
+    /// Writes a backup now and drops the oldest ones beyond the number to
+    /// keep. A file library is copied through a connection of its own, so
+    /// edits can go on meanwhile.
+    pub fn backup_now(&self) -> Result<DbBackup> {
+        let dir = self
+            .backup_dir()?
+            .ok_or_else(|| Error::invalid("Choose a folder for backups first"))?;
+        let (file, keep) = self.with_conn(|conn| {
+            let file = conn.path().filter(|p| !p.is_empty()).map(PathBuf::from);
+            Ok((file, backup_keep(conn)))
+        })?;
+        let path = match file {
+            Some(file) => {
+                let source = Connection::open_with_flags(&file, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+                backup::write(&source, &dir)?
+            }
+            None => self.with_conn(|conn| backup::write(conn, &dir))?,
+        };
+        backup::rotate(&dir, keep as usize)?;
+        backup::describe(&path).ok_or_else(|| {
+            Error::not_found("The new backup disappeared").with_path(path.to_string_lossy())
+        })
+    }
+
+    /// Backs up if scheduled backups are on and the last one is older than
+    /// the interval. Returns the new backup, if one was written.
+    pub fn backup_if_due(&self) -> Result<Option<DbBackup>> {
+        let hours = self.with_conn(|conn| Ok(backup_interval_hours(conn)))?;
+        let Some(dir) = self.backup_dir()? else {
+            return Ok(None);
+        };
+        if hours == 0 {
+            return Ok(None);
+        }
+        let interval = std::time::Duration::from_secs(hours as u64 * 3600);
+        let due = backup::last_written(&dir)
+            .and_then(|last| last.elapsed().ok())
+            .is_none_or(|age| age >= interval);
+        if !due {
+            return Ok(None);
+        }
+        self.backup_now().map(Some)
+    }
+
+    /// The backups in the backup folder, newest first.
+    pub fn get_backups(&self) -> Result<Vec<DbBackup>> {
+        Ok(self
+            .backup_dir()?
+            .map(|dir| backup::list(&dir))
+            .unwrap_or_default())
+    }
+}
+
+// ── Settings ─────────────────────────────────────────────────
+
+impl Library {
+    pub fn get_settings(&self) -> Result<Settings> {
+        self.with_conn(|conn| {
+            let get = |key: &str, default: &str| -> String {
+                conn.prepare("SELECT value FROM settings WHERE key = ?1")
+                    .and_then(|mut s| s.query_row([key], |row| row.get(0)))
+                    .unwrap_or_else(|_| default.to_string())
+            };
+
+            Ok(Settings {
+                theme: get("theme", "light"),
+                icon_size: get("icon_size", "64").parse().unwrap_or(64),
+                tint_color: {
+                    let v = get("tint_color", "");
+                    if v.is_empty() {
+                        None
+                    } else {
+                        Some(v)
+                    }
+                },
+                autotag_stop_words: get("autotag_stop_words", &autotag::default_stop_words()),
+                trash_retention_days: trash_retention_days(conn),
+                backup_folder: get_setting(conn, BACKUP_FOLDER_KEY).filter(|f| !f.is_empty()),
+                backup_interval_hours: backup_interval_hours(conn),
+                backup_keep: backup_keep(conn),
+            })
+        })
+    }
+
+    pub fn save_setting(&self, key: &str, value: &str) -> Result<()> {
+        self.with_conn(|conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+                [key, value],
+            )?;
+            Ok(())
+        })
+    }
+}
+fn get_setting(conn: &Connection, key: &str) -> Option<String> {
+    conn.query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| {
+        row.get(0)
+    })
+    .ok()
+}
+
+// ── Stats ────────────────────────────────────────────────────
+
+impl Library {
+    pub fn get_total_icon_count(&self) -> Result<i32> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare("SELECT COUNT(*) FROM icons")?;
+            let count: i32 = stmt.query_row([], |row| row.get(0))?;
+            Ok(count)
+        })
+    }
+
+    pub fn get_favorite_count(&self) -> Result<i32> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare("SELECT COUNT(*) FROM icons WHERE favorite = 1")?;
+            let count: i32 = stmt.query_row([], |row| row.get(0))?;
+            Ok(count)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A library in the original schema (tags as a comma-joined column),
+    /// brought up to date by the baseline migration.
+    fn library() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "PRAGMA foreign_keys=ON;
+            CREATE TABLE collections (
+                id TEXT PRIMARY KEY, name TEXT NOT NULL, parent_id TEXT,
+                icon_count INTEGER DEFAULT 0, color TEXT, created_at TEXT NOT NULL
+            );
+            CREATE TABLE icons (
+                id TEXT PRIMARY KEY, name TEXT NOT NULL, path TEXT,
+                svg_content TEXT NOT NULL, tags TEXT DEFAULT '',
+                collection_id TEXT NOT NULL, created_at TEXT NOT NULL,
+                file_size INTEGER DEFAULT 0, favorite INTEGER DEFAULT 0
+            );
+            INSERT INTO collections (id, name, icon_count, color, created_at)
+            VALUES ('c', 'Pack', 7, '#888', '2024-01-01'), ('d', 'Other', 0, '#888', '2024-01-01');",
+        )
+        .unwrap();
+        for (id, name, tags, size) in [
+            ("1", "arrow-left", "nav", 300),
+            ("2", "arrow-right", "nav, UI,ui,", 3000),
+            ("3", "trash", "delete", 200),
+        ] {
+            conn.execute(
+                "INSERT INTO icons (id, name, path, svg_content, tags, collection_id, created_at, file_size)
+                 VALUES (?1, ?2, '/icons/' || ?2 || '.svg', '<svg><title>Sign</title></svg>', ?3, 'c', '2024-01-01', ?4)",
+                rusqlite::params![id, name, tags, size],
+            )
+            .unwrap();
+        }
+        for migration in MIGRATIONS {
+            (migration.up)(&conn).unwrap();
+        }
+        conn
+    }
+
+    fn tags_of(conn: &Connection, icon_id: &str) -> Vec<String> {
+        conn.prepare(
+            "SELECT t.name FROM icon_tags it JOIN tags t ON t.id = it.tag_id
+             WHERE it.icon_id = ?1 ORDER BY t.name",
+        )
+        .unwrap()
+        .query_map([icon_id], |row| row.get(0))
+        .unwrap()
+        .collect::<rusqlite::Result<_>>()
+        .unwrap()
+    }
+
+    fn tag_id(conn: &Connection, name: &str) -> i64 {
+        conn.query_row("SELECT id FROM tags WHERE name = ?1", [name], |row| {
+            row.get(0)
+        })
+        .unwrap()
+    }
+
+    fn count(conn: &Connection, query: &str, thesaurus: &Thesaurus) -> i64 {
+        count_matches(conn, &query::parse(query).unwrap(), thesaurus).unwrap()
+    }
+
+    #[test]
+    fn smart_collection_counts_match_exactly() {
+        let conn = library();
+        let none = Thesaurus::new(Vec::<Vec<String>>::new());
+        assert_eq!(count(&conn, "", &none), 3);
+        assert_eq!(count(&conn, "arrow", &none), 2);
+        assert_eq!(count(&conn, "arrow size:<1kb", &none), 1);
+        assert_eq!(count(&conn, "tag:nav -tag:ui", &none), 1);
+        // No fuzzy fallback: a typo counts nothing
+        assert_eq!(count(&conn, "arow", &none), 0);
+
+        let bin = Thesaurus::new([vec!["bin".to_string(), "trash".to_string()]]);
+        assert_eq!(count(&conn, "bin", &none), 0);
+        assert_eq!(count(&conn, "bin", &bin), 1);
+    }
+
+    #[test]
+    fn tags_column_migrates_into_shared_tags() {
+        let conn = library();
+        assert_eq!(tags_of(&conn, "1"), ["nav"]);
+        assert_eq!(tags_of(&conn, "2"), ["nav", "UI"]);
+        let tags: i64 = conn
+            .query_row("SELECT COUNT(*) FROM tags", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(tags, 3);
+        let has_column: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('icons') WHERE name='tags'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(has_column, 0);
+    }
+
+    #[test]
+    fn icon_tags_are_trimmed_and_deduplicated_case_insensitively() {
+        let conn = library();
+        let tags = [" Nav ", "", "NAV", "new"].map(String::from);
+        set_icon_tags(&conn, "3", &tags).unwrap();
+        // "Nav" reuses the existing "nav" tag
+        assert_eq!(tags_of(&conn, "3"), ["nav", "new"]);
+        assert_eq!(tag_id(&conn, "NAV"), tag_id(&conn, "nav"));
+    }
+
+    #[test]
+    fn merging_tags_moves_their_icons() {
+        let conn = library();
+        let nav = tag_id(&conn, "nav");
+        merge_tags_into(&conn, &[tag_id(&conn, "ui"), nav], nav).unwrap();
+        assert_eq!(tags_of(&conn, "2"), ["nav"]);
+        assert_eq!(tags_of(&conn, "1"), ["nav"]);
+        assert!(conn
+            .query_row("SELECT id FROM tags WHERE name = 'ui'", [], |row| row
+                .get::<_, i64>(0))
+            .optional()
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn tag_paths_create_their_ancestors_in_the_stored_casing() {
+        let conn = library();
+        let outline = ensure_tag(&conn, " Style / outline ").unwrap();
+        assert_eq!(tag_path(&conn, outline).unwrap(), "Style/outline");
+        let style = tag_id(&conn, "style");
+        assert_eq!(
+            subtags(&conn, style).unwrap(),
+            [(outline, "Style/outline".to_string())]
+        );
+
+        let filled = ensure_tag(&conn, "STYLE/filled").unwrap();
+        assert_eq!(tag_path(&conn, filled).unwrap(), "Style/filled");
+        assert!(ensure_tag(&conn, " / ").is_err());
+    }
+
+    #[test]
+    fn moving_a_tag_carries_its_subtags() {
+        let conn = library();
+        let outline = ensure_tag(&conn, "Style/outline/thin").unwrap();
+        ensure_tag(&conn, "Weight").unwrap();
+        let style_outline = tag_id(&conn, "style/outline");
+
+        move_tag(&conn, style_outline, "weight/Lines").unwrap();
+        assert_eq!(tag_path(&conn, style_outline).unwrap(), "Weight/Lines");
+        assert_eq!(tag_path(&conn, outline).unwrap(), "Weight/Lines/thin");
+
+        // Case-only renames are allowed, moving under itself is not
+        move_tag(&conn, style_outline, "Weight/lines").unwrap();
+        assert_eq!(tag_path(&conn, outline).unwrap(), "Weight/lines/thin");
+        assert!(move_tag(&conn, style_outline, "weight/LINES/thin/x").is_err());
+
+        // Onto an existing tag, the two merge
+        move_tag(&conn, tag_id(&conn, "ui"), "nav").unwrap();
+        assert_eq!(tags_of(&conn, "2"), ["nav"]);
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    fn collection_of(conn: &Connection, icon_id: &str) -> String {
+        icon_collection(conn, icon_id).unwrap()
+    }
+
+    fn icon_count(conn: &Connection, collection_id: &str) -> i32 {
+        conn.query_row(
+            "SELECT icon_count FROM collections WHERE id = ?1",
+            [collection_id],
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn bulk_skips_duplicates_and_reports_missing_ids() {
+        let lib = Library::new(library(), None, None);
+        let mut seen = Vec::new();
+        let result = lib
+            .bulk("Test", &ids(&["1", "x", "1", "3"]), |_, id, _| {
+                seen.push(id.to_string());
+                Ok(id == "1")
+            })
+            .unwrap();
+        assert_eq!(seen, ["1", "3"]);
+        assert_eq!((result.processed, result.changed), (2, 1));
+        assert_eq!(result.missing, ["x"]);
+    }
+
+    #[test]
+    fn a_failing_bulk_action_changes_nothing() {
+        let lib = Library::new(library(), None, None);
+        let err = lib
+            .bulk("Test", &ids(&["1", "2"]), |conn, id, _| {
+                conn.execute("UPDATE icons SET collection_id = 'd' WHERE id = ?1", [id])?;
+                ensure_collection_exists(conn, if id == "2" { "gone" } else { "d" })?;
+                Ok(true)
+            })
+            .unwrap_err();
+        assert!(err.to_string().contains("Collection 'gone' not found"));
+        let conn = lib.conn.lock().unwrap();
+        assert_eq!(collection_of(&conn, "1"), "c");
+    }
+
+    #[test]
+    fn copies_keep_tags_and_derived_data() {
+        let conn = library();
+        let new_id = copy_icon_to(&conn, "2", "d", "2025-01-01").unwrap();
+        assert_eq!(collection_of(&conn, &new_id), "d");
+        assert_eq!(tags_of(&conn, &new_id), tags_of(&conn, "2"));
+        assert_eq!(icon_count(&conn, "d"), 1);
+        // The copy is searchable under its own row
+        let arrows = query::parse("arrow").unwrap();
+        let thesaurus = load_thesaurus(&conn).unwrap();
+        assert_eq!(count_matches(&conn, &arrows, &thesaurus).unwrap(), 3);
+    }
+
+    #[test]
+    fn moving_an_icon_recounts_both_collections() {
+        let conn = library();
+        assert!(move_icon_to(&conn, "1", "d").unwrap());
+        assert_eq!((icon_count(&conn, "c"), icon_count(&conn, "d")), (2, 1));
+        assert!(!move_icon_to(&conn, "1", "d").unwrap());
+
+        let icon = get_icon(&conn, "1").unwrap();
+        assert_eq!(
+            (icon.collection_id.as_str(), icon.tags),
+            ("d", vec!["nav".to_string()])
+        );
+        assert!(move_icon_to(&conn, "1", "gone").is_err());
+        assert!(copy_icon_to(&conn, "1", "gone", "2025-01-01").is_err());
+        assert_eq!(icon_count(&conn, "d"), 1);
+    }
+
+    fn add_collection(conn: &Connection, id: &str, name: &str, parent: Option<&str>) {
+        conn.execute(
+            "INSERT INTO collections (id, name, parent_id, color, created_at)
+             VALUES (?1, ?2, ?3, '#888', '2024-01-01')",
+            rusqlite::params![id, name, parent],
+        )
+        .unwrap();
+    }
+
+    fn add_icon(conn: &Connection, id: &str, name: &str, svg: &str, collection_id: &str) {
+        conn.execute(
+            "INSERT INTO icons (id, name, path, svg_content, collection_id, created_at)
+             VALUES (?1, ?2, '', ?3, ?4, '2024-01-01')",
+            [id, name, svg, collection_id],
+        )
+        .unwrap();
+    }
+
+    fn icon_names(conn: &Connection, collection_id: &str) -> Vec<String> {
+        conn.prepare("SELECT name FROM icons WHERE collection_id = ?1 ORDER BY name")
+            .unwrap()
+            .query_map([collection_id], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn merging_collections_dedupes_renames_and_merges_subcollections() {
+        let conn = library();
+        add_collection(&conn, "c-sub", "Solid", Some("c"));
+        add_collection(&conn, "d-sub", "solid", Some("d"));
+        add_collection(&conn, "d-other", "Outline", Some("d"));
+        add_icon(&conn, "d1", "trash", "<svg><title>Sign</title></svg>", "d");
+        add_icon(&conn, "d2", "Arrow-Left", "<svg><path/></svg>", "d");
+        add_icon(&conn, "d3", "star", "<svg/>", "d-sub");
+        conn.execute("UPDATE icons SET favorite = 1 WHERE id = 'd1'", [])
+            .unwrap();
+        set_icon_tags(&conn, "d1", &["bin".to_string()]).unwrap();
+
+        let mut result = MergeResult::default();
+        merge_collection_into(&conn, "d", "c", &mut result, &mut Tracker::default()).unwrap();
+        assert_eq!(
+            (
+                result.icons_moved,
+                result.icons_renamed,
+                result.duplicates_merged
+            ),
+            (2, 1, 1)
+        );
+
+        // The duplicate trash icon folded into the existing one
+        assert_eq!(
+            icon_names(&conn, "c"),
+            ["Arrow-Left (2)", "arrow-left", "arrow-right", "trash"]
+        );
+        let trash = get_icon(&conn, "3").unwrap();
+        assert!(trash.favorite);
+        assert_eq!(tags_of(&conn, "3"), ["bin", "delete"]);
+
+        // Same-named subcollections merged, the others moved over
+        assert_eq!(icon_names(&conn, "c-sub"), ["star"]);
+        assert_eq!(
+            collection_subtree(&conn, "c").unwrap(),
+            HashSet::from(["c", "c-sub", "d-other"].map(String::from))
+        );
+        assert!(ensure_collection_exists(&conn, "d").is_err());
+        assert!(ensure_collection_exists(&conn, "d-sub").is_err());
+    }
+
+    #[test]
+    fn icon_counts_follow_inserts_moves_and_deletes() {
+        let conn = library();
+        // The stale count written by an older version was recomputed
+        assert_eq!(icon_count(&conn, "c"), 3);
+
+        add_collection(&conn, "e", "Empty", None);
+        add_icon(&conn, "e1", "new", "<svg/>", "e");
+        assert_eq!(icon_count(&conn, "e"), 1);
+        conn.execute("UPDATE icons SET collection_id = 'e' WHERE id = '1'", [])
+            .unwrap();
+        conn.execute("UPDATE icons SET name = 'renamed' WHERE id = '2'", [])
+            .unwrap();
+        assert_eq!((icon_count(&conn, "c"), icon_count(&conn, "e")), (2, 2));
+        conn.execute("DELETE FROM icons WHERE collection_id = 'e'", [])
+            .unwrap();
+        assert_eq!(icon_count(&conn, "e"), 0);
+
+        // Counts aren't recomputed again on later starts
+        conn.execute("UPDATE collections SET icon_count = 9 WHERE id = 'e'", [])
+            .unwrap();
+        init_icon_counts(&conn).unwrap();
+        assert_eq!(icon_count(&conn, "e"), 9);
+    }
+
+    #[test]
+    fn collections_total_their_subtree() {
+        let conn = library();
+        add_collection(&conn, "c-sub", "Solid", Some("c"));
+        add_collection(&conn, "c-sub-sub", "Small", Some("c-sub"));
+        add_icon(&conn, "s1", "star", "<svg/>", "c-sub");
+        add_icon(&conn, "s2", "dot", "<svg/>", "c-sub-sub");
+        conn.execute(
+            "INSERT INTO smart_collections (id, name, query, color, created_at)
+             VALUES ('q', 'Arrows', 'arrow', '#888', '2024-01-01')",
+            [],
+        )
+        .unwrap();
+
+        let counts: HashMap<String, (i32, i32)> = load_collections(&conn)
+            .unwrap()
+            .into_iter()
+            .map(|c| (c.id, (c.icon_count, c.total_icon_count)))
+            .collect();
+        assert_eq!(counts["c"], (3, 5));
+        assert_eq!(counts["c-sub"], (1, 2));
+        assert_eq!(counts["c-sub-sub"], (1, 1));
+        assert_eq!(counts["d"], (0, 0));
+        assert_eq!(counts["q"], (2, 2));
+    }
 }
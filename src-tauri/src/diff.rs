@@ -0,0 +1,162 @@
+// Line diffs of SVG markup, for comparing two revisions of an icon.
+//
+// Exported SVGs are often minified onto a single line, so the markup is
+// first normalized to one tag (or text run) per line with whitespace
+// collapsed; the diff then lines up element by element no matter how either
+// version was formatted. The lines are compared with a plain LCS after
+// trimming the common start and end, which is where almost all of a typical
+// edit's unchanged lines are.
+
+use crate::DiffLine;
+
+/// Largest LCS table built; beyond it the changed middle is shown as removed
+/// and re-added as a whole.
+const MAX_CELLS: usize = 4_000_000;
+
+/// Splits SVG markup into one line per tag or text run.
+pub fn svg_lines(svg: &str) -> Vec<String> {
+    let collapsed = svg.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut lines = Vec::new();
+    let mut rest = collapsed.as_str();
+    while !rest.is_empty() {
+        let end = if rest.starts_with('<') {
+            rest.find('>').map_or(rest.len(), |i| i + 1)
+        } else {
+            rest.find('<').unwrap_or(rest.len())
+        };
+        let line = rest[..end].trim();
+        if !line.is_empty() {
+            lines.push(line.to_string());
+        }
+        rest = &rest[end..];
+    }
+    lines
+}
+
+/// Diffs two SVGs line by line (see `svg_lines`).
+pub fn diff_svg(old: &str, new: &str) -> Vec<DiffLine> {
+    diff_lines(&svg_lines(old), &svg_lines(new))
+}
+
+pub fn diff_lines(old: &[String], new: &[String]) -> Vec<DiffLine> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+
+    let mut lines: Vec<DiffLine> = old[..prefix].iter().map(|l| line("same", l)).collect();
+    if a.len().saturating_mul(b.len()) > MAX_CELLS {
+        lines.extend(a.iter().map(|l| line("removed", l)));
+        lines.extend(b.iter().map(|l| line("added", l)));
+    } else {
+        // lcs[i][j]: length of the longest common subsequence of a[i..], b[j..]
+        let width = b.len() + 1;
+        let mut lcs = vec![0u32; (a.len() + 1) * width];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                lcs[i * width + j] = if a[i] == b[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() || j < b.len() {
+            if i < a.len() && j < b.len() && a[i] == b[j] {
+                lines.push(line("same", &a[i]));
+                i += 1;
+                j += 1;
+            } else if j == b.len()
+                || (i < a.len() && lcs[(i + 1) * width + j] >= lcs[i * width + j + 1])
+            {
+                lines.push(line("removed", &a[i]));
+                i += 1;
+            } else {
+                lines.push(line("added", &b[j]));
+                j += 1;
+            }
+        }
+    }
+    lines.extend(old[old.len() - suffix..].iter().map(|l| line("same", l)));
+    lines
+}
+
+fn line(kind: &str, text: &str) -> DiffLine {
+    DiffLine {
+        kind: kind.to_string(),
+        text: text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.split_whitespace().map(str::to_string).collect()
+    }
+
+    fn render(diff: &[DiffLine]) -> Vec<String> {
+        diff.iter()
+            .map(|l| {
+                let sign = match l.kind.as_str() {
+                    "added" => '+',
+                    "removed" => '-',
+                    _ => ' ',
+                };
+                format!("{}{}", sign, l.text)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn markup_splits_into_one_tag_or_text_run_per_line() {
+        let svg =
+            "<svg viewBox=\"0 0 24 24\">\n  <title>Home\n  page</title><path   d=\"M0 0\"/></svg>";
+        assert_eq!(
+            svg_lines(svg),
+            [
+                "<svg viewBox=\"0 0 24 24\">",
+                "<title>",
+                "Home page",
+                "</title>",
+                "<path d=\"M0 0\"/>",
+                "</svg>",
+            ]
+        );
+    }
+
+    #[test]
+    fn changes_in_the_middle_keep_the_common_ends() {
+        let diff = diff_lines(&lines("a b c d e"), &lines("a x c y e"));
+        assert_eq!(render(&diff), [" a", "-b", "+x", " c", "-d", "+y", " e"]);
+        assert_eq!(
+            render(&diff_lines(&lines("a b"), &lines("a b"))),
+            [" a", " b"]
+        );
+    }
+
+    #[test]
+    fn one_side_can_be_a_prefix_of_the_other() {
+        assert_eq!(
+            render(&diff_lines(&lines("a b"), &lines("a b c"))),
+            [" a", " b", "+c"]
+        );
+        assert_eq!(
+            render(&diff_lines(&lines("a b c"), &lines("a b"))),
+            [" a", " b", "-c"]
+        );
+        // Repeated lines must not be counted as both prefix and suffix
+        assert_eq!(
+            render(&diff_lines(&lines("a a"), &lines("a a a"))),
+            [" a", " a", "+a"]
+        );
+        assert_eq!(render(&diff_lines(&[], &lines("a"))), ["+a"]);
+    }
+}
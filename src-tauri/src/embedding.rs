@@ -0,0 +1,201 @@
+// Offline semantic search with local word embeddings.
+//
+// The model is a plain word-vector file in the fastText/GloVe text format
+// (`word v1 v2 ... vN` per line, with an optional `count dims` header), which
+// any CPU can load without native runtimes or network access. Aligned
+// multilingual vectors put "payment", "wallet" and "кошелек" close together,
+// so meaning carries across wording and language.
+//
+// An icon's text (name, tags, SVG title/description) is embedded as the
+// normalized average of its known word vectors; search ranks icons by cosine
+// similarity to the embedded query.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+use crate::fuzzy::tokenize_name;
+
+pub struct EmbeddingModel {
+    /// Identifies the model whose vectors are stored in the database, so
+    /// switching models triggers re-embedding.
+    pub id: String,
+    pub dimensions: usize,
+    words: HashMap<String, Vec<f32>>,
+}
+
+impl EmbeddingModel {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let reader = BufReader::new(File::open(path)?);
+
+        let mut words = HashMap::new();
+        let mut dimensions = 0;
+        for (line_no, line) in reader.lines().enumerate() {
+            let line = line?;
+            let mut parts = line.split_whitespace();
+            let Some(word) = parts.next() else {
+                continue;
+            };
+            let values: Vec<f32> = parts
+                .map(|v| v.parse::<f32>())
+                .collect::<Result<_, _>>()
+                .map_err(|_| invalid(format!("Bad number on line {}", line_no + 1)))?;
+
+            // fastText header: "<count> <dimensions>"
+            if line_no == 0 && values.len() == 1 {
+                continue;
+            }
+            if values.is_empty() {
+                continue;
+            }
+            if dimensions == 0 {
+                dimensions = values.len();
+            } else if values.len() != dimensions {
+                return Err(invalid(format!(
+                    "Line {} has {} values, expected {}",
+                    line_no + 1,
+                    values.len(),
+                    dimensions
+                )));
+            }
+            words
+                .entry(word.to_lowercase())
+                .or_insert_with(|| normalized(values));
+        }
+
+        if words.is_empty() {
+            return Err(invalid("No word vectors found".to_string()));
+        }
+
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        Ok(EmbeddingModel {
+            id: format!("{}:{}:{}", name, words.len(), dimensions),
+            dimensions,
+            words,
+        })
+    }
+
+    pub fn vocabulary_size(&self) -> usize {
+        self.words.len()
+    }
+
+    /// Embeds free text as the normalized mean of its known word vectors.
+    /// Returns `None` if no word is in the vocabulary.
+    pub fn embed(&self, text: &str) -> Option<Vec<f32>> {
+        let mut sum = vec![0.0f32; self.dimensions];
+        let mut found = 0;
+        for word in tokenize_name(text) {
+            if let Some(vector) = self.words.get(&word) {
+                for (s, v) in sum.iter_mut().zip(vector) {
+                    *s += v;
+                }
+                found += 1;
+            }
+        }
+        (found > 0).then(|| normalized(sum))
+    }
+}
+
+fn normalized(mut v: Vec<f32>) -> Vec<f32> {
+    let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        v.iter_mut().for_each(|x| *x /= norm);
+    }
+    v
+}
+
+/// Cosine similarity of two normalized vectors.
+pub fn similarity(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+pub fn to_blob(v: &[f32]) -> Vec<u8> {
+    v.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+pub fn from_blob(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Writes `contents` to a file under the system temp dir, unique to the test.
+    fn model_file(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "iconbox-embedding-{}-{}.vec",
+            name,
+            std::process::id()
+        ));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn skips_the_fasttext_header_line() {
+        let path = model_file("header", "3 2\nwallet 1 0\npurse 0 1\n\nWallet 5 5\n");
+        let model = EmbeddingModel::load(&path).unwrap();
+        assert_eq!(model.dimensions, 2);
+        // The first spelling of a word wins
+        assert_eq!(model.vocabulary_size(), 2);
+        assert_eq!(model.embed("Wallet"), Some(vec![1.0, 0.0]));
+        assert!(model.id.ends_with(":2:2"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn glove_files_have_no_header() {
+        let path = model_file("glove", "card 3 4\n");
+        let model = EmbeddingModel::load(&path).unwrap();
+        assert_eq!(model.embed("card"), Some(vec![0.6, 0.8]));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn rejects_ragged_bad_and_empty_files() {
+        let ragged = model_file("ragged", "a 1 2\nb 1 2 3\n");
+        let err = EmbeddingModel::load(&ragged).err().unwrap();
+        assert_eq!(err.to_string(), "Line 2 has 3 values, expected 2");
+
+        let bad = model_file("bad", "a 1 x\n");
+        let err = EmbeddingModel::load(&bad).err().unwrap();
+        assert_eq!(err.to_string(), "Bad number on line 1");
+
+        let empty = model_file("empty", "10 300\n");
+        assert!(EmbeddingModel::load(&empty).is_err());
+
+        for path in [ragged, bad, empty] {
+            std::fs::remove_file(path).unwrap();
+        }
+        assert!(EmbeddingModel::load(Path::new("/nonexistent/model.vec")).is_err());
+    }
+
+    #[test]
+    fn embeds_the_mean_of_known_words() {
+        let path = model_file("mean", "credit 1 0\ncard 0 1\n");
+        let model = EmbeddingModel::load(&path).unwrap();
+        let v = model.embed("CreditCard unknown").unwrap();
+        let h = std::f32::consts::FRAC_1_SQRT_2;
+        assert!((v[0] - h).abs() < 1e-6 && (v[1] - h).abs() < 1e-6);
+        assert!((similarity(&v, &v) - 1.0).abs() < 1e-6);
+        assert_eq!(model.embed("nothing known"), None);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn blobs_round_trip() {
+        let v = vec![0.5, -1.25, f32::MAX];
+        assert_eq!(from_blob(&to_blob(&v)), v);
+        // A trailing partial value is ignored
+        assert_eq!(from_blob(&[0, 0, 128, 63, 1]), [1.0]);
+    }
+}
@@ -0,0 +1,240 @@
+// Error type shared by the storage layer and the Tauri commands.
+//
+// Errors reach the frontend as `{ code, message, context }`. `code` is
+// stable and meant for the UI to branch on (and to pick a localized text);
+// `message` is a readable English fallback; `context` names the icon,
+// collection, tag or file involved when known.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::query::QueryError;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// The icon, collection, tag or other item does not exist.
+    NotFound,
+    /// A name, path or value the user supplied is not acceptable.
+    InvalidInput,
+    /// A search query that does not parse; `context.position` points at it.
+    InvalidQuery,
+    /// The change would clash with existing data, e.g. a tag cycle.
+    Conflict,
+    /// The user dismissed a dialog.
+    Cancelled,
+    /// No library is open (see the startup status).
+    NotOpen,
+    /// The library file is damaged.
+    Corrupt,
+    /// Another process holds a lock on the library.
+    Locked,
+    /// The library or a file cannot be written.
+    ReadOnly,
+    /// The library was written by a newer version of the app.
+    TooNew,
+    DiskFull,
+    /// Reading or writing a file failed.
+    Io,
+    /// Loading or using the embedding model failed.
+    Model,
+    /// Any other database failure.
+    Database,
+    Internal,
+}
+
+/// What an error is about. Only the fields that apply are set.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ErrorContext {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collection_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Zero-based character offset in a search query.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Error {
+    pub code: ErrorCode,
+    pub message: String,
+    /// Boxed to keep `Result<T, Error>` small on the happy path.
+    #[serde(default)]
+    pub context: Box<ErrorContext>,
+}
+
+impl Error {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Error {
+            code,
+            message: message.into(),
+            context: Box::default(),
+        }
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Error::new(ErrorCode::NotFound, message)
+    }
+
+    pub fn invalid(message: impl Into<String>) -> Self {
+        Error::new(ErrorCode::InvalidInput, message)
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Error::new(ErrorCode::Conflict, message)
+    }
+
+    pub fn cancelled(message: impl Into<String>) -> Self {
+        Error::new(ErrorCode::Cancelled, message)
+    }
+
+    /// An I/O failure on `path`, with the path in the message and context.
+    pub fn io(path: &std::path::Path, action: &str, error: std::io::Error) -> Self {
+        let code = match error.kind() {
+            std::io::ErrorKind::NotFound => ErrorCode::NotFound,
+            std::io::ErrorKind::PermissionDenied => ErrorCode::ReadOnly,
+            _ => ErrorCode::Io,
+        };
+        Error::new(code, format!("{} {}: {}", action, path.display(), error))
+            .with_path(path.to_string_lossy())
+    }
+
+    pub fn with_icon(mut self, id: impl Into<String>) -> Self {
+        self.context.icon_id = Some(id.into());
+        self
+    }
+
+    pub fn with_collection(mut self, id: impl Into<String>) -> Self {
+        self.context.collection_id = Some(id.into());
+        self
+    }
+
+    pub fn with_tag(mut self, id: i64) -> Self {
+        self.context.tag_id = Some(id);
+        self
+    }
+
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.context.path = Some(path.into());
+        self
+    }
+
+    /// Prefixes the message, keeping code and context.
+    pub fn while_doing(mut self, what: &str) -> Self {
+        self.message = format!("{}: {}", what, self.message);
+        self
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<rusqlite::Error> for Error {
+    fn from(error: rusqlite::Error) -> Self {
+        use rusqlite::ErrorCode as Sqlite;
+
+        let code = match &error {
+            rusqlite::Error::QueryReturnedNoRows => ErrorCode::NotFound,
+            e => match e.sqlite_error_code() {
+                Some(Sqlite::DatabaseCorrupt | Sqlite::NotADatabase) => ErrorCode::Corrupt,
+                Some(Sqlite::DatabaseBusy | Sqlite::DatabaseLocked) => ErrorCode::Locked,
+                Some(Sqlite::ReadOnly | Sqlite::PermissionDenied) => ErrorCode::ReadOnly,
+                Some(Sqlite::DiskFull) => ErrorCode::DiskFull,
+                Some(Sqlite::ConstraintViolation) => ErrorCode::Conflict,
+                Some(Sqlite::CannotOpen) => ErrorCode::NotOpen,
+                _ => ErrorCode::Database,
+            },
+        };
+        let message = match &error {
+            rusqlite::Error::QueryReturnedNoRows => "Not found".to_string(),
+            rusqlite::Error::SqliteFailure(_, Some(message)) => message.clone(),
+            e => e.to_string(),
+        };
+        Error::new(code, message)
+    }
+}
+
+impl From<QueryError> for Error {
+    fn from(error: QueryError) -> Self {
+        let mut e = Error::new(ErrorCode::InvalidQuery, error.to_string());
+        e.context.position = Some(error.position);
+        e
+    }
+}
+
+impl From<tauri::Error> for Error {
+    fn from(error: tauri::Error) -> Self {
+        Error::new(ErrorCode::Internal, error.to_string())
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Error::new(ErrorCode::Internal, error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sqlite(code: i32) -> Error {
+        rusqlite::Error::SqliteFailure(rusqlite::ffi::Error::new(code), None).into()
+    }
+
+    #[test]
+    fn sqlite_failures_map_to_stable_codes() {
+        use rusqlite::ffi;
+
+        assert_eq!(sqlite(ffi::SQLITE_NOTADB).code, ErrorCode::Corrupt);
+        assert_eq!(sqlite(ffi::SQLITE_BUSY).code, ErrorCode::Locked);
+        assert_eq!(sqlite(ffi::SQLITE_READONLY).code, ErrorCode::ReadOnly);
+        assert_eq!(sqlite(ffi::SQLITE_FULL).code, ErrorCode::DiskFull);
+        assert_eq!(sqlite(ffi::SQLITE_CONSTRAINT).code, ErrorCode::Conflict);
+        assert_eq!(sqlite(ffi::SQLITE_CANTOPEN).code, ErrorCode::NotOpen);
+        assert_eq!(sqlite(ffi::SQLITE_ERROR).code, ErrorCode::Database);
+        let missing = Error::from(rusqlite::Error::QueryReturnedNoRows);
+        assert_eq!(missing.code, ErrorCode::NotFound);
+    }
+
+    #[test]
+    fn errors_serialize_with_only_the_context_that_applies() {
+        let e = Error::not_found("Icon 'x' not found").with_icon("x");
+        assert_eq!(
+            serde_json::to_value(&e).unwrap(),
+            serde_json::json!({
+                "code": "not_found",
+                "message": "Icon 'x' not found",
+                "context": { "icon_id": "x" },
+            })
+        );
+    }
+
+    #[test]
+    fn query_and_io_errors_keep_where_they_happened() {
+        let e = Error::from(crate::query::parse("tag:").unwrap_err());
+        assert_eq!(e.code, ErrorCode::InvalidQuery);
+        assert!(e.context.position.is_some());
+
+        let path = std::path::Path::new("/missing/icon.svg");
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "gone");
+        let e = Error::io(path, "Cannot read", io).while_doing("Import failed");
+        assert_eq!(e.code, ErrorCode::NotFound);
+        assert_eq!(e.context.path.as_deref(), Some("/missing/icon.svg"));
+        assert!(e
+            .message
+            .starts_with("Import failed: Cannot read /missing/icon.svg"));
+    }
+}
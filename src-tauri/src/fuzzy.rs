@@ -0,0 +1,307 @@
+// Typo-tolerant fuzzy matching of icon names.
+//
+// Names are split into lowercase word tokens on separator and case
+// boundaries (`arrow-left`, `ArrowLeft` and `arrow_left_24` all become
+// `arrow left ...`). Distinct tokens are interned into a shared vocabulary,
+// so a query only has to be compared against each distinct word once rather
+// than against every icon, which keeps lookups instant on large libraries.
+
+use std::collections::HashMap;
+
+/// Splits an icon name into lowercase word tokens.
+pub fn tokenize_name(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                tokens.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+
+        if let Some(prev) = current.chars().last() {
+            let next = chars.get(i + 1).copied();
+            let boundary =
+                // arrowLeft
+                (prev.is_lowercase() && c.is_uppercase())
+                // arrow24, 24px
+                || (prev.is_alphabetic() != c.is_alphabetic())
+                // SVGIcon -> SVG, Icon
+                || (prev.is_uppercase()
+                    && c.is_uppercase()
+                    && next.is_some_and(|n| n.is_lowercase()));
+            if boundary {
+                tokens.push(std::mem::take(&mut current));
+            }
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    tokens.into_iter().map(|t| t.to_lowercase()).collect()
+}
+
+/// Optimal string alignment distance (Levenshtein plus adjacent
+/// transpositions), giving up early once it exceeds `max`.
+fn edit_distance(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+
+    let width = b.len() + 1;
+    let mut prev2 = vec![0; width];
+    let mut prev: Vec<usize> = (0..width).collect();
+    let mut row = vec![0; width];
+
+    for i in 1..=a.len() {
+        row[0] = i;
+        let mut row_min = row[0];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut d = (prev[j] + 1).min(row[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d = d.min(prev2[j - 2] + 1);
+            }
+            row[j] = d;
+            row_min = row_min.min(d);
+        }
+        if row_min > max {
+            return None;
+        }
+        std::mem::swap(&mut prev2, &mut prev);
+        std::mem::swap(&mut prev, &mut row);
+    }
+
+    let d = prev[b.len()];
+    (d <= max).then_some(d)
+}
+
+/// How many typos a query word of this length may contain.
+fn allowed_typos(len: usize) -> usize {
+    match len {
+        0..=2 => 0,
+        3..=5 => 1,
+        _ => 2,
+    }
+}
+
+/// Scores how well one query word matches one name word, from 0 (no match)
+/// to 1 (identical).
+fn score_token(query: &str, token: &str) -> f64 {
+    if query == token {
+        return 1.0;
+    }
+
+    let q: Vec<char> = query.chars().collect();
+    let t: Vec<char> = token.chars().collect();
+
+    // Still typing: "arr" -> "arrow"
+    if token.starts_with(query) {
+        return 0.8 + 0.15 * q.len() as f64 / t.len() as f64;
+    }
+    // Abbreviated name: "arrow" -> "arr" (as in `arr-l`)
+    if query.starts_with(token) {
+        return 0.45 + 0.3 * t.len() as f64 / q.len() as f64;
+    }
+
+    // Abbreviated name with a typo in it: "arow" -> "arr"
+    if t.len() < q.len() {
+        return match edit_distance(&q[..t.len()], &t, allowed_typos(t.len())) {
+            Some(d) => {
+                let abbreviation = 0.45 + 0.3 * t.len() as f64 / q.len() as f64;
+                0.8 * abbreviation * (1.0 - d as f64 / (t.len() as f64 + 1.0))
+            }
+            None => edit_distance(&q, &t, allowed_typos(q.len()))
+                .map(|d| 0.75 * (1.0 - d as f64 / (q.len() as f64 + 1.0)))
+                .unwrap_or(0.0),
+        };
+    }
+
+    let max = allowed_typos(q.len());
+    if max == 0 {
+        return 0.0;
+    }
+
+    // Typo in the whole word, or in the part typed so far
+    let whole = edit_distance(&q, &t, max);
+    let prefix = if t.len() > q.len() {
+        edit_distance(&q, &t[..q.len()], max)
+    } else {
+        None
+    };
+    let best = match (whole, prefix) {
+        (Some(w), Some(p)) => Some((w as f64).min(p as f64 + 0.5)),
+        (Some(w), None) => Some(w as f64),
+        (None, Some(p)) => Some(p as f64 + 0.5),
+        (None, None) => None,
+    };
+    match best {
+        Some(d) => 0.75 * (1.0 - d / (q.len() as f64 + 1.0)),
+        None => 0.0,
+    }
+}
+
+struct Entry {
+    id: String,
+    name_len: usize,
+    tokens: Vec<u32>,
+}
+
+/// In-memory index of icon names for fuzzy lookup.
+#[derive(Default)]
+pub struct FuzzyIndex {
+    vocabulary: Vec<String>,
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuzzyMatch {
+    pub id: String,
+    pub score: f64,
+}
+
+impl FuzzyIndex {
+    pub fn build<I>(icons: I) -> Self
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let mut ids: HashMap<String, u32> = HashMap::new();
+        let mut index = FuzzyIndex::default();
+
+        for (id, name) in icons {
+            let tokens = tokenize_name(&name)
+                .into_iter()
+                .map(|t| {
+                    *ids.entry(t.clone()).or_insert_with(|| {
+                        index.vocabulary.push(t);
+                        (index.vocabulary.len() - 1) as u32
+                    })
+                })
+                .collect();
+            index.entries.push(Entry {
+                id,
+                name_len: name.len(),
+                tokens,
+            });
+        }
+        index
+    }
+
+    /// Returns icons whose names match every word of `query`, best first.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<FuzzyMatch> {
+        let words = tokenize_name(query);
+        if words.is_empty() {
+            return vec![];
+        }
+
+        // Score every query word against each distinct vocabulary word once
+        let word_scores: Vec<Vec<f64>> = words
+            .iter()
+            .map(|w| self.vocabulary.iter().map(|t| score_token(w, t)).collect())
+            .collect();
+
+        let mut matches: Vec<(f64, usize, &Entry)> = self
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let mut total = 0.0;
+                for scores in &word_scores {
+                    let best = entry
+                        .tokens
+                        .iter()
+                        .map(|&t| scores[t as usize])
+                        .fold(0.0, f64::max);
+                    if best == 0.0 {
+                        return None;
+                    }
+                    total += best;
+                }
+                // Prefer names without many extra words beyond the query
+                let coverage =
+                    words.len().min(entry.tokens.len()) as f64 / entry.tokens.len().max(1) as f64;
+                let score = total / words.len() as f64 * (0.85 + 0.15 * coverage);
+                Some((score, entry.name_len, entry))
+            })
+            .collect();
+
+        matches.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+        matches
+            .into_iter()
+            .take(limit)
+            .map(|(score, _, entry)| FuzzyMatch {
+                id: entry.id.clone(),
+                score,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distance(a: &str, b: &str, max: usize) -> Option<usize> {
+        let a: Vec<char> = a.chars().collect();
+        let b: Vec<char> = b.chars().collect();
+        edit_distance(&a, &b, max)
+    }
+
+    #[test]
+    fn tokenizes_on_separators_case_and_digits() {
+        assert_eq!(tokenize_name("arrow-left"), ["arrow", "left"]);
+        assert_eq!(tokenize_name("ArrowLeft"), ["arrow", "left"]);
+        assert_eq!(tokenize_name("arrow_left_24"), ["arrow", "left", "24"]);
+        assert_eq!(tokenize_name("SVGIcon"), ["svg", "icon"]);
+        assert_eq!(tokenize_name("icon24px"), ["icon", "24", "px"]);
+        assert_eq!(tokenize_name("СтрелкаВлево"), ["стрелка", "влево"]);
+        assert!(tokenize_name("--").is_empty());
+    }
+
+    #[test]
+    fn edit_distance_counts_transpositions_once() {
+        assert_eq!(distance("arrow", "arrow", 2), Some(0));
+        assert_eq!(distance("arow", "arrow", 2), Some(1));
+        assert_eq!(distance("arrwo", "arrow", 2), Some(1));
+        assert_eq!(distance("", "abc", 3), Some(3));
+    }
+
+    #[test]
+    fn edit_distance_gives_up_past_max() {
+        assert_eq!(distance("home", "house", 1), None);
+        assert_eq!(distance("home", "house", 2), Some(2));
+        // Length difference alone rules it out
+        assert_eq!(distance("ab", "abcde", 2), None);
+    }
+
+    #[test]
+    fn short_words_must_match_exactly() {
+        assert_eq!(score_token("ab", "ac"), 0.0);
+        assert!(score_token("abc", "abd") > 0.0);
+    }
+
+    #[test]
+    fn search_ranks_exact_then_prefix_then_typo() {
+        let index = FuzzyIndex::build([
+            ("1".to_string(), "arrow-left".to_string()),
+            ("2".to_string(), "arrowhead".to_string()),
+            ("3".to_string(), "arow".to_string()),
+            ("4".to_string(), "home".to_string()),
+        ]);
+        let ids: Vec<String> = index
+            .search("arrow", 10)
+            .into_iter()
+            .map(|m| m.id)
+            .collect();
+        assert_eq!(ids, ["1", "2", "3"]);
+        // Every query word has to match something
+        assert!(index.search("arrow house", 10).is_empty());
+        assert_eq!(index.search("lefy arow", 10)[0].id, "1");
+        assert_eq!(index.search("arrow", 1).len(), 1);
+        assert!(index.search(" - ", 10).is_empty());
+    }
+}
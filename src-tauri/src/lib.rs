@@ -1,8 +1,25 @@
 use serde::{Deserialize, Serialize};
-use std::fs;
-use uuid::Uuid;
 
+use crate::error::Error;
+
+pub use crate::db::Library;
+
+mod archive;
+mod autotag;
+mod backup;
 mod db;
+mod diff;
+mod embedding;
+pub mod error;
+mod fuzzy;
+mod migrations;
+mod palette;
+mod query;
+mod search;
+mod startup;
+mod svg;
+mod synonyms;
+mod visual;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Icon {
@@ -17,6 +34,34 @@ pub struct Icon {
     pub favorite: bool,
 }
 
+/// An icon without its SVG body, for listing large collections.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IconSummary {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    pub tags: Vec<String>,
+    pub collection_id: String,
+    pub created_at: String,
+    pub file_size: i64,
+    pub favorite: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IconPage {
+    pub icons: Vec<IconSummary>,
+    /// Icons in the whole listing, not just this page.
+    pub total: i64,
+    /// Pass to the next call to continue; `None` on the last page.
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IconSvg {
+    pub id: String,
+    pub svg_content: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Collection {
     pub id: String,
@@ -25,6 +70,81 @@ pub struct Collection {
     pub icon_count: i32,
     pub color: String,
     pub created_at: String,
+    /// Saved search of a smart collection, whose icons are whatever currently
+    /// matches it. `None` for regular collections.
+    #[serde(default)]
+    pub query: Option<String>,
+    /// Manual position among siblings; unordered ones follow, by name.
+    #[serde(default)]
+    pub sort_order: Option<i64>,
+    /// Icons in this collection and all its subcollections. `icon_count`
+    /// counts only the icons directly in it.
+    #[serde(default)]
+    pub total_icon_count: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SearchResults {
+    pub icons: Vec<Icon>,
+    pub total: i64,
+    /// Results come from typo-tolerant name matching rather than exact terms.
+    pub fuzzy: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SimilarIcon {
+    pub icon: Icon,
+    /// 0 for identical artwork, 1 for completely different.
+    pub distance: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SemanticMatch {
+    pub icon: Icon,
+    /// Cosine similarity between the query and the icon's text.
+    pub score: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EmbeddingStatus {
+    pub model_path: Option<String>,
+    pub loaded: bool,
+    pub dimensions: usize,
+    pub vocabulary: usize,
+    /// Icons that currently have a vector for the loaded model.
+    pub indexed: i64,
+    /// Why the model at `model_path` could not be loaded, if it failed.
+    pub error: Option<Error>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Tag {
+    pub id: i64,
+    /// Full path, e.g. `category/navigation`.
+    pub name: String,
+    pub parent_id: Option<i64>,
+    pub color: Option<String>,
+    /// Icons with exactly this tag.
+    pub icon_count: i64,
+    /// Icons with this tag or any of its subtags.
+    pub total_count: i64,
+}
+
+/// A tag with its subtags, for the tag tree.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TagNode {
+    #[serde(flatten)]
+    pub tag: Tag,
+    /// Last segment of the tag's path.
+    pub label: String,
+    pub children: Vec<TagNode>,
+}
+
+/// A set of words treated as equivalent when searching.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SynonymGroup {
+    pub id: i64,
+    pub terms: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -32,13 +152,220 @@ pub struct Settings {
     pub theme: String,
     pub icon_size: i32,
     pub tint_color: Option<String>,
+    /// Words never used as automatic tags, separated by commas.
+    pub autotag_stop_words: String,
+    /// Days deleted items stay in the trash; 0 keeps them until emptied.
+    pub trash_retention_days: i64,
+    /// Where backups are written; `None` for `backups/` next to the library.
+    pub backup_folder: Option<String>,
+    /// Hours between scheduled backups; 0 turns them off.
+    pub backup_interval_hours: i64,
+    /// How many backups are kept before the oldest are deleted.
+    pub backup_keep: i64,
+}
+
+/// A deleted icon, or a deleted collection with everything that was in it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrashItem {
+    pub id: String,
+    /// `"icon"` or `"collection"`.
+    pub kind: String,
+    pub name: String,
+    /// Name of the collection it was deleted from, if that still exists.
+    pub location: Option<String>,
+    pub deleted_at: String,
+    /// When it will be purged; `None` if the trash is kept until emptied.
+    pub expires_at: Option<String>,
+    pub icon_count: i64,
+    /// Collections in the entry, the deleted one included.
+    pub collection_count: i64,
+    /// The artwork of a deleted icon, for the preview.
+    pub svg_content: Option<String>,
+}
+
+/// What undo and redo would do next, as labels like "Move icons".
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct HistoryState {
+    pub undo: Option<String>,
+    pub redo: Option<String>,
+}
+
+/// One stored version of an icon's artwork.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IconRevision {
+    pub id: i64,
+    pub icon_id: String,
+    pub svg_content: String,
+    /// `"import"`, `"optimize"`, `"edit"`, `"sync"` or `"restore"`.
+    pub reason: String,
+    pub created_at: String,
+    pub file_size: i64,
+    /// Whether this is the artwork the icon currently shows.
+    pub current: bool,
+}
+
+/// One line of normalized SVG markup in a revision diff.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DiffLine {
+    /// `"same"`, `"added"` or `"removed"`.
+    pub kind: String,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RevisionDiff {
+    pub from: IconRevision,
+    pub to: IconRevision,
+    pub lines: Vec<DiffLine>,
+    /// 0 for identical artwork, 1 for completely different; `None` if either
+    /// version cannot be rendered.
+    pub visual_distance: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AutotagResult {
+    pub icons_updated: usize,
+    pub tags_added: usize,
+}
+
+/// Outcome of importing a folder as a new collection.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImportResult {
+    pub collection: Collection,
+    /// SVG files that were left out, with the reason.
+    pub failed: Vec<ImportFailure>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImportFailure {
+    pub path: String,
+    pub error: Error,
+}
+
+/// Summary of merging one collection into another.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MergeResult {
+    pub icons_moved: usize,
+    /// Moved icons renamed because the target had one with the same name.
+    pub icons_renamed: usize,
+    /// Icons dropped as exact duplicates (same name and SVG) of a target icon.
+    pub duplicates_merged: usize,
+    /// The source plus any same-named subcollections merged along with it.
+    pub collections_merged: usize,
+}
+
+/// Summary of a bulk icon command.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BulkResult {
+    /// Distinct existing icons the command ran on.
+    pub processed: usize,
+    /// Icons that actually changed, e.g. weren't already favorites.
+    pub changed: usize,
+    /// Requested ids that matched no icon.
+    pub missing: Vec<String>,
+    /// Ids of the icons created by a copy, in request order.
+    pub created_ids: Vec<String>,
+}
+
+/// Outcome of opening the library at startup (or of the last recovery
+/// action), shown by the UI before anything else.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct StartupStatus {
+    /// `ready`, `read_only` or `failed`.
+    pub state: String,
+    /// Why opening failed.
+    pub error: Option<Error>,
+    pub db_path: String,
+    /// Schema version stored in the library file, if it could be read.
+    pub schema_version: Option<u32>,
+    /// Newest schema version this build understands.
+    pub supported_version: u32,
+    /// Copy taken before the schema was upgraded on this start.
+    pub migration_backup: Option<String>,
+    /// Where the previous library file was moved by a restore or fresh start.
+    pub set_aside: Option<String>,
+    pub backups: Vec<DbBackup>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DbBackup {
+    pub path: String,
+    pub name: String,
+    pub size: u64,
+    pub modified: String,
+}
+
+/// The backup folder and what is in it, for the settings screen.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupStatus {
+    pub folder: Option<String>,
+    /// Newest first.
+    pub backups: Vec<DbBackup>,
+    /// Why the last scheduled backup failed, if it did.
+    pub last_error: Option<Error>,
+}
+
+/// Summary of exporting an archive.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ArchiveExportResult {
+    pub path: String,
+    pub collections: usize,
+    pub icons: usize,
+}
+
+/// Summary of importing an archive, or of what importing it would do.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ArchiveImportResult {
+    pub collections_created: usize,
+    /// Archive collections merged into a same-named one already there.
+    pub collections_merged: usize,
+    pub icons_added: usize,
+    /// Icons whose artwork was replaced by a same-named one from the archive.
+    pub icons_replaced: usize,
+    /// Icons added under a new name because theirs was taken.
+    pub icons_renamed: usize,
+    /// Icons left out because their name was taken.
+    pub icons_skipped: usize,
+    /// Icons already in the library with the same name and SVG.
+    pub duplicates_merged: usize,
+    pub smart_collections_created: usize,
+}
+
+// ── Startup ──────────────────────────────────────────────────
+
+#[tauri::command]
+async fn get_startup_status(app: tauri::AppHandle) -> Result<StartupStatus, Error> {
+    startup::get_startup_status(&app)
+}
+
+#[tauri::command]
+async fn retry_open_library(app: tauri::AppHandle) -> Result<StartupStatus, Error> {
+    startup::retry_open_library(&app)
+}
+
+#[tauri::command]
+async fn open_library_read_only(app: tauri::AppHandle) -> Result<StartupStatus, Error> {
+    startup::open_library_read_only(&app)
+}
+
+#[tauri::command]
+async fn restore_library_backup(
+    app: tauri::AppHandle,
+    backup_path: String,
+) -> Result<StartupStatus, Error> {
+    startup::restore_library_backup(&app, &backup_path)
+}
+
+#[tauri::command]
+async fn start_fresh_library(app: tauri::AppHandle) -> Result<StartupStatus, Error> {
+    startup::start_fresh_library(&app)
 }
 
 // ── Collection commands ──────────────────────────────────────
 
 #[tauri::command]
-async fn get_collections(app: tauri::AppHandle) -> Result<Vec<Collection>, String> {
-    db::get_all_collections(&app).map_err(|e| e.to_string())
+async fn get_collections(app: tauri::AppHandle) -> Result<Vec<Collection>, Error> {
+    startup::library(&app)?.get_all_collections()
 }
 
 #[tauri::command]
@@ -46,17 +373,8 @@ async fn create_collection(
     app: tauri::AppHandle,
     name: String,
     parent_id: Option<String>,
-) -> Result<Collection, String> {
-    let collection = Collection {
-        id: Uuid::new_v4().to_string(),
-        name,
-        parent_id,
-        icon_count: 0,
-        color: random_color(),
-        created_at: chrono::Utc::now().to_rfc3339(),
-    };
-    db::insert_collection(&app, &collection).map_err(|e| e.to_string())?;
-    Ok(collection)
+) -> Result<Collection, Error> {
+    startup::library(&app)?.create_collection(&name, parent_id.as_deref())
 }
 
 #[tauri::command]
@@ -64,35 +382,98 @@ async fn rename_collection(
     app: tauri::AppHandle,
     id: String,
     new_name: String,
-) -> Result<(), String> {
-    db::rename_collection(&app, &id, &new_name).map_err(|e| e.to_string())
+) -> Result<(), Error> {
+    startup::library(&app)?.rename_collection(&id, &new_name)
 }
 
 #[tauri::command]
-async fn delete_collection(app: tauri::AppHandle, id: String) -> Result<(), String> {
-    db::delete_collection(&app, &id).map_err(|e| e.to_string())
+async fn delete_collection(app: tauri::AppHandle, id: String) -> Result<(), Error> {
+    startup::library(&app)?.delete_collection(&id)
+}
+
+#[tauri::command]
+async fn move_collection(
+    app: tauri::AppHandle,
+    id: String,
+    parent_id: Option<String>,
+) -> Result<(), Error> {
+    startup::library(&app)?.move_collection(&id, parent_id.as_deref())
+}
+
+#[tauri::command]
+async fn reorder_collections(
+    app: tauri::AppHandle,
+    parent_id: Option<String>,
+    ordered_ids: Vec<String>,
+) -> Result<(), Error> {
+    startup::library(&app)?.reorder_collections(parent_id.as_deref(), &ordered_ids)
+}
+
+#[tauri::command]
+async fn merge_collections(
+    app: tauri::AppHandle,
+    source_id: String,
+    target_id: String,
+) -> Result<MergeResult, Error> {
+    startup::library(&app)?.merge_collections(&source_id, &target_id)
+}
+
+#[tauri::command]
+async fn save_smart_collection(
+    app: tauri::AppHandle,
+    id: Option<String>,
+    name: String,
+    query: String,
+) -> Result<Collection, Error> {
+    startup::library(&app)?.save_smart_collection(id.as_deref(), &name, &query)
 }
 
 // ── Icon commands ────────────────────────────────────────────
 
 #[tauri::command]
-async fn get_icons(app: tauri::AppHandle, collection_id: String) -> Result<Vec<Icon>, String> {
-    db::get_icons_by_collection(&app, &collection_id).map_err(|e| e.to_string())
+async fn get_icons(app: tauri::AppHandle, collection_id: String) -> Result<Vec<Icon>, Error> {
+    startup::library(&app)?.get_icons_by_collection(&collection_id)
+}
+
+#[tauri::command]
+async fn get_all_icons(app: tauri::AppHandle) -> Result<Vec<Icon>, Error> {
+    startup::library(&app)?.get_all_icons()
+}
+
+#[tauri::command]
+async fn get_favorite_icons(app: tauri::AppHandle) -> Result<Vec<Icon>, Error> {
+    startup::library(&app)?.get_favorite_icons()
 }
 
+/// Lists icons a page at a time without their SVG bodies, so the grid can
+/// virtualize; fetch the artwork of the visible ones with `get_icon_svgs`.
 #[tauri::command]
-async fn get_all_icons(app: tauri::AppHandle) -> Result<Vec<Icon>, String> {
-    db::get_all_icons(&app).map_err(|e| e.to_string())
+async fn list_icons(
+    app: tauri::AppHandle,
+    collection_id: Option<String>,
+    favorites_only: Option<bool>,
+    cursor: Option<String>,
+    limit: Option<i64>,
+) -> Result<IconPage, Error> {
+    startup::library(&app)?.list_icons(
+        collection_id.as_deref(),
+        favorites_only.unwrap_or(false),
+        cursor.as_deref(),
+        limit.unwrap_or(200),
+    )
 }
 
 #[tauri::command]
-async fn get_favorite_icons(app: tauri::AppHandle) -> Result<Vec<Icon>, String> {
-    db::get_favorite_icons(&app).map_err(|e| e.to_string())
+async fn get_icon_svgs(
+    app: tauri::AppHandle,
+    icon_ids: Vec<String>,
+) -> Result<Vec<IconSvg>, Error> {
+    startup::library(&app)?.get_icon_svgs(&icon_ids)
 }
 
 #[tauri::command]
-async fn toggle_favorite(app: tauri::AppHandle, icon_id: String) -> Result<bool, String> {
-    db::toggle_favorite(&app, &icon_id).map_err(|e| e.to_string())
+async fn toggle_favorite(app: tauri::AppHandle, icon_id: String) -> Result<bool, Error> {
+    startup::library(&app)?.toggle_favorite(&icon_id)
 }
 
 #[tauri::command]
@@ -100,19 +481,275 @@ async fn update_icon_tags(
     app: tauri::AppHandle,
     icon_id: String,
     tags: Vec<String>,
-) -> Result<(), String> {
-    db::update_icon_tags(&app, &icon_id, &tags).map_err(|e| e.to_string())
+) -> Result<(), Error> {
+    startup::library(&app)?.update_icon_tags(&icon_id, &tags)
+}
+
+#[tauri::command]
+async fn delete_icon(app: tauri::AppHandle, icon_id: String) -> Result<(), Error> {
+    startup::library(&app)?.delete_icon(&icon_id)
+}
+
+#[tauri::command]
+async fn move_icon(
+    app: tauri::AppHandle,
+    icon_id: String,
+    collection_id: String,
+) -> Result<Icon, Error> {
+    startup::library(&app)?.move_icon(&icon_id, &collection_id)
+}
+
+#[tauri::command]
+async fn copy_icon(
+    app: tauri::AppHandle,
+    icon_id: String,
+    collection_id: String,
+) -> Result<Icon, Error> {
+    startup::library(&app)?.copy_icon(&icon_id, &collection_id)
+}
+
+// Bulk versions of the icon commands for multi-select. Each runs in one
+// transaction and returns a summary.
+
+#[tauri::command]
+async fn bulk_add_tags(
+    app: tauri::AppHandle,
+    icon_ids: Vec<String>,
+    tags: Vec<String>,
+) -> Result<BulkResult, Error> {
+    startup::library(&app)?.bulk_add_tags(&icon_ids, &tags)
+}
+
+#[tauri::command]
+async fn bulk_remove_tags(
+    app: tauri::AppHandle,
+    icon_ids: Vec<String>,
+    tags: Vec<String>,
+) -> Result<BulkResult, Error> {
+    startup::library(&app)?.bulk_remove_tags(&icon_ids, &tags)
+}
+
+#[tauri::command]
+async fn bulk_set_favorite(
+    app: tauri::AppHandle,
+    icon_ids: Vec<String>,
+    favorite: bool,
+) -> Result<BulkResult, Error> {
+    startup::library(&app)?.bulk_set_favorite(&icon_ids, favorite)
+}
+
+#[tauri::command]
+async fn bulk_move_icons(
+    app: tauri::AppHandle,
+    icon_ids: Vec<String>,
+    collection_id: String,
+) -> Result<BulkResult, Error> {
+    startup::library(&app)?.bulk_move_icons(&icon_ids, &collection_id)
+}
+
+#[tauri::command]
+async fn bulk_copy_icons(
+    app: tauri::AppHandle,
+    icon_ids: Vec<String>,
+    collection_id: String,
+) -> Result<BulkResult, Error> {
+    startup::library(&app)?.bulk_copy_icons(&icon_ids, &collection_id)
+}
+
+#[tauri::command]
+async fn bulk_delete_icons(
+    app: tauri::AppHandle,
+    icon_ids: Vec<String>,
+) -> Result<BulkResult, Error> {
+    startup::library(&app)?.bulk_delete_icons(&icon_ids)
+}
+
+// ── Tag commands ─────────────────────────────────────────────
+
+#[tauri::command]
+async fn get_tags(app: tauri::AppHandle) -> Result<Vec<Tag>, Error> {
+    startup::library(&app)?.get_tags()
+}
+
+#[tauri::command]
+async fn get_tag_tree(app: tauri::AppHandle) -> Result<Vec<TagNode>, Error> {
+    startup::library(&app)?.get_tag_tree()
+}
+
+#[tauri::command]
+async fn rename_tag(app: tauri::AppHandle, id: i64, new_name: String) -> Result<(), Error> {
+    startup::library(&app)?.rename_tag(id, &new_name)
+}
+
+#[tauri::command]
+async fn set_tag_parent(
+    app: tauri::AppHandle,
+    id: i64,
+    parent_id: Option<i64>,
+) -> Result<(), Error> {
+    startup::library(&app)?.set_tag_parent(id, parent_id)
+}
+
+#[tauri::command]
+async fn merge_tags(
+    app: tauri::AppHandle,
+    source_ids: Vec<i64>,
+    target_id: i64,
+) -> Result<(), Error> {
+    startup::library(&app)?.merge_tags(&source_ids, target_id)
+}
+
+#[tauri::command]
+async fn delete_tag(app: tauri::AppHandle, id: i64) -> Result<(), Error> {
+    startup::library(&app)?.delete_tag(id)
+}
+
+#[tauri::command]
+async fn set_tag_color(app: tauri::AppHandle, id: i64, color: Option<String>) -> Result<(), Error> {
+    startup::library(&app)?.set_tag_color(id, color.as_deref())
+}
+
+/// Re-runs automatic tagging over the icons of the given collections (and
+/// their subcollections), or over the whole library. Derived tags are added
+/// to the existing ones; nothing is removed.
+#[tauri::command]
+async fn run_autotag(
+    app: tauri::AppHandle,
+    collection_ids: Option<Vec<String>>,
+) -> Result<AutotagResult, Error> {
+    startup::library(&app)?.autotag_icons(collection_ids.as_deref())
+}
+
+// ── Search ───────────────────────────────────────────────────
+
+/// Runs a search-bar query: free text plus `field:value` filters
+/// (see `query.rs` for the syntax).
+#[tauri::command]
+async fn search_icons(
+    app: tauri::AppHandle,
+    query: String,
+    collection_id: Option<String>,
+    favorites_only: Option<bool>,
+    exact: Option<bool>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<SearchResults, Error> {
+    startup::library(&app)?.search_icons(
+        &query,
+        collection_id.as_deref(),
+        favorites_only.unwrap_or(false),
+        exact.unwrap_or(false),
+        limit.unwrap_or(200),
+        offset.unwrap_or(0),
+    )
 }
 
 #[tauri::command]
-async fn delete_icon(app: tauri::AppHandle, icon_id: String) -> Result<(), String> {
-    db::delete_icon(&app, &icon_id).map_err(|e| e.to_string())
+async fn suggest_search(
+    app: tauri::AppHandle,
+    query: String,
+) -> Result<Vec<query::Suggestion>, Error> {
+    startup::library(&app)?.suggest_search(&query)
+}
+
+#[tauri::command]
+async fn find_similar(
+    app: tauri::AppHandle,
+    icon_id: String,
+    limit: Option<usize>,
+) -> Result<Vec<SimilarIcon>, Error> {
+    startup::library(&app)?.find_similar(&icon_id, limit.unwrap_or(50))
+}
+
+#[tauri::command]
+async fn get_icon_palette(app: tauri::AppHandle, icon_id: String) -> Result<Vec<String>, Error> {
+    startup::library(&app)?.get_icon_palette(&icon_id)
+}
+
+/// Searches by meaning using the local embedding model, so "payment" finds
+/// `credit-card` or `wallet` without sharing any keywords.
+#[tauri::command]
+async fn semantic_search(
+    app: tauri::AppHandle,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<SemanticMatch>, Error> {
+    startup::library(&app)?.semantic_search(&query, limit.unwrap_or(100))
+}
+
+#[tauri::command]
+async fn get_embedding_status(app: tauri::AppHandle) -> Result<EmbeddingStatus, Error> {
+    startup::library(&app)?.get_embedding_status()
+}
+
+/// Loads a word-vector file (fastText/GloVe text format) as the embedding
+/// model and makes it the default for future sessions.
+#[tauri::command]
+async fn load_embedding_model(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<EmbeddingStatus, Error> {
+    let library = startup::library(&app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        library.load_embedding_model(std::path::Path::new(&path))
+    })
+    .await
+    .map_err(|e| Error::from(e).while_doing("Model loading thread error"))?
+}
+
+/// Asks for a word-vector file and loads it as the embedding model.
+#[tauri::command]
+async fn choose_embedding_model(app: tauri::AppHandle) -> Result<EmbeddingStatus, Error> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let library = startup::library(&app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let file = app
+            .dialog()
+            .file()
+            .add_filter("Word vectors", &["vec", "txt"])
+            .blocking_pick_file()
+            .ok_or_else(|| Error::cancelled("No model file selected"))?;
+        let path = file
+            .as_path()
+            .ok_or_else(|| Error::invalid("Invalid path"))?;
+        library.load_embedding_model(path)
+    })
+    .await
+    .map_err(|e| Error::from(e).while_doing("Model loading thread error"))?
+}
+
+#[tauri::command]
+async fn get_synonym_groups(app: tauri::AppHandle) -> Result<Vec<SynonymGroup>, Error> {
+    startup::library(&app)?.get_synonym_groups()
+}
+
+#[tauri::command]
+async fn save_synonym_group(
+    app: tauri::AppHandle,
+    id: Option<i64>,
+    terms: Vec<String>,
+) -> Result<Option<SynonymGroup>, Error> {
+    startup::library(&app)?.save_synonym_group(id, &terms)
+}
+
+#[tauri::command]
+async fn delete_synonym_group(app: tauri::AppHandle, id: i64) -> Result<(), Error> {
+    startup::library(&app)?.delete_synonym_group(id)
+}
+
+#[tauri::command]
+async fn reset_synonyms(app: tauri::AppHandle) -> Result<(), Error> {
+    startup::library(&app)?.reset_synonyms()
 }
 
 // ── Import ───────────────────────────────────────────────────
 
 #[tauri::command]
-async fn import_folder(app: tauri::AppHandle, parent_id: Option<String>) -> Result<Collection, String> {
+async fn import_folder(
+    app: tauri::AppHandle,
+    parent_id: Option<String>,
+) -> Result<ImportResult, Error> {
     use tauri_plugin_dialog::DialogExt;
 
     // Use spawn_blocking to avoid deadlocking the async runtime
@@ -121,97 +758,241 @@ async fn import_folder(app: tauri::AppHandle, parent_id: Option<String>) -> Resu
         app_clone.dialog().file().blocking_pick_folder()
     })
     .await
-    .map_err(|e| format!("Dialog thread error: {}", e))?;
+    .map_err(|e| Error::from(e).while_doing("Dialog thread error"))?;
 
-    let folder_path = folder.ok_or("No folder selected")?;
-    let folder_path = folder_path.as_path().ok_or("Invalid path")?;
+    let folder_path = folder.ok_or_else(|| Error::cancelled("No folder selected"))?;
+    let folder_path = folder_path
+        .as_path()
+        .ok_or_else(|| Error::invalid("Invalid path"))?;
 
-    let folder_name = folder_path
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("Imported")
-        .to_string();
+    startup::library(&app)?.import_folder(folder_path, parent_id.as_deref())
+}
 
-    let collection = Collection {
-        id: Uuid::new_v4().to_string(),
-        name: folder_name,
-        parent_id,
-        icon_count: 0,
-        color: random_color(),
-        created_at: chrono::Utc::now().to_rfc3339(),
-    };
-    db::insert_collection(&app, &collection).map_err(|e| e.to_string())?;
-
-    // Scan for SVG files
-    let mut count = 0;
-    if let Ok(entries) = fs::read_dir(folder_path) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.extension().map_or(false, |e| e == "svg") {
-                if let Ok(content) = fs::read_to_string(&path) {
-                    let file_size = fs::metadata(&path).map(|m| m.len() as i64).unwrap_or(0);
-                    let icon = Icon {
-                        id: Uuid::new_v4().to_string(),
-                        name: path
-                            .file_stem()
-                            .and_then(|n| n.to_str())
-                            .unwrap_or("icon")
-                            .to_string(),
-                        path: path.to_string_lossy().to_string(),
-                        svg_content: content,
-                        tags: vec![],
-                        collection_id: collection.id.clone(),
-                        created_at: chrono::Utc::now().to_rfc3339(),
-                        file_size,
-                        favorite: false,
-                    };
-                    if db::insert_icon(&app, &icon).is_ok() {
-                        count += 1;
-                    }
-                }
-            }
-        }
-    }
-
-    db::update_collection_count(&app, &collection.id, count).map_err(|e| e.to_string())?;
-
-    let mut updated = collection;
-    updated.icon_count = count;
-    Ok(updated)
+// ── Trash ────────────────────────────────────────────────────
+
+#[tauri::command]
+async fn get_trash(app: tauri::AppHandle) -> Result<Vec<TrashItem>, Error> {
+    startup::library(&app)?.get_trash()
 }
 
-// ── Settings ─────────────────────────────────────────────────
+#[tauri::command]
+async fn restore_from_trash(
+    app: tauri::AppHandle,
+    trash_ids: Vec<String>,
+) -> Result<BulkResult, Error> {
+    startup::library(&app)?.restore_from_trash(&trash_ids)
+}
 
 #[tauri::command]
-async fn get_settings(app: tauri::AppHandle) -> Result<Settings, String> {
-    db::get_settings(&app).map_err(|e| e.to_string())
+async fn delete_from_trash(
+    app: tauri::AppHandle,
+    trash_ids: Vec<String>,
+) -> Result<BulkResult, Error> {
+    startup::library(&app)?.delete_from_trash(&trash_ids)
 }
 
 #[tauri::command]
-async fn save_setting(app: tauri::AppHandle, key: String, value: String) -> Result<(), String> {
-    db::save_setting(&app, &key, &value).map_err(|e| e.to_string())
+async fn empty_trash(app: tauri::AppHandle) -> Result<usize, Error> {
+    startup::library(&app)?.empty_trash()
 }
 
-// ── Stats ────────────────────────────────────────────────────
+// ── History ──────────────────────────────────────────────────
+
+/// Undoes the last edit; returns its label, or `None` if there was none.
+#[tauri::command]
+async fn undo(app: tauri::AppHandle) -> Result<Option<String>, Error> {
+    startup::library(&app)?.undo()
+}
+
+/// Redoes the last undone edit; returns its label, or `None` if there was none.
+#[tauri::command]
+async fn redo(app: tauri::AppHandle) -> Result<Option<String>, Error> {
+    startup::library(&app)?.redo()
+}
+
+#[tauri::command]
+async fn get_history(app: tauri::AppHandle) -> Result<HistoryState, Error> {
+    startup::library(&app)?.get_history()
+}
+
+// ── Revisions ────────────────────────────────────────────────
+
+#[tauri::command]
+async fn get_icon_revisions(
+    app: tauri::AppHandle,
+    icon_id: String,
+) -> Result<Vec<IconRevision>, Error> {
+    startup::library(&app)?.get_icon_revisions(&icon_id)
+}
+
+/// Replaces an icon's artwork, keeping the previous version as a revision.
+#[tauri::command]
+async fn update_icon_svg(
+    app: tauri::AppHandle,
+    icon_id: String,
+    svg_content: String,
+    reason: String,
+) -> Result<Icon, Error> {
+    startup::library(&app)?.update_icon_svg(&icon_id, &svg_content, &reason)
+}
+
+#[tauri::command]
+async fn diff_icon_revisions(
+    app: tauri::AppHandle,
+    from_id: i64,
+    to_id: i64,
+) -> Result<RevisionDiff, Error> {
+    startup::library(&app)?.diff_icon_revisions(from_id, to_id)
+}
+
+#[tauri::command]
+async fn restore_icon_revision(app: tauri::AppHandle, revision_id: i64) -> Result<Icon, Error> {
+    startup::library(&app)?.restore_icon_revision(revision_id)
+}
+
+// ── Archives ─────────────────────────────────────────────────
+
+/// Asks where to save and exports the given collections, or the whole
+/// library for `None`.
+#[tauri::command]
+async fn export_archive(
+    app: tauri::AppHandle,
+    collection_ids: Option<Vec<String>>,
+) -> Result<ArchiveExportResult, Error> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let app_clone = app.clone();
+    let file = tauri::async_runtime::spawn_blocking(move || {
+        app_clone
+            .dialog()
+            .file()
+            .add_filter("IconBox archive", &["zip"])
+            .set_file_name("IconBox Library.zip")
+            .blocking_save_file()
+    })
+    .await
+    .map_err(|e| Error::from(e).while_doing("Dialog thread error"))?;
+
+    let file = file.ok_or_else(|| Error::cancelled("No file selected"))?;
+    let file = file
+        .as_path()
+        .ok_or_else(|| Error::invalid("Invalid path"))?;
+    startup::library(&app)?.export_archive(file, collection_ids.as_deref())
+}
+
+/// Asks for an archive to import. Returns its path.
+#[tauri::command]
+async fn choose_archive(app: tauri::AppHandle) -> Result<String, Error> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let file = tauri::async_runtime::spawn_blocking(move || {
+        app.dialog()
+            .file()
+            .add_filter("IconBox archive", &["zip"])
+            .blocking_pick_file()
+    })
+    .await
+    .map_err(|e| Error::from(e).while_doing("Dialog thread error"))?;
+
+    let file = file.ok_or_else(|| Error::cancelled("No file selected"))?;
+    Ok(file
+        .as_path()
+        .ok_or_else(|| Error::invalid("Invalid path"))?
+        .to_string_lossy()
+        .to_string())
+}
+
+#[tauri::command]
+async fn preview_archive_import(
+    app: tauri::AppHandle,
+    path: String,
+    parent_id: Option<String>,
+    on_conflict: String,
+) -> Result<ArchiveImportResult, Error> {
+    startup::library(&app)?.preview_archive_import(
+        std::path::Path::new(&path),
+        parent_id.as_deref(),
+        &on_conflict,
+    )
+}
 
 #[tauri::command]
-async fn get_total_icon_count(app: tauri::AppHandle) -> Result<i32, String> {
-    db::get_total_icon_count(&app).map_err(|e| e.to_string())
+async fn import_archive(
+    app: tauri::AppHandle,
+    path: String,
+    parent_id: Option<String>,
+    on_conflict: String,
+) -> Result<ArchiveImportResult, Error> {
+    startup::library(&app)?.import_archive(
+        std::path::Path::new(&path),
+        parent_id.as_deref(),
+        &on_conflict,
+    )
 }
 
+// ── Backups ──────────────────────────────────────────────────
+
 #[tauri::command]
-async fn get_favorite_count(app: tauri::AppHandle) -> Result<i32, String> {
-    db::get_favorite_count(&app).map_err(|e| e.to_string())
+async fn get_backup_status(app: tauri::AppHandle) -> Result<BackupStatus, Error> {
+    let library = startup::library(&app)?;
+    Ok(BackupStatus {
+        folder: library
+            .backup_dir()?
+            .map(|dir| dir.to_string_lossy().to_string()),
+        backups: library.get_backups()?,
+        last_error: startup::last_backup_error(&app),
+    })
 }
 
-// ── Helpers ──────────────────────────────────────────────────
+#[tauri::command]
+async fn backup_library_now(app: tauri::AppHandle) -> Result<DbBackup, Error> {
+    startup::library(&app)?.backup_now()
+}
 
-fn random_color() -> String {
-    let colors = [
-        "#6366f1", "#8b5cf6", "#a855f7", "#ec4899", "#f43f5e",
-        "#f97316", "#eab308", "#22c55e", "#14b8a6", "#3b82f6",
-    ];
-    colors[rand::random::<usize>() % colors.len()].to_string()
+/// Asks for a folder and makes it where backups go. Returns the folder.
+#[tauri::command]
+async fn choose_backup_folder(app: tauri::AppHandle) -> Result<String, Error> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let app_clone = app.clone();
+    let folder = tauri::async_runtime::spawn_blocking(move || {
+        app_clone.dialog().file().blocking_pick_folder()
+    })
+    .await
+    .map_err(|e| Error::from(e).while_doing("Dialog thread error"))?;
+
+    let folder = folder.ok_or_else(|| Error::cancelled("No folder selected"))?;
+    let folder = folder
+        .as_path()
+        .ok_or_else(|| Error::invalid("Invalid path"))?
+        .to_string_lossy()
+        .to_string();
+    startup::library(&app)?.save_setting("backup_folder", &folder)?;
+    Ok(folder)
+}
+
+// ── Settings ─────────────────────────────────────────────────
+
+#[tauri::command]
+async fn get_settings(app: tauri::AppHandle) -> Result<Settings, Error> {
+    startup::library(&app)?.get_settings()
+}
+
+#[tauri::command]
+async fn save_setting(app: tauri::AppHandle, key: String, value: String) -> Result<(), Error> {
+    startup::library(&app)?.save_setting(&key, &value)
+}
+
+// ── Stats ────────────────────────────────────────────────────
+
+#[tauri::command]
+async fn get_total_icon_count(app: tauri::AppHandle) -> Result<i32, Error> {
+    startup::library(&app)?.get_total_icon_count()
+}
+
+#[tauri::command]
+async fn get_favorite_count(app: tauri::AppHandle) -> Result<i32, Error> {
+    startup::library(&app)?.get_favorite_count()
 }
 
 // ── App entry ────────────────────────────────────────────────
@@ -222,21 +1003,80 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
         .setup(|app| {
-            let _ = db::init_db(app.handle());
+            // A failure is kept in the startup status; the UI offers recovery
+            let _ = startup::init_db(app.handle());
+            startup::start_backup_schedule(app.handle());
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
+            get_startup_status,
+            retry_open_library,
+            open_library_read_only,
+            restore_library_backup,
+            start_fresh_library,
             get_collections,
             create_collection,
             rename_collection,
             delete_collection,
+            move_collection,
+            reorder_collections,
+            merge_collections,
+            save_smart_collection,
             get_icons,
             get_all_icons,
             get_favorite_icons,
+            list_icons,
+            get_icon_svgs,
             toggle_favorite,
             update_icon_tags,
             delete_icon,
+            move_icon,
+            copy_icon,
+            bulk_add_tags,
+            bulk_remove_tags,
+            bulk_set_favorite,
+            bulk_move_icons,
+            bulk_copy_icons,
+            bulk_delete_icons,
+            get_tags,
+            get_tag_tree,
+            rename_tag,
+            set_tag_parent,
+            merge_tags,
+            delete_tag,
+            set_tag_color,
+            run_autotag,
+            search_icons,
+            suggest_search,
+            find_similar,
+            get_icon_palette,
+            semantic_search,
+            get_embedding_status,
+            load_embedding_model,
+            choose_embedding_model,
+            get_synonym_groups,
+            save_synonym_group,
+            delete_synonym_group,
+            reset_synonyms,
             import_folder,
+            get_trash,
+            restore_from_trash,
+            delete_from_trash,
+            empty_trash,
+            undo,
+            redo,
+            get_history,
+            get_icon_revisions,
+            update_icon_svg,
+            diff_icon_revisions,
+            restore_icon_revision,
+            export_archive,
+            choose_archive,
+            preview_archive_import,
+            import_archive,
+            get_backup_status,
+            backup_library_now,
+            choose_backup_folder,
             get_settings,
             save_setting,
             get_total_icon_count,
@@ -0,0 +1,247 @@
+// Versioned schema migrations, keyed on SQLite's `PRAGMA user_version`.
+//
+// Each migration takes the schema from version N-1 to N. It runs in its own
+// transaction together with the version bump, so a failing step leaves the
+// database at the last version that fully applied. Before an existing
+// database is touched, a copy of it is written with `VACUUM INTO`.
+//
+// The list of migrations lives in db.rs. Append new ones; never edit one
+// that has shipped.
+
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, ErrorCode, Result};
+
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    pub up: fn(&Connection) -> Result<()>,
+}
+
+pub fn user_version(conn: &Connection) -> Result<u32> {
+    Ok(conn.query_row("PRAGMA user_version", [], |row| row.get(0))?)
+}
+
+/// The version the app's schema is at once all `migrations` have run.
+pub fn latest(migrations: &[Migration]) -> u32 {
+    migrations.last().map_or(0, |m| m.version)
+}
+
+/// Returns the database's version, or an error if it was written by a newer
+/// version of the app than `migrations` know about.
+pub fn check_supported(conn: &Connection, migrations: &[Migration]) -> Result<u32> {
+    let current = user_version(conn)?;
+    let latest = latest(migrations);
+    if current > latest {
+        return Err(too_new_error(current, latest));
+    }
+    Ok(current)
+}
+
+/// Brings the database up to the last of `migrations`, which must be sorted
+/// by version, and returns the backup taken on the way, if any (`backup_dir`
+/// is `None` for in-memory databases). Fails without touching anything if
+/// the database was written by a newer version of the app.
+pub fn migrate(
+    conn: &Connection,
+    migrations: &[Migration],
+    backup_dir: Option<&Path>,
+) -> Result<Option<PathBuf>> {
+    let current = check_supported(conn, migrations)?;
+    if current == latest(migrations) {
+        return Ok(None);
+    }
+
+    let is_new: bool = conn.query_row("SELECT COUNT(*) = 0 FROM sqlite_master", [], |row| {
+        row.get(0)
+    })?;
+    let backup_path = match backup_dir {
+        Some(dir) if !is_new => Some(backup(conn, dir, current)?),
+        _ => None,
+    };
+
+    for migration in migrations.iter().filter(|m| m.version > current) {
+        let tx = conn.unchecked_transaction()?;
+        (migration.up)(&tx).map_err(|e| {
+            e.while_doing(&format!(
+                "Upgrading the library to version {} ({}) failed",
+                migration.version, migration.description
+            ))
+        })?;
+        tx.pragma_update(None, "user_version", migration.version)?;
+        tx.commit()?;
+    }
+    Ok(backup_path)
+}
+
+/// Writes a consistent copy of the database (WAL included) to `dir`.
+fn backup(conn: &Connection, dir: &Path, version: u32) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir).map_err(|e| Error::io(dir, "Cannot create backup folder", e))?;
+    let stem = format!(
+        "iconbox-v{}-{}",
+        version,
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    );
+    let mut path = dir.join(format!("{}.db", stem));
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("{}-{}.db", stem, n));
+        n += 1;
+    }
+    conn.execute("VACUUM INTO ?1", [path.to_string_lossy()])?;
+    Ok(path)
+}
+
+fn too_new_error(found: u32, supported: u32) -> Error {
+    Error::new(
+        ErrorCode::TooNew,
+        format!(
+            "This library was saved by a newer version of IconBox (schema version {}, \
+             this version supports up to {}). Update IconBox to open it.",
+            found, supported
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_a(conn: &Connection) -> Result<()> {
+        Ok(conn.execute_batch("CREATE TABLE a (x INTEGER)")?)
+    }
+
+    fn add_b(conn: &Connection) -> Result<()> {
+        Ok(conn.execute_batch("ALTER TABLE a ADD COLUMN b TEXT")?)
+    }
+
+    fn broken(conn: &Connection) -> Result<()> {
+        Ok(conn.execute_batch("CREATE TABLE c (x); SELECT * FROM missing")?)
+    }
+
+    const STEPS: &[Migration] = &[
+        Migration {
+            version: 1,
+            description: "create a",
+            up: create_a,
+        },
+        Migration {
+            version: 2,
+            description: "add b",
+            up: add_b,
+        },
+    ];
+
+    fn backup_dir(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("iconbox-migrations-{}", test));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn new_databases_migrate_without_a_backup() {
+        let conn = Connection::open_in_memory().unwrap();
+        let dir = backup_dir("new");
+        assert_eq!(migrate(&conn, STEPS, Some(&dir)).unwrap(), None);
+        assert_eq!(user_version(&conn).unwrap(), 2);
+        conn.execute("INSERT INTO a (x, b) VALUES (1, 'b')", [])
+            .unwrap();
+        // Up to date: nothing runs again
+        assert_eq!(migrate(&conn, STEPS, Some(&dir)).unwrap(), None);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn existing_databases_are_backed_up_first() {
+        let conn = Connection::open_in_memory().unwrap();
+        let dir = backup_dir("existing");
+        migrate(&conn, &STEPS[..1], Some(&dir)).unwrap();
+        conn.execute("INSERT INTO a (x) VALUES (7)", []).unwrap();
+
+        let backup = migrate(&conn, STEPS, Some(&dir)).unwrap().unwrap();
+        assert!(backup.starts_with(&dir));
+        assert!(backup
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("iconbox-v1-"));
+        let copy = Connection::open(&backup).unwrap();
+        assert_eq!(user_version(&copy).unwrap(), 1);
+        let x: i64 = copy
+            .query_row("SELECT x FROM a", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(x, 7);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn a_failing_step_keeps_the_last_applied_version() {
+        let conn = Connection::open_in_memory().unwrap();
+        let dir = backup_dir("failing");
+        let steps = [
+            Migration {
+                version: 1,
+                description: "create a",
+                up: create_a,
+            },
+            Migration {
+                version: 2,
+                description: "broken",
+                up: broken,
+            },
+        ];
+        let err = migrate(&conn, &steps, Some(&dir)).unwrap_err();
+        assert!(err.to_string().contains("version 2 (broken)"), "{}", err);
+        assert_eq!(user_version(&conn).unwrap(), 1);
+        let has_c: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM sqlite_master WHERE name = 'c'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(!has_c);
+    }
+
+    #[test]
+    fn newer_databases_are_refused() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", 3).unwrap();
+        let err = migrate(&conn, STEPS, Some(&backup_dir("newer"))).unwrap_err();
+        assert_eq!(err.code, ErrorCode::TooNew);
+        assert!(err.to_string().contains("schema version 3"), "{}", err);
+        assert_eq!(user_version(&conn).unwrap(), 3);
+    }
+
+    #[test]
+    fn supported_versions_are_checked_without_migrating() {
+        let conn = Connection::open_in_memory().unwrap();
+        assert_eq!(latest(STEPS), 2);
+        assert_eq!(latest(&[]), 0);
+        assert_eq!(check_supported(&conn, STEPS).unwrap(), 0);
+        conn.pragma_update(None, "user_version", 2).unwrap();
+        assert_eq!(check_supported(&conn, STEPS).unwrap(), 2);
+        assert!(check_supported(&conn, &STEPS[..1]).is_err());
+    }
+
+    #[test]
+    fn backups_taken_in_the_same_second_do_not_collide() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_a(&conn).unwrap();
+        let dir = backup_dir("collide");
+        let first = backup(&conn, &dir, 1).unwrap();
+        let second = backup(&conn, &dir, 1).unwrap();
+        assert_ne!(first, second);
+        assert!(first.exists() && second.exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn without_a_backup_folder_nothing_is_copied() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate(&conn, &STEPS[..1], None).unwrap();
+        assert_eq!(migrate(&conn, STEPS, None).unwrap(), None);
+        assert_eq!(user_version(&conn).unwrap(), 2);
+    }
+}
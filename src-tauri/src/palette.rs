@@ -0,0 +1,297 @@
+// Color palettes of icons, for "contains color near #ff5500" and
+// "monochrome only" searches.
+//
+// Colors are collected from presentation attributes (`fill="#f50"`), inline
+// `style` attributes, `<style>` blocks and gradient stops, so illustrative
+// icons styled through CSS classes are covered too. `none`, `currentColor`
+// and `url(#gradient)` references aren't colors themselves; the gradient's
+// `stop-color`s are picked up instead.
+
+use std::collections::HashMap;
+
+/// Attribute and CSS property names that carry a paint color.
+const COLOR_PROPERTIES: &[&str] = &["fill", "stroke", "stop-color", "flood-color", "color"];
+
+/// Most colors kept per icon; beyond that the rarest ones are dropped.
+const MAX_COLORS: usize = 16;
+
+/// How far a color may be from the searched one and still count as "near",
+/// in "redmean" weighted RGB distance (0 for identical, about 765 for black
+/// vs white). Roughly the step between neighbouring named shades, e.g.
+/// orange vs dark orange.
+pub const NEAR_THRESHOLD: f64 = 100.0;
+
+const NAMED_COLORS: &[(&str, Rgb)] = &[
+    ("black", Rgb::new(0, 0, 0)),
+    ("white", Rgb::new(255, 255, 255)),
+    ("gray", Rgb::new(128, 128, 128)),
+    ("grey", Rgb::new(128, 128, 128)),
+    ("silver", Rgb::new(192, 192, 192)),
+    ("red", Rgb::new(255, 0, 0)),
+    ("maroon", Rgb::new(128, 0, 0)),
+    ("orange", Rgb::new(255, 165, 0)),
+    ("yellow", Rgb::new(255, 255, 0)),
+    ("gold", Rgb::new(255, 215, 0)),
+    ("olive", Rgb::new(128, 128, 0)),
+    ("lime", Rgb::new(0, 255, 0)),
+    ("green", Rgb::new(0, 128, 0)),
+    ("teal", Rgb::new(0, 128, 128)),
+    ("cyan", Rgb::new(0, 255, 255)),
+    ("aqua", Rgb::new(0, 255, 255)),
+    ("blue", Rgb::new(0, 0, 255)),
+    ("navy", Rgb::new(0, 0, 128)),
+    ("purple", Rgb::new(128, 0, 128)),
+    ("magenta", Rgb::new(255, 0, 255)),
+    ("fuchsia", Rgb::new(255, 0, 255)),
+    ("pink", Rgb::new(255, 192, 203)),
+    ("brown", Rgb::new(165, 42, 42)),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Rgb { r, g, b }
+    }
+
+    pub fn hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
+    /// Parses a CSS color: `#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`,
+    /// `rgb()`/`rgba()` and the basic named colors. Fully transparent colors
+    /// and non-colors (`none`, `currentColor`, `url(...)`) return `None`.
+    pub fn parse(value: &str) -> Option<Rgb> {
+        let value = value.trim().trim_end_matches("!important").trim();
+        let lower = value.to_lowercase();
+
+        if let Some(hex) = lower.strip_prefix('#') {
+            return parse_hex(hex);
+        }
+        if let Some(args) = lower
+            .strip_prefix("rgba(")
+            .or_else(|| lower.strip_prefix("rgb("))
+        {
+            return parse_rgb_function(args.strip_suffix(')')?);
+        }
+        NAMED_COLORS
+            .iter()
+            .find(|(name, _)| *name == lower)
+            .map(|(_, rgb)| *rgb)
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<Rgb> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digit = |i: usize, len: usize| u8::from_str_radix(&hex[i * len..(i + 1) * len], 16).ok();
+    let (rgb, alpha) = match hex.len() {
+        3 | 4 => {
+            let short = |i| digit(i, 1).map(|d| d * 17);
+            (
+                Rgb::new(short(0)?, short(1)?, short(2)?),
+                if hex.len() == 4 { short(3)? } else { 255 },
+            )
+        }
+        6 | 8 => (
+            Rgb::new(digit(0, 2)?, digit(1, 2)?, digit(2, 2)?),
+            if hex.len() == 8 { digit(3, 2)? } else { 255 },
+        ),
+        _ => return None,
+    };
+    (alpha > 0).then_some(rgb)
+}
+
+fn parse_rgb_function(args: &str) -> Option<Rgb> {
+    let parts: Vec<&str> = args
+        .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+        .filter(|p| !p.is_empty())
+        .collect();
+    if parts.len() < 3 {
+        return None;
+    }
+    let channel = |p: &str| -> Option<u8> {
+        let v = match p.strip_suffix('%') {
+            Some(pct) => pct.parse::<f64>().ok()? * 2.55,
+            None => p.parse::<f64>().ok()?,
+        };
+        Some(v.round().clamp(0.0, 255.0) as u8)
+    };
+    if let Some(alpha) = parts.get(3) {
+        let a = match alpha.strip_suffix('%') {
+            Some(pct) => pct.parse::<f64>().ok()? / 100.0,
+            None => alpha.parse::<f64>().ok()?,
+        };
+        if a <= 0.0 {
+            return None;
+        }
+    }
+    Some(Rgb::new(
+        channel(parts[0])?,
+        channel(parts[1])?,
+        channel(parts[2])?,
+    ))
+}
+
+/// Named colors accepted by `Rgb::parse`, for autocomplete.
+pub fn color_names() -> impl Iterator<Item = &'static str> {
+    NAMED_COLORS.iter().map(|(name, _)| *name)
+}
+
+/// Extracts the distinct colors used by an SVG, most frequent first.
+///
+/// An empty palette means the icon draws only with `currentColor` or the
+/// default black, i.e. it is monochrome and takes the surrounding text color.
+pub fn extract(svg: &str) -> Vec<Rgb> {
+    let lower = svg.to_lowercase();
+    let mut counts: HashMap<Rgb, usize> = HashMap::new();
+    let mut order = Vec::new();
+
+    for property in COLOR_PROPERTIES {
+        for value in property_values(&lower, property) {
+            if let Some(rgb) = Rgb::parse(value) {
+                let count = counts.entry(rgb).or_insert(0);
+                if *count == 0 {
+                    order.push(rgb);
+                }
+                *count += 1;
+            }
+        }
+    }
+
+    // Stable sort keeps document order among equally frequent colors
+    order.sort_by_key(|rgb| std::cmp::Reverse(counts[rgb]));
+    order.truncate(MAX_COLORS);
+    order
+}
+
+/// Finds the values assigned to `property`, either as an attribute
+/// (`fill="..."`) or as a CSS declaration (`fill: ...;`) in a `style`
+/// attribute or `<style>` block.
+fn property_values<'a>(svg: &'a str, property: &str) -> Vec<&'a str> {
+    let mut values = Vec::new();
+    let bytes = svg.as_bytes();
+
+    for (start, _) in svg.match_indices(property) {
+        // Whole property names only: skip `data-fill`, `fill-opacity`, ...
+        let before = start.checked_sub(1).map(|i| bytes[i]);
+        if before.is_some_and(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_') {
+            continue;
+        }
+        let rest = svg[start + property.len()..].trim_start();
+        let value = if let Some(attr) = rest.strip_prefix('=') {
+            let attr = attr.trim_start();
+            let Some(quote) = attr.chars().next().filter(|&c| c == '"' || c == '\'') else {
+                continue;
+            };
+            let attr = &attr[1..];
+            attr.find(quote).map(|end| &attr[..end])
+        } else if let Some(decl) = rest.strip_prefix(':') {
+            let end = decl
+                .find([';', '}', '"', '\'', '<', '\n'])
+                .unwrap_or(decl.len());
+            Some(&decl[..end])
+        } else {
+            None
+        };
+        if let Some(value) = value {
+            values.push(value);
+        }
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hex_forms() {
+        assert_eq!(Rgb::parse("#F50"), Some(Rgb::new(255, 85, 0)));
+        assert_eq!(Rgb::parse("#f50c"), Some(Rgb::new(255, 85, 0)));
+        assert_eq!(Rgb::parse("#ff5500"), Some(Rgb::new(255, 85, 0)));
+        assert_eq!(
+            Rgb::parse(" #ff550080 !important"),
+            Some(Rgb::new(255, 85, 0))
+        );
+        assert_eq!(Rgb::parse("#ff55"), Some(Rgb::new(255, 255, 85)));
+        assert_eq!(Rgb::parse("#ff550"), None);
+        assert_eq!(Rgb::parse("#ggg"), None);
+        assert_eq!(Rgb::parse("#ÿÿÿ"), None);
+    }
+
+    #[test]
+    fn fully_transparent_colors_are_not_colors() {
+        assert_eq!(Rgb::parse("#f500"), None);
+        assert_eq!(Rgb::parse("#ff550000"), None);
+        assert_eq!(Rgb::parse("rgba(255, 85, 0, 0)"), None);
+        assert_eq!(Rgb::parse("rgb(255 85 0 / 0%)"), None);
+        assert_eq!(
+            Rgb::parse("rgba(255, 85, 0, 0.01)"),
+            Some(Rgb::new(255, 85, 0))
+        );
+    }
+
+    #[test]
+    fn parses_rgb_functions_and_names() {
+        assert_eq!(Rgb::parse("rgb(255,85,0)"), Some(Rgb::new(255, 85, 0)));
+        assert_eq!(
+            Rgb::parse("RGB(100%, 0%, 100%)"),
+            Some(Rgb::new(255, 0, 255))
+        );
+        assert_eq!(Rgb::parse("rgb(300, -5, 0)"), Some(Rgb::new(255, 0, 0)));
+        assert_eq!(Rgb::parse("rgb(1, 2)"), None);
+        assert_eq!(Rgb::parse("rgb(1, 2, 3"), None);
+        assert_eq!(Rgb::parse("Teal"), Some(Rgb::new(0, 128, 128)));
+        for non_color in ["none", "currentColor", "url(#g)", "inherit", ""] {
+            assert_eq!(Rgb::parse(non_color), None, "{}", non_color);
+        }
+        assert_eq!(Rgb::new(255, 85, 0).hex(), "#ff5500");
+    }
+
+    #[test]
+    fn extracts_attributes_styles_and_gradient_stops() {
+        let svg = r##"<svg>
+            <style>.a { fill: #00f; } .b{stroke:red}</style>
+            <linearGradient><stop stop-color="#0f0"/><stop offset="1" style="stop-color: white"/></linearGradient>
+            <path fill="url(#g)" stroke="currentColor"/>
+            <path class="a" fill='#00F' data-fill="#123456" fill-opacity="#654321"/>
+        </svg>"##;
+        assert_eq!(
+            extract(svg),
+            [
+                Rgb::new(0, 0, 255),
+                Rgb::new(255, 0, 0),
+                Rgb::new(0, 255, 0),
+                Rgb::new(255, 255, 255),
+            ]
+        );
+    }
+
+    #[test]
+    fn monochrome_icons_have_an_empty_palette() {
+        assert!(extract(r#"<svg><path fill="currentColor" stroke="none"/></svg>"#).is_empty());
+        assert!(extract(r#"<svg><path d="M0 0"/></svg>"#).is_empty());
+    }
+
+    #[test]
+    fn keeps_the_most_frequent_colors() {
+        let svg: String = (0..20u8)
+            .map(|i| format!(r##"<path fill="#{:02x}0000"/>"##, i))
+            .chain(std::iter::repeat_n(
+                r##"<path fill="#00ff00"/>"##.to_string(),
+                3,
+            ))
+            .collect();
+        let colors = extract(&svg);
+        assert_eq!(colors.len(), MAX_COLORS);
+        assert_eq!(colors[0], Rgb::new(0, 255, 0));
+        assert_eq!(colors[1], Rgb::new(0, 0, 0));
+    }
+}
@@ -0,0 +1,658 @@
+// Structured search queries.
+//
+// The search bar accepts free text mixed with `field:value` filters, e.g.
+//
+//     arrow tag:nav collection:tabler fav:yes size:<2kb stroke:yes -tag:deprecated
+//     color:#ff5500 mono:no
+//
+// `parse` turns the input into a `Query`, and `Query::sql_filter` renders the
+// filters as a parameterized WHERE fragment over `icons i`. Values are only
+// ever bound as parameters, never spliced into the SQL text.
+
+use crate::palette::{self, Rgb};
+use rusqlite::types::Value;
+use serde::Serialize;
+use std::fmt;
+
+/// Filter fields understood by the parser, with a short description used for
+/// autocomplete.
+pub const FIELDS: &[(&str, &str)] = &[
+    (
+        "tag",
+        "Icon has this tag or a subtag of it (use * as a wildcard)",
+    ),
+    ("collection", "Icon belongs to this collection"),
+    ("name", "Icon name contains this text"),
+    ("fav", "Favorite: yes or no"),
+    ("size", "File size, e.g. size:<2kb or size:>=500b"),
+    ("stroke", "Uses strokes (outline style): yes or no"),
+    ("added", "Date added, e.g. added:>2024-01-31"),
+    (
+        "color",
+        "Uses a color near this one, e.g. color:#ff5500 or color:red",
+    ),
+    ("mono", "Monochrome (at most one color): yes or no"),
+];
+
+const BOOL_VALUES: &[&str] = &["yes", "no"];
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Query {
+    /// Free-text terms that must match (ranked through the FTS index).
+    pub text: Vec<String>,
+    /// Free-text terms prefixed with `-`.
+    pub excluded_text: Vec<String>,
+    pub filters: Vec<Filter>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    pub negated: bool,
+    pub kind: FilterKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterKind {
+    Tag(String),
+    Collection(String),
+    Name(String),
+    Favorite(bool),
+    Stroke(bool),
+    Size(Comparison, i64),
+    Added(Comparison, String),
+    Color(Rgb),
+    Monochrome(bool),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+}
+
+impl Comparison {
+    fn sql(self) -> &'static str {
+        match self {
+            Comparison::Lt => "<",
+            Comparison::Le => "<=",
+            Comparison::Gt => ">",
+            Comparison::Ge => ">=",
+            Comparison::Eq => "=",
+        }
+    }
+
+    /// Splits a leading comparison operator off `value` (defaults to `=`).
+    fn split(value: &str) -> (Comparison, &str) {
+        for (prefix, cmp) in [
+            ("<=", Comparison::Le),
+            (">=", Comparison::Ge),
+            ("<", Comparison::Lt),
+            (">", Comparison::Gt),
+            ("=", Comparison::Eq),
+        ] {
+            if let Some(rest) = value.strip_prefix(prefix) {
+                return (cmp, rest);
+            }
+        }
+        (Comparison::Eq, value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryError {
+    pub message: String,
+    /// Zero-based character offset of the offending token.
+    pub position: usize,
+}
+
+impl QueryError {
+    fn new(message: impl Into<String>, position: usize) -> Self {
+        QueryError {
+            message: message.into(),
+            position,
+        }
+    }
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (at position {})", self.message, self.position + 1)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// A WHERE fragment and the parameters it binds, in order.
+pub struct SqlFilter {
+    pub sql: String,
+    pub params: Vec<Value>,
+}
+
+// ── Parsing ──────────────────────────────────────────────────
+
+struct Token {
+    text: String,
+    /// Character offset of the token in the input.
+    start: usize,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, QueryError> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut start = 0;
+    let mut in_token = false;
+    let mut quote_start = None;
+
+    for (pos, c) in input.chars().enumerate() {
+        if c == '"' {
+            quote_start = match quote_start {
+                Some(_) => None,
+                None => Some(pos),
+            };
+            if !in_token {
+                in_token = true;
+                start = pos;
+            }
+        } else if c.is_whitespace() && quote_start.is_none() {
+            if in_token {
+                tokens.push(Token {
+                    text: std::mem::take(&mut current),
+                    start,
+                });
+                in_token = false;
+            }
+        } else {
+            if !in_token {
+                in_token = true;
+                start = pos;
+            }
+            current.push(c);
+        }
+    }
+
+    if let Some(pos) = quote_start {
+        return Err(QueryError::new("Unterminated quote", pos));
+    }
+    if in_token {
+        tokens.push(Token {
+            text: current,
+            start,
+        });
+    }
+    Ok(tokens)
+}
+
+/// Splits `field:value` when the part before the colon looks like a field
+/// name. Anything else (`12:30`, `http://...`) is treated as free text.
+fn split_field(token: &str) -> Option<(&str, &str)> {
+    let (field, value) = token.split_once(':')?;
+    if !field.is_empty() && field.chars().all(|c| c.is_ascii_alphabetic()) {
+        Some((field, value))
+    } else {
+        None
+    }
+}
+
+fn canonical_field(field: &str) -> Option<&'static str> {
+    match field.to_lowercase().as_str() {
+        "tag" | "tags" => Some("tag"),
+        "collection" | "col" | "in" => Some("collection"),
+        "name" => Some("name"),
+        "fav" | "favorite" | "favourite" => Some("fav"),
+        "size" => Some("size"),
+        "stroke" => Some("stroke"),
+        "added" | "date" => Some("added"),
+        "color" | "colour" => Some("color"),
+        "mono" | "monochrome" => Some("mono"),
+        _ => None,
+    }
+}
+
+pub fn parse(input: &str) -> Result<Query, QueryError> {
+    let mut query = Query::default();
+
+    for token in tokenize(input)? {
+        let (negated, body) = match token.text.strip_prefix('-') {
+            Some(rest) if !rest.is_empty() => (true, rest),
+            _ => (false, token.text.as_str()),
+        };
+
+        let Some((field, value)) = split_field(body) else {
+            if negated {
+                query.excluded_text.push(body.to_string());
+            } else {
+                query.text.push(body.to_string());
+            }
+            continue;
+        };
+
+        let Some(field) = canonical_field(field) else {
+            let known: Vec<&str> = FIELDS.iter().map(|(f, _)| *f).collect();
+            return Err(QueryError::new(
+                format!(
+                    "Unknown field '{}' (expected one of: {})",
+                    field,
+                    known.join(", ")
+                ),
+                token.start,
+            ));
+        };
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(QueryError::new(
+                format!("Missing value after '{}:'", field),
+                token.start,
+            ));
+        }
+
+        let kind = parse_filter(field, value).map_err(|msg| QueryError::new(msg, token.start))?;
+        query.filters.push(Filter { negated, kind });
+    }
+
+    Ok(query)
+}
+
+fn parse_filter(field: &str, value: &str) -> Result<FilterKind, String> {
+    Ok(match field {
+        "tag" => FilterKind::Tag(value.to_string()),
+        "collection" => FilterKind::Collection(value.to_string()),
+        "name" => FilterKind::Name(value.to_string()),
+        "fav" => FilterKind::Favorite(parse_bool(field, value)?),
+        "stroke" => FilterKind::Stroke(parse_bool(field, value)?),
+        "size" => {
+            let (cmp, rest) = Comparison::split(value);
+            let bytes = parse_size(rest)
+                .ok_or_else(|| format!("Invalid size '{}' (use e.g. 500b, 2kb, 1.5mb)", rest))?;
+            FilterKind::Size(cmp, bytes)
+        }
+        "added" => {
+            let (cmp, rest) = Comparison::split(value);
+            let date = chrono::NaiveDate::parse_from_str(rest, "%Y-%m-%d")
+                .map_err(|_| format!("Invalid date '{}' (use YYYY-MM-DD)", rest))?;
+            FilterKind::Added(cmp, date.format("%Y-%m-%d").to_string())
+        }
+        "color" => match value.to_lowercase().as_str() {
+            // Shorthand for mono:yes
+            "mono" | "monochrome" => FilterKind::Monochrome(true),
+            _ => FilterKind::Color(Rgb::parse(value).ok_or_else(|| {
+                format!(
+                    "Invalid color '{}' (use e.g. #ff5500, rgb(255,85,0) or red)",
+                    value
+                )
+            })?),
+        },
+        "mono" => FilterKind::Monochrome(parse_bool(field, value)?),
+        _ => unreachable!("canonical_field returned an unhandled field"),
+    })
+}
+
+fn parse_bool(field: &str, value: &str) -> Result<bool, String> {
+    match value.to_lowercase().as_str() {
+        "yes" | "y" | "true" | "1" | "on" => Ok(true),
+        "no" | "n" | "false" | "0" | "off" => Ok(false),
+        _ => Err(format!(
+            "Invalid value '{}' for {} (use yes or no)",
+            value, field
+        )),
+    }
+}
+
+/// Parses sizes like `512`, `512b`, `2kb`, `1.5mb` into bytes.
+fn parse_size(value: &str) -> Option<i64> {
+    let value = value.to_lowercase();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let multiplier = match unit {
+        "" | "b" => 1.0,
+        "k" | "kb" => 1024.0,
+        "m" | "mb" => 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((number * multiplier).round() as i64)
+}
+
+// ── SQL ──────────────────────────────────────────────────────
+
+/// Turns a user value into a LIKE pattern: `*` becomes `%`, everything else
+/// matches literally. Patterns must be used with `ESCAPE '\'`.
+fn like_pattern(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '%' | '_' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '*' => out.push('%'),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+const STROKE_SQL: &str = "(replace(replace(i.svg_content, 'stroke=\"none\"', ''), 'stroke:none', '') LIKE '%stroke=%'
+      OR replace(replace(i.svg_content, 'stroke=\"none\"', ''), 'stroke:none', '') LIKE '%stroke:%')";
+
+/// "Redmean" weighted RGB distance from `icon_colors c` to a color bound as
+/// r, r, r, g, g, r, b, b, compared squared against the last parameter:
+/// (2 + r̄/256)·Δr² + 4·Δg² + (2 + (255 − r̄)/256)·Δb².
+const COLOR_NEAR_SQL: &str = "EXISTS (SELECT 1 FROM icon_colors c WHERE c.icon_id = i.id
+      AND (512 + (c.r + ?) / 2.0) * (c.r - ?) * (c.r - ?) / 256.0
+        + 4 * (c.g - ?) * (c.g - ?)
+        + (767 - (c.r + ?) / 2.0) * (c.b - ?) * (c.b - ?) / 256.0 <= ?)";
+
+const MONOCHROME_SQL: &str = "(SELECT COUNT(*) FROM icon_colors c WHERE c.icon_id = i.id) <= 1";
+
+impl Query {
+    /// Renders the structured filters and excluded terms as a WHERE fragment
+    /// over `icons i`. Free-text `text` terms are left to the caller, which
+    /// matches them against the FTS index for ranking.
+    pub fn sql_filter(&self) -> SqlFilter {
+        let mut conditions = Vec::new();
+        let mut params = Vec::new();
+
+        for term in &self.excluded_text {
+            if let Some(expr) = crate::search::match_expression(term) {
+                conditions.push(
+                    "i.rowid NOT IN (SELECT rowid FROM icons_fts WHERE icons_fts MATCH ?)"
+                        .to_string(),
+                );
+                params.push(Value::Text(expr));
+            }
+        }
+
+        for filter in &self.filters {
+            let sql = match &filter.kind {
+                FilterKind::Tag(tag) => {
+                    // Matches the tag at any depth of a path, plus its
+                    // subtags: tag:style finds style and style/outline
+                    let pattern = like_pattern(tag.trim_matches('/'));
+                    for p in [
+                        pattern.clone(),
+                        format!("{}/%", pattern),
+                        format!("%/{}", pattern),
+                        format!("%/{}/%", pattern),
+                    ] {
+                        params.push(Value::Text(p));
+                    }
+                    "EXISTS (SELECT 1 FROM icon_tags it JOIN tags t ON t.id = it.tag_id
+                             WHERE it.icon_id = i.id
+                               AND (t.name LIKE ? ESCAPE '\\' OR t.name LIKE ? ESCAPE '\\'
+                                    OR t.name LIKE ? ESCAPE '\\' OR t.name LIKE ? ESCAPE '\\'))"
+                        .to_string()
+                }
+                FilterKind::Collection(name) => {
+                    params.push(Value::Text(like_pattern(name)));
+                    params.push(Value::Text(name.clone()));
+                    "i.collection_id IN (SELECT id FROM collections WHERE name LIKE ? ESCAPE '\\' OR id = ?)"
+                        .to_string()
+                }
+                FilterKind::Name(name) => {
+                    params.push(Value::Text(format!("%{}%", like_pattern(name))));
+                    "i.name LIKE ? ESCAPE '\\'".to_string()
+                }
+                FilterKind::Favorite(yes) => {
+                    params.push(Value::Integer(*yes as i64));
+                    "i.favorite = ?".to_string()
+                }
+                FilterKind::Stroke(yes) => {
+                    if *yes {
+                        STROKE_SQL.to_string()
+                    } else {
+                        format!("NOT {}", STROKE_SQL)
+                    }
+                }
+                FilterKind::Size(cmp, bytes) => {
+                    params.push(Value::Integer(*bytes));
+                    format!("i.file_size {} ?", cmp.sql())
+                }
+                FilterKind::Added(cmp, date) => {
+                    params.push(Value::Text(date.clone()));
+                    format!("substr(i.created_at, 1, 10) {} ?", cmp.sql())
+                }
+                FilterKind::Color(rgb) => {
+                    let (r, g, b) = (rgb.r as i64, rgb.g as i64, rgb.b as i64);
+                    for v in [r, r, r, g, g, r, b, b] {
+                        params.push(Value::Integer(v));
+                    }
+                    params.push(Value::Real(
+                        palette::NEAR_THRESHOLD * palette::NEAR_THRESHOLD,
+                    ));
+                    COLOR_NEAR_SQL.to_string()
+                }
+                FilterKind::Monochrome(yes) => {
+                    if *yes {
+                        MONOCHROME_SQL.to_string()
+                    } else {
+                        format!("NOT {}", MONOCHROME_SQL)
+                    }
+                }
+            };
+            conditions.push(if filter.negated {
+                format!("NOT ({})", sql)
+            } else {
+                sql
+            });
+        }
+
+        SqlFilter {
+            sql: if conditions.is_empty() {
+                "1".to_string()
+            } else {
+                conditions.join(" AND ")
+            },
+            params,
+        }
+    }
+}
+
+// ── Autocomplete ─────────────────────────────────────────────
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Suggestion {
+    /// Text shown in the dropdown.
+    pub label: String,
+    pub detail: String,
+    /// The whole query with the last token replaced by the suggestion.
+    pub query: String,
+}
+
+/// Suggests completions for the token under the cursor (the last one):
+/// field names while typing a bare word, and known values after `field:`.
+pub fn suggest(input: &str, tags: &[String], collections: &[String]) -> Vec<Suggestion> {
+    const MAX: usize = 20;
+
+    let last_start = input
+        .char_indices()
+        .rev()
+        .find(|(_, c)| c.is_whitespace())
+        .map(|(i, c)| i + c.len_utf8())
+        .unwrap_or(0);
+    let (head, last) = input.split_at(last_start);
+    let (negation, last) = match last.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", last),
+    };
+
+    let complete = |token: String, label: String, detail: &str| Suggestion {
+        query: format!("{}{}{}", head, negation, token),
+        label,
+        detail: detail.to_string(),
+    };
+
+    let Some((field, partial)) = split_field(last) else {
+        let partial = last.to_lowercase();
+        if partial.is_empty() {
+            return vec![];
+        }
+        return FIELDS
+            .iter()
+            .filter(|(f, _)| f.starts_with(&partial))
+            .map(|(f, detail)| complete(format!("{}:", f), format!("{}:", f), detail))
+            .collect();
+    };
+
+    let Some(field) = canonical_field(field) else {
+        return vec![];
+    };
+    let partial_lower = partial.trim_matches('"').to_lowercase();
+    let quote = |v: &str| {
+        if v.contains(char::is_whitespace) {
+            format!("\"{}\"", v)
+        } else {
+            v.to_string()
+        }
+    };
+
+    let values: Vec<(String, &str)> = match field {
+        "tag" => tags.iter().map(|t| (t.clone(), "tag")).collect(),
+        "collection" => collections
+            .iter()
+            .map(|c| (c.clone(), "collection"))
+            .collect(),
+        "fav" | "stroke" | "mono" => BOOL_VALUES.iter().map(|v| (v.to_string(), "")).collect(),
+        "color" => std::iter::once(("mono".to_string(), "monochrome icons"))
+            .chain(palette::color_names().map(|name| (name.to_string(), "color")))
+            .collect(),
+        _ => vec![],
+    };
+
+    values
+        .into_iter()
+        .filter(|(v, _)| v.to_lowercase().starts_with(&partial_lower))
+        .take(MAX)
+        .map(|(v, detail)| complete(format!("{}:{}", field, quote(&v)), v, detail))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(negated: bool, kind: FilterKind) -> Filter {
+        Filter { negated, kind }
+    }
+
+    #[test]
+    fn parses_text_filters_and_negation() {
+        let q =
+            parse(r#"arrow -old tag:nav -tag:deprecated in:"Material Icons" fav:YES size:<=2kb"#)
+                .unwrap();
+        assert_eq!(q.text, ["arrow"]);
+        assert_eq!(q.excluded_text, ["old"]);
+        assert_eq!(
+            q.filters,
+            [
+                filter(false, FilterKind::Tag("nav".into())),
+                filter(true, FilterKind::Tag("deprecated".into())),
+                filter(false, FilterKind::Collection("Material Icons".into())),
+                filter(false, FilterKind::Favorite(true)),
+                filter(false, FilterKind::Size(Comparison::Le, 2048)),
+            ]
+        );
+    }
+
+    #[test]
+    fn colons_after_non_field_words_are_text() {
+        let q = parse("12:30 a-b -").unwrap();
+        assert_eq!(q.text, ["12:30", "a-b", "-"]);
+        assert!(q.filters.is_empty());
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_token() {
+        let err = parse("arrow hue:red").unwrap_err();
+        assert_eq!(err.position, 6);
+        assert!(err.message.starts_with("Unknown field 'hue'"));
+
+        assert_eq!(parse("a tag:").unwrap_err().position, 2);
+        assert_eq!(parse("fav:maybe").unwrap_err().position, 0);
+        assert_eq!(parse("added:2024-13-01").unwrap_err().position, 0);
+        // Positions count characters, not bytes
+        assert_eq!(parse("ёж size:big").unwrap_err().position, 3);
+        assert_eq!(parse(r#"x "open"#).unwrap_err().position, 2);
+    }
+
+    #[test]
+    fn sizes_accept_units_and_fractions() {
+        assert_eq!(parse_size("512"), Some(512));
+        assert_eq!(parse_size("512B"), Some(512));
+        assert_eq!(parse_size("1.5kb"), Some(1536));
+        assert_eq!(parse_size("2m"), Some(2 * 1024 * 1024));
+        assert_eq!(parse_size("kb"), None);
+        assert_eq!(parse_size("2gb"), None);
+    }
+
+    #[test]
+    fn like_patterns_escape_wildcards() {
+        assert_eq!(like_pattern("50%_a\\b*"), "50\\%\\_a\\\\b%");
+    }
+
+    #[test]
+    fn sql_filter_binds_one_param_per_placeholder() {
+        let q =
+            parse("-x tag:a* col:c name:n fav:no stroke:yes size:>1kb added:>=2024-01-31").unwrap();
+        let filter = q.sql_filter();
+        assert_eq!(filter.sql.matches('?').count(), filter.params.len());
+        assert!(filter.params.contains(&Value::Integer(1024)));
+        assert!(filter.params.contains(&Value::Text("2024-01-31".into())));
+        assert_eq!(Query::default().sql_filter().sql, "1");
+    }
+
+    #[test]
+    fn color_filters_match_nearby_shades() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE icons (id TEXT);
+             CREATE TABLE icon_colors (icon_id TEXT, r INTEGER, g INTEGER, b INTEGER);
+             INSERT INTO icons VALUES ('orange'), ('dark-orange'), ('blue'), ('mono');
+             INSERT INTO icon_colors VALUES ('orange', 255, 165, 0), ('dark-orange', 255, 140, 0),
+                                            ('blue', 0, 0, 255), ('blue', 255, 255, 255);",
+        )
+        .unwrap();
+        let matching = |input: &str| -> Vec<String> {
+            let filter = parse(input).unwrap().sql_filter();
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT id FROM icons i WHERE {} ORDER BY id",
+                    filter.sql
+                ))
+                .unwrap();
+            stmt.query_map(rusqlite::params_from_iter(filter.params), |row| row.get(0))
+                .unwrap()
+                .collect::<rusqlite::Result<_>>()
+                .unwrap()
+        };
+        assert_eq!(matching("color:orange"), ["dark-orange", "orange"]);
+        assert_eq!(matching("color:#00f"), ["blue"]);
+        assert!(matching("color:green").is_empty());
+        assert_eq!(matching("color:mono"), ["dark-orange", "mono", "orange"]);
+        assert_eq!(matching("mono:no"), ["blue"]);
+        assert!(parse("color:#12").is_err());
+    }
+
+    #[test]
+    fn suggests_fields_then_values() {
+        let tags = vec!["navigation".to_string(), "nature walk".to_string()];
+        let fields: Vec<String> = suggest("arrow s", &tags, &[])
+            .into_iter()
+            .map(|s| s.query)
+            .collect();
+        assert_eq!(fields, ["arrow size:", "arrow stroke:"]);
+
+        let values: Vec<String> = suggest("x -tag:NA", &tags, &[])
+            .into_iter()
+            .map(|s| s.query)
+            .collect();
+        assert_eq!(values, ["x -tag:navigation", "x -tag:\"nature walk\""]);
+
+        assert!(suggest("", &tags, &[]).is_empty());
+        assert!(suggest("size:", &tags, &[]).is_empty());
+    }
+}
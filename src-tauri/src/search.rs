@@ -0,0 +1,88 @@
+// Turning user-typed search text into FTS5 queries.
+
+use crate::synonyms::Thesaurus;
+
+/// Splits free text into search terms.
+///
+/// Anything that isn't a letter or digit separates terms, which mirrors how
+/// the `unicode61` tokenizer splits the indexed text.
+pub fn terms(input: &str) -> Vec<String> {
+    input
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_lowercase())
+        .collect()
+}
+
+/// Builds an FTS5 `MATCH` expression where every term must be present as a
+/// prefix of some indexed word. Returns `None` when there is nothing to search.
+///
+/// Terms are always emitted as quoted strings, so user input can never be
+/// interpreted as FTS5 query syntax (`OR`, `NEAR`, column filters, ...).
+pub fn match_expression(input: &str) -> Option<String> {
+    match_expression_with(input, &Thesaurus::default())
+}
+
+/// Like `match_expression`, but each term may also match any of its
+/// synonyms: `trash` becomes `("trash"* OR "bin"* OR "корзина"* ...)`.
+pub fn match_expression_with(input: &str, thesaurus: &Thesaurus) -> Option<String> {
+    let terms = terms(input);
+    if terms.is_empty() {
+        return None;
+    }
+    Some(
+        terms
+            .iter()
+            .map(|term| {
+                let alternatives: Vec<String> = thesaurus
+                    .expand(term)
+                    .iter()
+                    .map(|word| self::terms(word).join(" "))
+                    .filter(|word| !word.is_empty())
+                    .map(|word| format!("\"{}\"*", word))
+                    .collect();
+                if alternatives.len() == 1 {
+                    alternatives[0].clone()
+                } else {
+                    format!("({})", alternatives.join(" OR "))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn terms_split_on_punctuation_and_lowercase() {
+        assert_eq!(terms("Arrow-Left_2"), ["arrow", "left", "2"]);
+        assert_eq!(terms("  Корзина!  "), ["корзина"]);
+        assert!(terms(" -_/ ").is_empty());
+    }
+
+    #[test]
+    fn match_expression_quotes_every_term() {
+        assert_eq!(
+            match_expression("arrow left").as_deref(),
+            Some("\"arrow\"* \"left\"*")
+        );
+        // FTS5 operators and column filters are searched for as plain words
+        assert_eq!(
+            match_expression("name:home OR NEAR").as_deref(),
+            Some("\"name\"* \"home\"* \"or\"* \"near\"*")
+        );
+        assert_eq!(match_expression("\"*\""), None);
+    }
+
+    #[test]
+    fn synonyms_become_alternatives() {
+        let thesaurus = Thesaurus::new([vec!["trash".to_string(), "Recycle-Bin".to_string()]]);
+        assert_eq!(
+            match_expression_with("trash can", &thesaurus).as_deref(),
+            Some("(\"trash\"* OR \"recycle bin\"*) \"can\"*")
+        );
+    }
+}
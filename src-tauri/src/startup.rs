@@ -0,0 +1,411 @@
+// Startup and recovery.
+//
+// Opening the library can fail because the file is corrupt, locked by
+// another process, read-only, or from a newer version of the app. Instead
+// of aborting, startup records what happened in `StartupState` and leaves
+// `DbState` empty; the UI then offers to retry, open read-only, restore a
+// backup or start fresh. Nothing is ever deleted: a library that gets
+// replaced is renamed to `iconbox-old-<time>.db` next to it.
+//
+// Scheduled backups also run from here, on a thread that checks every few
+// minutes whether the open library is due for one.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+use crate::backup;
+use crate::db::Library;
+use crate::error::{Error, ErrorCode, Result};
+use crate::{DbBackup, StartupStatus};
+
+/// The open library. `None` until startup succeeds, or after a failed
+/// reopen; commands then fail with "library is not open". Commands clone the
+/// `Arc` so a long operation does not block reopening.
+pub struct DbState(pub Mutex<Option<Arc<Library>>>);
+
+/// Outcome of the last attempt to open the library, for the startup screen.
+pub struct StartupState(pub Mutex<StartupStatus>);
+
+/// State of the backup schedule.
+pub struct BackupState {
+    /// Why the last scheduled backup failed; cleared by the next one that works.
+    last_error: Mutex<Option<Error>>,
+    /// Held while a scheduled backup runs, and by a restore or fresh start
+    /// for as long as it swaps the library file, so the two never overlap.
+    paused: Mutex<()>,
+}
+
+const DB_FILE: &str = "iconbox.db";
+
+/// How often the schedule is checked. Backups themselves are as far apart
+/// as the `backup_interval_hours` setting says.
+const BACKUP_CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// How long replacing the library waits for running commands to let go of
+/// it before giving up.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub fn get_app_data_dir(app: &AppHandle) -> Result<PathBuf> {
+    app.path().app_data_dir().map_err(|e| {
+        Error::new(
+            ErrorCode::Io,
+            format!("Cannot locate the app data folder: {}", e),
+        )
+    })
+}
+
+/// Registers the app state and opens the library. A failure is also kept in
+/// the startup status, where the UI picks it up and offers recovery.
+pub fn init_db(app: &AppHandle) -> Result<()> {
+    app.manage(DbState(Mutex::new(None)));
+    app.manage(StartupState(Mutex::new(StartupStatus::default())));
+    app.manage(BackupState {
+        last_error: Mutex::new(None),
+        paused: Mutex::new(()),
+    });
+    open_library(app, false, None)
+}
+
+/// Starts the thread that writes scheduled backups of whatever library is
+/// open. A failure is kept for the settings screen and retried next check.
+pub fn start_backup_schedule(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        let state = app.state::<BackupState>();
+        let paused = state.paused.lock().unwrap();
+        if let Ok(library) = library(&app) {
+            let result = library.backup_if_due();
+            *state.last_error.lock().unwrap() = result.err();
+        }
+        drop(paused);
+        std::thread::sleep(BACKUP_CHECK_INTERVAL);
+    });
+}
+
+/// Why the last scheduled backup failed, if it did.
+pub fn last_backup_error(app: &AppHandle) -> Option<Error> {
+    app.state::<BackupState>()
+        .last_error
+        .lock()
+        .unwrap()
+        .clone()
+}
+
+/// The open library, or a "not open" error.
+pub fn library(app: &AppHandle) -> Result<Arc<Library>> {
+    app.state::<DbState>()
+        .0
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or_else(|| Error::new(ErrorCode::NotOpen, "The icon library is not open"))
+}
+
+/// Closes the current library (if any) and opens it again, recording the
+/// outcome in the startup status. `set_aside` is where the previous file
+/// was moved, if the caller replaced it.
+fn open_library(app: &AppHandle, read_only: bool, set_aside: Option<PathBuf>) -> Result<()> {
+    let state = app.state::<DbState>();
+    let mut slot = state.0.lock().unwrap();
+    *slot = None;
+
+    let (db_path, result) = match get_app_data_dir(app) {
+        Ok(dir) => {
+            let db_path = dir.join(DB_FILE);
+            let result = std::fs::create_dir_all(&dir)
+                .map_err(|e| Error::io(&dir, "Cannot create", e))
+                .and_then(|_| {
+                    if read_only {
+                        Library::open_read_only(&db_path)
+                    } else {
+                        Library::open(&db_path)
+                    }
+                });
+            (db_path, result)
+        }
+        Err(e) => (PathBuf::new(), Err(e)),
+    };
+    let mut status = StartupStatus {
+        state: "ready".to_string(),
+        db_path: db_path.to_string_lossy().to_string(),
+        schema_version: Library::schema_version(&db_path),
+        supported_version: Library::supported_version(),
+        set_aside: set_aside.map(|p| p.to_string_lossy().to_string()),
+        ..Default::default()
+    };
+    let outcome = match result {
+        Ok(library) => {
+            if read_only {
+                status.state = "read_only".to_string();
+            }
+            status.migration_backup = library
+                .migration_backup()
+                .map(|p| p.to_string_lossy().to_string());
+            *slot = Some(Arc::new(library));
+            Ok(())
+        }
+        Err(e) => {
+            status.state = "failed".to_string();
+            status.error = Some(e.clone());
+            Err(e)
+        }
+    };
+    *app.state::<StartupState>().0.lock().unwrap() = status;
+    outcome
+}
+
+/// The startup status, with the backups currently available for restore.
+pub fn get_startup_status(app: &AppHandle) -> Result<StartupStatus> {
+    let mut status = app.state::<StartupState>().0.lock().unwrap().clone();
+    status.backups = list_backups(app)?;
+    Ok(status)
+}
+
+/// Library backups, newest first: those in `backups/` next to the library,
+/// plus those in the chosen backup folder while the library is open (its
+/// settings are not readable otherwise).
+fn list_backups(app: &AppHandle) -> Result<Vec<DbBackup>> {
+    let mut backups = backup::list(&get_app_data_dir(app)?.join("backups"));
+    if let Some(dir) = library(app)
+        .ok()
+        .and_then(|l| l.backup_dir().ok().flatten())
+    {
+        for found in backup::list(&dir) {
+            if !backups.iter().any(|b| b.path == found.path) {
+                backups.push(found);
+            }
+        }
+    }
+    backups.sort_by(|a, b| b.modified.cmp(&a.modified));
+    Ok(backups)
+}
+
+/// Tries to open the library normally again, e.g. once another process has
+/// released its lock.
+pub fn retry_open_library(app: &AppHandle) -> Result<StartupStatus> {
+    let _ = open_library(app, false, None);
+    get_startup_status(app)
+}
+
+/// Opens the library without writing to it, so its icons can be browsed and
+/// exported even if it cannot be upgraded or written.
+pub fn open_library_read_only(app: &AppHandle) -> Result<StartupStatus> {
+    let _ = open_library(app, true, None);
+    get_startup_status(app)
+}
+
+/// Replaces the library with a backup. The backup is copied next to the
+/// library and the copy checked before the live file is touched, so a bad
+/// backup or a failed copy leaves the library as it was. The open library is
+/// closed first, with scheduled backups held off until the new one is open.
+/// The current file is kept under a new name; if it cannot be moved, it is
+/// reopened as it was.
+pub fn restore_library_backup(app: &AppHandle, backup_path: &str) -> Result<StartupStatus> {
+    let backup_path = PathBuf::from(backup_path);
+    Library::verify(&backup_path)?;
+
+    let db_path = get_app_data_dir(app)?.join(DB_FILE);
+    let staged = db_path.with_extension("db.restoring");
+    std::fs::copy(&backup_path, &staged).map_err(|e| Error::io(&backup_path, "Cannot copy", e))?;
+    if let Err(e) = Library::verify(&staged) {
+        let _ = std::fs::remove_file(&staged);
+        return Err(e.while_doing("The copied backup is not usable"));
+    }
+
+    let backups = app.state::<BackupState>();
+    let _paused = backups.paused.lock().unwrap();
+    if let Err(e) = close_library(app) {
+        let _ = std::fs::remove_file(&staged);
+        return Err(e);
+    }
+    let set_aside = match replace_library_file(&db_path, Some(&staged), &set_aside_path(&db_path)) {
+        Ok(set_aside) => set_aside,
+        Err(e) => {
+            // The current file is back in place; carry on with it
+            let _ = open_library(app, false, None);
+            return Err(e);
+        }
+    };
+    let _ = open_library(app, false, set_aside);
+    get_startup_status(app)
+}
+
+/// Starts with an empty library. The current file is kept under a new name,
+/// or reopened if it cannot be moved.
+pub fn start_fresh_library(app: &AppHandle) -> Result<StartupStatus> {
+    let db_path = get_app_data_dir(app)?.join(DB_FILE);
+    let backups = app.state::<BackupState>();
+    let _paused = backups.paused.lock().unwrap();
+    close_library(app)?;
+    let set_aside = match replace_library_file(&db_path, None, &set_aside_path(&db_path)) {
+        Ok(set_aside) => set_aside,
+        Err(e) => {
+            let _ = open_library(app, false, None);
+            return Err(e);
+        }
+    };
+    let _ = open_library(app, false, set_aside);
+    get_startup_status(app)
+}
+
+/// Closes the open library before its file is replaced. New commands fail
+/// with "not open" straight away; running ones hold their own `Arc`, so this
+/// waits for the last of them to finish. If they don't within
+/// `CLOSE_TIMEOUT` the library is put back and left open.
+fn close_library(app: &AppHandle) -> Result<()> {
+    let state = app.state::<DbState>();
+    let Some(mut library) = state.0.lock().unwrap().take() else {
+        return Ok(());
+    };
+    let started = Instant::now();
+    loop {
+        library = match Arc::try_unwrap(library) {
+            Ok(library) => {
+                drop(library);
+                return Ok(());
+            }
+            Err(library) => library,
+        };
+        if started.elapsed() >= CLOSE_TIMEOUT {
+            *state.0.lock().unwrap() = Some(library);
+            return Err(Error::new(
+                ErrorCode::Locked,
+                "The library is still busy; try again once the current operation finishes",
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+/// Where `set_aside_library` moves the library file: `iconbox-old-<time>.db`
+/// next to it.
+fn set_aside_path(db_path: &Path) -> PathBuf {
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    db_path.with_file_name(format!("iconbox-old-{}.db", stamp))
+}
+
+/// The library file and its WAL side files, which belong to it.
+fn library_files(db_path: &Path) -> impl Iterator<Item = PathBuf> + '_ {
+    ["", "-wal", "-shm"]
+        .into_iter()
+        .map(move |suffix| PathBuf::from(format!("{}{}", db_path.display(), suffix)))
+}
+
+/// Renames the library file and its side files to `target`. Returns the new
+/// path, or `None` if there was no file. If one of them cannot be moved, the
+/// ones already moved are put back.
+fn set_aside_library(db_path: &Path, target: &Path) -> Result<Option<PathBuf>> {
+    if !db_path.exists() {
+        return Ok(None);
+    }
+    let mut moved = Vec::new();
+    for (from, to) in library_files(db_path).zip(library_files(target)) {
+        if !from.exists() {
+            continue;
+        }
+        if let Err(e) = std::fs::rename(&from, &to) {
+            for (from, to) in moved.into_iter().rev() {
+                let _ = std::fs::rename(to, from);
+            }
+            return Err(Error::io(&from, "Cannot move aside", e));
+        }
+        moved.push((from, to));
+    }
+    Ok(Some(target.to_path_buf()))
+}
+
+/// Sets the library file aside as `aside` and moves `staged` into its place,
+/// or leaves no file for a fresh start. Returns where the old file went. If
+/// a step fails, the old file is put back and `staged` is removed, so the
+/// caller can reopen the library as it was.
+fn replace_library_file(
+    db_path: &Path,
+    staged: Option<&Path>,
+    aside: &Path,
+) -> Result<Option<PathBuf>> {
+    let set_aside = match set_aside_library(db_path, aside) {
+        Ok(set_aside) => set_aside,
+        Err(e) => {
+            if let Some(staged) = staged {
+                let _ = std::fs::remove_file(staged);
+            }
+            return Err(e);
+        }
+    };
+    let Some(staged) = staged else {
+        return Ok(set_aside);
+    };
+    if let Err(e) = std::fs::rename(staged, db_path) {
+        let _ = std::fs::remove_file(staged);
+        if let Some(set_aside) = &set_aside {
+            for (from, to) in library_files(set_aside).zip(library_files(db_path)) {
+                if from.exists() {
+                    let _ = std::fs::rename(from, to);
+                }
+            }
+        }
+        return Err(Error::io(staged, "Cannot move", e));
+    }
+    Ok(set_aside)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_aside_moves_the_library_and_its_wal_files() {
+        let dir = std::env::temp_dir().join("iconbox-startup-set-aside");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join(DB_FILE);
+        let aside = set_aside_path(&db_path);
+        assert_eq!(set_aside_library(&db_path, &aside).unwrap(), None);
+
+        std::fs::write(&db_path, "db").unwrap();
+        std::fs::write(dir.join(format!("{}-wal", DB_FILE)), "wal").unwrap();
+        let target = set_aside_library(&db_path, &aside).unwrap().unwrap();
+        assert_eq!(target, aside);
+        assert!(!db_path.exists());
+        assert!(target.exists());
+        assert!(PathBuf::from(format!("{}-wal", target.display())).exists());
+        assert!(!PathBuf::from(format!("{}-shm", target.display())).exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn a_failed_swap_leaves_the_library_in_place() {
+        let dir = std::env::temp_dir().join("iconbox-startup-failed-swap");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join(DB_FILE);
+        let wal = dir.join(format!("{}-wal", DB_FILE));
+        std::fs::write(&db_path, "db").unwrap();
+        std::fs::write(&wal, "wal").unwrap();
+        let staged = dir.join("iconbox.db.restoring");
+        std::fs::write(&staged, "backup").unwrap();
+
+        // A non-empty folder where the WAL file should go stops the set-aside
+        // after the main file has already moved
+        let aside = dir.join("iconbox-old.db");
+        std::fs::create_dir_all(dir.join("iconbox-old.db-wal/blocked")).unwrap();
+        let err = replace_library_file(&db_path, Some(&staged), &aside).unwrap_err();
+        assert_eq!(err.context.path.as_deref(), wal.to_str());
+        assert_eq!(std::fs::read_to_string(&db_path).unwrap(), "db");
+        assert_eq!(std::fs::read_to_string(&wal).unwrap(), "wal");
+        assert!(!aside.exists());
+        assert!(!staged.exists());
+
+        // Without the obstacle the backup takes the library's place
+        std::fs::remove_dir_all(dir.join("iconbox-old.db-wal")).unwrap();
+        std::fs::write(&staged, "backup").unwrap();
+        let set_aside = replace_library_file(&db_path, Some(&staged), &aside).unwrap();
+        assert_eq!(set_aside.as_deref(), Some(aside.as_path()));
+        assert_eq!(std::fs::read_to_string(&db_path).unwrap(), "backup");
+        assert_eq!(std::fs::read_to_string(&aside).unwrap(), "db");
+        assert!(!staged.exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
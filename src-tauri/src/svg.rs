@@ -0,0 +1,135 @@
+// Lightweight SVG inspection helpers.
+//
+// These work on the raw markup with plain string scanning: icon files are
+// small and we only need a handful of well-known elements, so pulling in a
+// full XML parser isn't worth it.
+
+/// Human-readable text embedded in an SVG document.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SvgText {
+    pub title: String,
+    pub description: String,
+}
+
+pub fn extract_text(svg: &str) -> SvgText {
+    SvgText {
+        title: element_text(svg, "title").unwrap_or_default(),
+        description: element_text(svg, "desc").unwrap_or_default(),
+    }
+}
+
+/// Returns the name (without the `data-` prefix) and decoded value of every
+/// `data-*` attribute in the document, in order.
+pub fn data_attributes(svg: &str) -> Vec<(String, String)> {
+    let mut out = Vec::new();
+    let mut from = 0;
+    while let Some(pos) = svg[from..].find("data-") {
+        let start = from + pos;
+        from = start + "data-".len();
+
+        // Attribute names follow whitespace inside a tag
+        if !svg[..start].ends_with(char::is_whitespace) {
+            continue;
+        }
+        let rest = &svg[from..];
+        let name_len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+            .unwrap_or(rest.len());
+        let name = &rest[..name_len];
+        let Some(value) = rest[name_len..].trim_start().strip_prefix('=') else {
+            continue;
+        };
+        let value = value.trim_start();
+        let Some(quote) = value.chars().next().filter(|&c| c == '"' || c == '\'') else {
+            continue;
+        };
+        let Some(end) = value[1..].find(quote) else {
+            continue;
+        };
+        if !name.is_empty() {
+            out.push((name.to_string(), decode_entities(&value[1..1 + end])));
+        }
+    }
+    out
+}
+
+/// Returns the text content of the first `<tag>` element, with markup
+/// stripped and entities decoded.
+fn element_text(svg: &str, tag: &str) -> Option<String> {
+    let open = format!("<{}", tag);
+    let close = format!("</{}>", tag);
+
+    let mut from = 0;
+    while let Some(pos) = svg[from..].find(&open) {
+        let start = from + pos + open.len();
+        from = start;
+
+        // Make sure we matched `<title>` / `<title ...>` and not `<titlebar>`
+        match svg[start..].chars().next() {
+            Some(c) if c == '>' || c.is_whitespace() => {}
+            Some('/') => return None,
+            _ => continue,
+        }
+
+        let body_start = start + svg[start..].find('>')? + 1;
+        let body_end = body_start + svg[body_start..].find(&close)?;
+        let text = decode_entities(&strip_tags(&svg[body_start..body_end]));
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        return if text.is_empty() { None } else { Some(text) };
+    }
+    None
+}
+
+fn strip_tags(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut in_tag = false;
+    for c in s.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                out.push(' ');
+            }
+            _ if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out
+}
+
+fn decode_entities(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_title_and_description() {
+        let svg = r#"<svg><title id="t">Home &amp; garden</title><desc>
+            A <tspan>small</tspan>   house</desc></svg>"#;
+        assert_eq!(
+            extract_text(svg),
+            SvgText {
+                title: "Home & garden".into(),
+                description: "A small house".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn ignores_lookalike_empty_and_unclosed_elements() {
+        assert_eq!(
+            element_text("<titlebar>x</titlebar><title>y</title>", "title").as_deref(),
+            Some("y")
+        );
+        assert_eq!(element_text("<title/><title>y</title>", "title"), None);
+        assert_eq!(element_text("<title>  </title>", "title"), None);
+        assert_eq!(element_text("<title>never closed", "title"), None);
+    }
+}
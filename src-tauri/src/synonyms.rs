@@ -0,0 +1,172 @@
+// Synonym dictionary used to expand search terms.
+//
+// Groups of equivalent words live in the `synonym_groups`/`synonyms` tables
+// so users can edit them; `DEFAULT_GROUPS` seeds a fresh database with a
+// basic English/Russian set. A word belongs to at most one default group, so
+// ambiguous words (`корзина` is both a trash bin and a shopping cart) only
+// expand to their most common meaning.
+
+use std::collections::HashMap;
+
+#[rustfmt::skip]
+pub const DEFAULT_GROUPS: &[&[&str]] = &[
+    &["trash", "bin", "delete", "remove", "garbage", "корзина", "удалить", "мусор"],
+    &["search", "find", "magnifier", "lookup", "поиск", "найти", "лупа"],
+    &["settings", "gear", "cog", "preferences", "options", "настройки", "шестеренка"],
+    &["home", "house", "main", "дом", "главная"],
+    &["user", "person", "profile", "account", "avatar", "пользователь", "профиль", "аккаунт"],
+    &["users", "people", "group", "team", "пользователи", "люди", "группа", "команда"],
+    &["close", "cross", "exit", "cancel", "закрыть", "крестик", "отмена"],
+    &["add", "plus", "new", "create", "добавить", "плюс", "создать"],
+    &["minus", "subtract", "минус"],
+    &["edit", "pencil", "pen", "write", "редактировать", "карандаш", "ручка"],
+    &["save", "floppy", "disk", "сохранить", "дискета"],
+    &["download", "скачать", "загрузка"],
+    &["upload", "выгрузить", "отправить"],
+    &["mail", "email", "envelope", "letter", "почта", "письмо", "конверт"],
+    &["message", "chat", "comment", "bubble", "сообщение", "чат", "комментарий"],
+    &["phone", "call", "telephone", "телефон", "звонок"],
+    &["heart", "love", "like", "сердце", "лайк"],
+    &["star", "favorite", "rating", "звезда", "избранное", "рейтинг"],
+    &["lock", "padlock", "secure", "security", "замок", "безопасность"],
+    &["unlock", "open", "открыть", "разблокировать"],
+    &["key", "password", "ключ", "пароль"],
+    &["calendar", "date", "schedule", "календарь", "дата", "расписание"],
+    &["clock", "time", "watch", "часы", "время"],
+    &["image", "picture", "photo", "картинка", "изображение", "фото"],
+    &["camera", "камера", "фотоаппарат"],
+    &["video", "film", "movie", "видео", "фильм"],
+    &["music", "audio", "sound", "музыка", "звук"],
+    &["play", "start", "воспроизвести", "старт"],
+    &["pause", "пауза"],
+    &["stop", "стоп"],
+    &["arrow", "стрелка"],
+    &["left", "back", "previous", "влево", "лево", "назад"],
+    &["right", "forward", "next", "вправо", "право", "вперед"],
+    &["up", "вверх"],
+    &["down", "вниз"],
+    &["chart", "graph", "diagram", "statistics", "график", "диаграмма", "статистика"],
+    &["cart", "basket", "shopping", "shop", "покупки", "магазин"],
+    &["money", "cash", "payment", "coin", "деньги", "оплата", "монета"],
+    &["wallet", "purse", "кошелек"],
+    &["card", "credit", "кредитка"],
+    &["info", "information", "help", "информация", "справка", "помощь"],
+    &["warning", "alert", "caution", "предупреждение", "внимание"],
+    &["error", "danger", "ошибка", "опасность"],
+    &["check", "ok", "done", "success", "tick", "галочка", "готово", "успех"],
+    &["bell", "notification", "колокольчик", "уведомление"],
+    &["menu", "hamburger", "меню"],
+    &["share", "поделиться"],
+    &["copy", "duplicate", "clone", "копировать", "копия"],
+    &["folder", "directory", "папка"],
+    &["file", "document", "файл", "документ"],
+    &["cloud", "облако"],
+    &["link", "chain", "url", "ссылка", "цепь"],
+    &["map", "location", "pin", "marker", "карта", "место", "метка"],
+    &["refresh", "reload", "sync", "обновить", "синхронизация"],
+    &["filter", "фильтр"],
+    &["print", "printer", "печать", "принтер"],
+    &["eye", "view", "visible", "show", "глаз", "просмотр", "показать"],
+    &["hide", "hidden", "invisible", "скрыть"],
+    &["sun", "light", "day", "солнце", "светлый", "день"],
+    &["moon", "dark", "night", "луна", "темный", "ночь"],
+    &["globe", "world", "earth", "language", "глобус", "мир", "язык"],
+    &["logout", "signout", "выход", "выйти"],
+    &["login", "signin", "вход", "войти"],
+];
+
+/// Lookup table from a word to the other words in its synonym groups.
+#[derive(Debug, Default)]
+pub struct Thesaurus {
+    groups: Vec<Vec<String>>,
+    by_term: HashMap<String, Vec<usize>>,
+}
+
+/// Normalizes a dictionary word for comparison (`Ёлка` and `елка` are equal).
+pub fn normalize(term: &str) -> String {
+    term.trim().to_lowercase().replace('ё', "е")
+}
+
+impl Thesaurus {
+    pub fn new<I>(groups: I) -> Self
+    where
+        I: IntoIterator<Item = Vec<String>>,
+    {
+        let mut thesaurus = Thesaurus::default();
+        for group in groups {
+            let index = thesaurus.groups.len();
+            for term in &group {
+                thesaurus
+                    .by_term
+                    .entry(normalize(term))
+                    .or_default()
+                    .push(index);
+            }
+            thesaurus.groups.push(group);
+        }
+        thesaurus
+    }
+
+    /// Returns `term` followed by all of its synonyms, without duplicates.
+    pub fn expand(&self, term: &str) -> Vec<String> {
+        let mut out = vec![term.to_string()];
+        let mut seen = vec![normalize(term)];
+
+        for &group in self.by_term.get(&normalize(term)).into_iter().flatten() {
+            for synonym in &self.groups[group] {
+                let key = normalize(synonym);
+                if !seen.contains(&key) {
+                    seen.push(key);
+                    out.push(synonym.clone());
+                }
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thesaurus(groups: &[&[&str]]) -> Thesaurus {
+        Thesaurus::new(
+            groups
+                .iter()
+                .map(|g| g.iter().map(|t| t.to_string()).collect()),
+        )
+    }
+
+    #[test]
+    fn expands_to_the_whole_group_case_insensitively() {
+        let t = thesaurus(&[&["trash", "bin", "Корзина"], &["home", "house"]]);
+        assert_eq!(t.expand("BIN"), ["BIN", "trash", "Корзина"]);
+        assert_eq!(t.expand("корзина"), ["корзина", "trash", "bin"]);
+        assert_eq!(t.expand("garden"), ["garden"]);
+    }
+
+    #[test]
+    fn yo_and_ye_are_the_same_letter() {
+        assert_eq!(normalize(" Ёлка "), "елка");
+        let t = thesaurus(&[&["tree", "ёлка"]]);
+        assert_eq!(t.expand("елка"), ["елка", "tree"]);
+    }
+
+    #[test]
+    fn words_in_several_groups_expand_to_all_of_them_once() {
+        let t = thesaurus(&[&["open", "unlock"], &["open", "show"]]);
+        assert_eq!(t.expand("open"), ["open", "unlock", "show"]);
+    }
+
+    #[test]
+    fn default_groups_do_not_share_words() {
+        let mut seen = HashMap::new();
+        for (i, group) in DEFAULT_GROUPS.iter().enumerate() {
+            for term in *group {
+                if let Some(other) = seen.insert(normalize(term), i) {
+                    assert_eq!(other, i, "'{}' is in two default groups", term);
+                }
+            }
+        }
+    }
+}
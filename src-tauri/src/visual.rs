@@ -0,0 +1,218 @@
+// Visual fingerprints for "find icons that look like this".
+//
+// Each icon is rasterized into a small coverage map (alpha only, so color
+// doesn't matter) after cropping to its drawn content, which makes the
+// comparison independent of padding and viewBox size. Two descriptors are
+// kept per icon:
+//
+// - a 64-bit DCT perceptual hash, compared by Hamming distance, which
+//   captures the overall structure;
+// - a 16×16 coverage thumbnail, compared pixel by pixel, which separates
+//   icons whose hashes collide but whose shapes differ in detail.
+
+use resvg::{tiny_skia, usvg};
+
+const RASTER_SIZE: usize = 32;
+pub const SHAPE_SIZE: usize = 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fingerprint {
+    pub phash: u64,
+    /// `SHAPE_SIZE`×`SHAPE_SIZE` coverage values, row-major.
+    pub shape: Vec<u8>,
+}
+
+pub fn fingerprint(svg: &str) -> Option<Fingerprint> {
+    let pixels = rasterize(svg, RASTER_SIZE)?;
+    Some(Fingerprint {
+        phash: phash(&pixels),
+        shape: downsample(&pixels, RASTER_SIZE, SHAPE_SIZE),
+    })
+}
+
+/// Visual distance between two fingerprints: 0 for identical, 1 for
+/// completely different.
+pub fn distance(a: &Fingerprint, b: &Fingerprint) -> f64 {
+    let hash = (a.phash ^ b.phash).count_ones() as f64 / 64.0;
+    let shape = if a.shape.len() == b.shape.len() && !a.shape.is_empty() {
+        let diff: u64 = a
+            .shape
+            .iter()
+            .zip(&b.shape)
+            .map(|(x, y)| x.abs_diff(*y) as u64)
+            .sum();
+        diff as f64 / (255.0 * a.shape.len() as f64)
+    } else {
+        1.0
+    };
+    0.5 * hash + 0.5 * shape
+}
+
+/// Renders the SVG's content, cropped and centered, into a `size`×`size`
+/// coverage map with values in 0..=1. Returns `None` for unparseable or
+/// empty documents.
+fn rasterize(svg: &str, size: usize) -> Option<Vec<f32>> {
+    let tree = usvg::Tree::from_str(svg, &usvg::Options::default()).ok()?;
+
+    let bbox = tree.root().abs_stroke_bounding_box();
+    let (x, y, w, h) = if bbox.width() > 0.0 && bbox.height() > 0.0 {
+        (bbox.x(), bbox.y(), bbox.width(), bbox.height())
+    } else {
+        let s = tree.size();
+        (0.0, 0.0, s.width(), s.height())
+    };
+
+    // One pixel of margin so antialiased edges aren't clipped
+    let scale = (size as f32 - 2.0) / w.max(h);
+    let tx = (size as f32 - w * scale) / 2.0 - x * scale;
+    let ty = (size as f32 - h * scale) / 2.0 - y * scale;
+
+    let mut pixmap = tiny_skia::Pixmap::new(size as u32, size as u32)?;
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::from_row(scale, 0.0, 0.0, scale, tx, ty),
+        &mut pixmap.as_mut(),
+    );
+
+    let pixels: Vec<f32> = pixmap
+        .pixels()
+        .iter()
+        .map(|p| p.alpha() as f32 / 255.0)
+        .collect();
+    if pixels.iter().all(|&p| p == 0.0) {
+        return None;
+    }
+    Some(pixels)
+}
+
+/// Classic pHash: 2D DCT of the coverage map, then one bit per low
+/// frequency coefficient (top-left 8×8, skipping DC) depending on whether it
+/// is above the median.
+fn phash(pixels: &[f32]) -> u64 {
+    let n = RASTER_SIZE;
+    let cos: Vec<f32> = (0..n * n)
+        .map(|i| {
+            let (k, x) = (i / n, i % n);
+            (std::f32::consts::PI / n as f32 * (x as f32 + 0.5) * k as f32).cos()
+        })
+        .collect();
+
+    // Separable DCT: rows first, then columns (only the 8 lowest needed)
+    let mut rows = vec![0.0f32; n * 8];
+    for y in 0..n {
+        for u in 0..8 {
+            rows[y * 8 + u] = (0..n).map(|x| pixels[y * n + x] * cos[u * n + x]).sum();
+        }
+    }
+    let mut coeffs = Vec::with_capacity(64);
+    for v in 0..8 {
+        for u in 0..8 {
+            coeffs.push(
+                (0..n)
+                    .map(|y| rows[y * 8 + u] * cos[v * n + y])
+                    .sum::<f32>(),
+            );
+        }
+    }
+
+    let ac = &coeffs[1..];
+    let mut sorted = ac.to_vec();
+    sorted.sort_by(f32::total_cmp);
+    let median = sorted[sorted.len() / 2];
+
+    ac.iter()
+        .enumerate()
+        .filter(|(_, &c)| c > median)
+        .fold(0u64, |hash, (i, _)| hash | (1 << i))
+}
+
+/// Box-filters a `from`×`from` coverage map down to `to`×`to` bytes.
+fn downsample(pixels: &[f32], from: usize, to: usize) -> Vec<u8> {
+    let factor = from / to;
+    let mut out = Vec::with_capacity(to * to);
+    for ty in 0..to {
+        for tx in 0..to {
+            let mut sum = 0.0;
+            for y in ty * factor..(ty + 1) * factor {
+                for x in tx * factor..(tx + 1) * factor {
+                    sum += pixels[y * from + x];
+                }
+            }
+            let avg = sum / (factor * factor) as f32;
+            out.push((avg * 255.0).round() as u8);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SQUARE: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24"><rect x="4" y="4" width="16" height="16"/></svg>"#;
+    const CIRCLE: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24"><circle cx="12" cy="12" r="8"/></svg>"#;
+    const BAR: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24"><rect x="2" y="10" width="20" height="4"/></svg>"#;
+
+    #[test]
+    fn identical_artwork_has_zero_distance() {
+        let a = fingerprint(SQUARE).unwrap();
+        assert_eq!(distance(&a, &a), 0.0);
+    }
+
+    #[test]
+    fn padding_scale_and_color_do_not_matter() {
+        let a = fingerprint(SQUARE).unwrap();
+        let b = fingerprint(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100"><rect x="40" y="40" width="20" height="20" fill="red"/></svg>"#,
+        )
+        .unwrap();
+        assert!(distance(&a, &b) < 0.05, "{}", distance(&a, &b));
+    }
+
+    #[test]
+    fn different_shapes_are_further_apart() {
+        let square = fingerprint(SQUARE).unwrap();
+        let circle = fingerprint(CIRCLE).unwrap();
+        let bar = fingerprint(BAR).unwrap();
+        assert!(distance(&square, &circle) < distance(&square, &bar));
+        assert!(distance(&square, &bar) <= 1.0);
+    }
+
+    #[test]
+    fn unreadable_or_empty_svgs_have_no_fingerprint() {
+        assert_eq!(fingerprint("not svg"), None);
+        assert_eq!(
+            fingerprint(r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24"/>"#),
+            None
+        );
+    }
+
+    #[test]
+    fn mirrored_maps_differ_in_the_first_horizontal_frequency() {
+        let left: Vec<f32> = (0..RASTER_SIZE * RASTER_SIZE)
+            .map(|i| {
+                if i % RASTER_SIZE < RASTER_SIZE / 2 {
+                    1.0
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        let right: Vec<f32> = left.iter().map(|p| 1.0 - p).collect();
+        assert_ne!(phash(&left) & 1, phash(&right) & 1);
+    }
+
+    #[test]
+    fn mismatched_shapes_count_as_fully_different() {
+        let a = Fingerprint {
+            phash: 0,
+            shape: vec![0; 4],
+        };
+        let b = Fingerprint {
+            phash: 0,
+            shape: vec![0; 9],
+        };
+        assert_eq!(distance(&a, &b), 0.5);
+        assert_eq!(downsample(&[1.0, 0.0, 0.0, 1.0], 2, 1), [128]);
+    }
+}
@@ -0,0 +1,1758 @@
+// Integration tests for the storage layer, run against `Library` directly:
+// an in-memory library for most operations, a temporary folder where the
+// file on disk matters (reopening, migrations, backups, imports).
+
+use std::path::PathBuf;
+
+use iconbox_lib::error::ErrorCode;
+use iconbox_lib::{Collection, Icon, Library};
+
+fn library() -> Library {
+    Library::open(":memory:").unwrap()
+}
+
+/// An empty folder under the system temp dir, unique to the test.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("iconbox-test-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn add_icon(lib: &Library, collection_id: &str, name: &str, svg: &str, tags: &[&str]) -> Icon {
+    let icon = Icon {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: name.to_string(),
+        path: format!("/pack/{}.svg", name),
+        svg_content: svg.to_string(),
+        tags: tags.iter().map(|t| t.to_string()).collect(),
+        collection_id: collection_id.to_string(),
+        created_at: "2024-05-01T12:00:00+00:00".to_string(),
+        file_size: svg.len() as i64,
+        favorite: false,
+    };
+    lib.insert_icon(&icon).unwrap();
+    icon
+}
+
+fn collection(lib: &Library, id: &str) -> Collection {
+    lib.get_all_collections()
+        .unwrap()
+        .into_iter()
+        .find(|c| c.id == id)
+        .unwrap()
+}
+
+fn icon(lib: &Library, id: &str) -> Icon {
+    lib.get_all_icons()
+        .unwrap()
+        .into_iter()
+        .find(|i| i.id == id)
+        .unwrap()
+}
+
+fn count(lib: &Library, query: &str) -> i64 {
+    lib.search_icons(query, None, false, false, 100, 0)
+        .unwrap()
+        .total
+}
+
+fn names(lib: &Library, query: &str) -> Vec<String> {
+    let mut names: Vec<String> = lib
+        .search_icons(query, None, false, false, 100, 0)
+        .unwrap()
+        .icons
+        .into_iter()
+        .map(|i| i.name)
+        .collect();
+    names.sort();
+    names
+}
+
+// ── Opening ──────────────────────────────────────────────────
+
+#[test]
+fn in_memory_library_starts_empty() {
+    let lib = library();
+    assert!(lib.get_all_collections().unwrap().is_empty());
+    assert_eq!(lib.get_total_icon_count().unwrap(), 0);
+    assert!(lib.migration_backup().is_none());
+}
+
+#[test]
+fn file_library_keeps_data_across_reopen() {
+    let dir = temp_dir("reopen");
+    let path = dir.join("iconbox.db");
+    let id = {
+        let lib = Library::open(&path).unwrap();
+        let c = lib.create_collection("Pack", None).unwrap();
+        add_icon(&lib, &c.id, "arrow", "<svg/>", &["nav"]);
+        c.id
+    };
+
+    let lib = Library::open(&path).unwrap();
+    assert_eq!(collection(&lib, &id).icon_count, 1);
+    assert_eq!(count(&lib, "tag:nav"), 1);
+    assert_eq!(
+        Library::schema_version(&path),
+        Some(Library::supported_version())
+    );
+    // Nothing to upgrade, so no backup
+    assert!(lib.migration_backup().is_none());
+    assert!(!dir.join("backups").exists());
+}
+
+#[test]
+fn upgrading_an_old_library_takes_a_backup() {
+    let dir = temp_dir("upgrade");
+    let path = dir.join("iconbox.db");
+    {
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE collections (id TEXT PRIMARY KEY, name TEXT NOT NULL, parent_id TEXT,
+                 icon_count INTEGER DEFAULT 0, color TEXT, created_at TEXT NOT NULL);
+             CREATE TABLE icons (id TEXT PRIMARY KEY, name TEXT NOT NULL, path TEXT,
+                 svg_content TEXT NOT NULL, tags TEXT DEFAULT '', collection_id TEXT NOT NULL,
+                 created_at TEXT NOT NULL, file_size INTEGER DEFAULT 0, favorite INTEGER DEFAULT 0);
+             INSERT INTO collections VALUES ('c', 'Pack', NULL, 2, '#fff', 'x');
+             INSERT INTO icons VALUES ('a', 'arrow', 'p', '<svg/>', 'nav, Arrows,nav', 'c', 'x', 1, 0);
+             INSERT INTO icons VALUES ('b', 'home', 'p', '<svg/>', '', 'c', 'x', 1, 0);",
+        )
+        .unwrap();
+    }
+
+    let lib = Library::open(&path).unwrap();
+    let backup = lib.migration_backup().unwrap();
+    assert!(backup.starts_with(dir.join("backups")));
+    assert_eq!(Library::schema_version(backup), Some(0));
+    Library::verify(backup).unwrap();
+
+    assert_eq!(icon(&lib, "a").tags, vec!["nav", "Arrows"]);
+    assert!(icon(&lib, "b").tags.is_empty());
+    assert_eq!(count(&lib, "arrows"), 1);
+    assert_eq!(lib.get_icon_revisions("a").unwrap()[0].reason, "import");
+}
+
+#[test]
+fn library_from_a_newer_version_is_refused() {
+    let dir = temp_dir("too-new");
+    let path = dir.join("iconbox.db");
+    drop(Library::open(&path).unwrap());
+    {
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.pragma_update(None, "user_version", 99).unwrap();
+    }
+
+    let err = Library::open(&path).err().unwrap();
+    assert_eq!(err.code, ErrorCode::TooNew);
+    assert_eq!(
+        Library::open_read_only(&path).err().unwrap().code,
+        ErrorCode::TooNew
+    );
+    assert_eq!(Library::verify(&path).unwrap_err().code, ErrorCode::TooNew);
+}
+
+#[test]
+fn damaged_file_is_reported_as_corrupt() {
+    let dir = temp_dir("corrupt");
+    let path = dir.join("iconbox.db");
+    std::fs::write(&path, vec![7u8; 8192]).unwrap();
+
+    assert_eq!(Library::open(&path).err().unwrap().code, ErrorCode::Corrupt);
+    assert!(Library::verify(&path).is_err());
+}
+
+#[test]
+fn read_only_library_refuses_edits() {
+    let dir = temp_dir("read-only");
+    let path = dir.join("iconbox.db");
+    {
+        let lib = Library::open(&path).unwrap();
+        lib.create_collection("Pack", None).unwrap();
+    }
+
+    let lib = Library::open_read_only(&path).unwrap();
+    assert_eq!(lib.get_all_collections().unwrap().len(), 1);
+    let err = lib.create_collection("Other", None).unwrap_err();
+    assert_eq!(err.code, ErrorCode::ReadOnly);
+}
+
+// ── Collections ──────────────────────────────────────────────
+
+#[test]
+fn collection_create_rename_delete() {
+    let lib = library();
+    let pack = lib.create_collection("Pack", None).unwrap();
+    let child = lib.create_collection("Child", Some(&pack.id)).unwrap();
+    add_icon(&lib, &child.id, "arrow", "<svg/>", &[]);
+
+    let all = lib.get_all_collections().unwrap();
+    assert_eq!(all.len(), 2);
+    assert_eq!(
+        collection(&lib, &child.id).parent_id.as_deref(),
+        Some(pack.id.as_str())
+    );
+    assert!(pack.color.starts_with('#'));
+
+    lib.rename_collection(&pack.id, "Renamed").unwrap();
+    assert_eq!(collection(&lib, &pack.id).name, "Renamed");
+
+    // Deleting a collection removes its subcollections and their icons
+    lib.delete_collection(&pack.id).unwrap();
+    assert!(lib.get_all_collections().unwrap().is_empty());
+    assert_eq!(lib.get_total_icon_count().unwrap(), 0);
+}
+
+#[test]
+fn collection_move_and_reorder() {
+    let lib = library();
+    let a = lib.create_collection("A", None).unwrap();
+    let b = lib.create_collection("B", None).unwrap();
+    let c = lib.create_collection("C", None).unwrap();
+    let x = lib.create_collection("x", Some(&a.id)).unwrap();
+    let y = lib.create_collection("y", Some(&x.id)).unwrap();
+
+    // A collection cannot move into itself or its own subtree
+    assert_eq!(
+        lib.move_collection(&a.id, Some(&y.id)).unwrap_err().code,
+        ErrorCode::Conflict
+    );
+    assert!(lib.move_collection(&a.id, Some(&a.id)).is_err());
+
+    lib.move_collection(&y.id, None).unwrap();
+    assert_eq!(collection(&lib, &y.id).parent_id, None);
+    lib.move_collection(&y.id, Some(&b.id)).unwrap();
+    assert_eq!(
+        collection(&lib, &y.id).parent_id.as_deref(),
+        Some(b.id.as_str())
+    );
+
+    // Ordered siblings come first, the rest follow by name
+    lib.reorder_collections(None, &[c.id.clone(), a.id.clone()])
+        .unwrap();
+    let top: Vec<String> = lib
+        .get_all_collections()
+        .unwrap()
+        .into_iter()
+        .filter(|c| c.parent_id.is_none())
+        .map(|c| c.name)
+        .collect();
+    assert_eq!(top, vec!["C", "A", "B"]);
+}
+
+#[test]
+fn collection_merge() {
+    let lib = library();
+    let a = lib.create_collection("A", None).unwrap();
+    let b = lib.create_collection("B", None).unwrap();
+    let a_x = lib.create_collection("x", Some(&a.id)).unwrap();
+    let b_x = lib.create_collection("X", Some(&b.id)).unwrap();
+    let star = add_icon(&lib, &a.id, "star", "<svg>1</svg>", &["s"]);
+    add_icon(&lib, &b.id, "star", "<svg>1</svg>", &["t"]);
+    add_icon(&lib, &a.id, "Heart", "<svg>2</svg>", &[]);
+    add_icon(&lib, &b.id, "heart", "<svg>3</svg>", &[]);
+    add_icon(&lib, &a_x.id, "inner", "<svg/>", &[]);
+    lib.bulk_set_favorite(std::slice::from_ref(&star.id), true)
+        .unwrap();
+
+    // Not into its own subcollection
+    assert!(lib.merge_collections(&a.id, &a_x.id).is_err());
+
+    let result = lib.merge_collections(&a.id, &b.id).unwrap();
+    assert_eq!(result.icons_moved, 2);
+    assert_eq!(result.icons_renamed, 1);
+    assert_eq!(result.duplicates_merged, 1);
+    assert_eq!(result.collections_merged, 2);
+
+    let all = lib.get_all_collections().unwrap();
+    assert!(all.iter().all(|c| c.id != a.id && c.id != a_x.id));
+    let mut merged: Vec<Icon> = lib.get_icons_by_collection(&b.id).unwrap();
+    merged.sort_by(|x, y| x.name.cmp(&y.name));
+    let merged_names: Vec<&str> = merged.iter().map(|i| i.name.as_str()).collect();
+    assert_eq!(merged_names, vec!["Heart (2)", "heart", "star"]);
+    // The duplicate keeps the union of tags and favorites
+    let star = merged.iter().find(|i| i.name == "star").unwrap();
+    assert!(star.favorite);
+    assert_eq!(star.tags.len(), 2);
+    assert_eq!(collection(&lib, &b_x.id).icon_count, 1);
+}
+
+#[test]
+fn collection_counts_include_subcollections() {
+    let lib = library();
+    let root = lib.create_collection("Root", None).unwrap();
+    let s1 = lib.create_collection("S1", Some(&root.id)).unwrap();
+    let s2 = lib.create_collection("S2", Some(&s1.id)).unwrap();
+    let other = lib.create_collection("Other", None).unwrap();
+    add_icon(&lib, &root.id, "a", "<svg/>", &[]);
+    let b = add_icon(&lib, &s1.id, "b", "<svg/>", &[]);
+    add_icon(&lib, &s2.id, "c", "<svg/>", &[]);
+    add_icon(&lib, &s2.id, "d", "<svg/>", &[]);
+
+    let counts = || {
+        [&root, &s1, &s2, &other]
+            .iter()
+            .map(|c| {
+                let c = collection(&lib, &c.id);
+                (c.icon_count, c.total_icon_count)
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(counts(), vec![(1, 4), (1, 3), (2, 2), (0, 0)]);
+
+    lib.delete_icon(&b.id).unwrap();
+    assert_eq!(counts(), vec![(1, 3), (0, 2), (2, 2), (0, 0)]);
+
+    let ids: Vec<String> = lib
+        .get_icons_by_collection(&s2.id)
+        .unwrap()
+        .into_iter()
+        .map(|i| i.id)
+        .collect();
+    lib.bulk_move_icons(&ids, &other.id).unwrap();
+    assert_eq!(counts(), vec![(1, 1), (0, 0), (0, 0), (2, 2)]);
+
+    lib.move_collection(&other.id, Some(&s2.id)).unwrap();
+    assert_eq!(counts(), vec![(1, 3), (0, 2), (0, 2), (2, 2)]);
+}
+
+#[test]
+fn smart_collections_follow_their_query() {
+    let lib = library();
+    let pack = lib.create_collection("Pack", None).unwrap();
+    add_icon(&lib, &pack.id, "arrow-left", "<svg/>", &["nav"]);
+    let right = add_icon(&lib, &pack.id, "arrow-right", "<svg/>", &["nav"]);
+    add_icon(&lib, &pack.id, "home", "<svg/>", &[]);
+
+    let smart = lib.save_smart_collection(None, "Nav", "tag:nav").unwrap();
+    assert_eq!(smart.query.as_deref(), Some("tag:nav"));
+    assert_eq!(smart.icon_count, 2);
+
+    lib.update_icon_tags(&right.id, &[]).unwrap();
+    assert_eq!(collection(&lib, &smart.id).icon_count, 1);
+
+    let smart = lib
+        .save_smart_collection(Some(&smart.id), "Arrows", "arrow")
+        .unwrap();
+    assert_eq!((smart.name.as_str(), smart.icon_count), ("Arrows", 2));
+
+    // Smart collections match exactly, without the search bar's fuzzy fallback
+    let typo = lib.save_smart_collection(None, "Typo", "arow").unwrap();
+    assert_eq!(typo.icon_count, 0);
+    assert!(
+        lib.search_icons("arow", None, false, false, 10, 0)
+            .unwrap()
+            .fuzzy
+    );
+    let exact = lib.search_icons("arow", None, false, true, 10, 0).unwrap();
+    assert_eq!((exact.total, exact.fuzzy), (0, false));
+    lib.delete_collection(&typo.id).unwrap();
+
+    let err = lib
+        .save_smart_collection(None, "Bad", "shape:round")
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidQuery);
+    assert_eq!(err.context.position, Some(0));
+    assert_eq!(
+        lib.save_smart_collection(Some("missing"), "X", "x")
+            .unwrap_err()
+            .code,
+        ErrorCode::NotFound
+    );
+
+    lib.delete_collection(&smart.id).unwrap();
+    assert_eq!(lib.get_all_collections().unwrap().len(), 1);
+}
+
+// ── Icons ────────────────────────────────────────────────────
+
+#[test]
+fn icon_listing_favorites_and_counts() {
+    let lib = library();
+    let pack = lib.create_collection("Pack", None).unwrap();
+    let other = lib.create_collection("Other", None).unwrap();
+    let a = add_icon(&lib, &pack.id, "a", "<svg/>", &[]);
+    add_icon(&lib, &pack.id, "b", "<svg/>", &[]);
+    add_icon(&lib, &other.id, "c", "<svg/>", &[]);
+
+    assert_eq!(lib.get_icons_by_collection(&pack.id).unwrap().len(), 2);
+    assert_eq!(lib.get_all_icons().unwrap().len(), 3);
+    assert_eq!(lib.get_total_icon_count().unwrap(), 3);
+
+    assert!(lib.toggle_favorite(&a.id).unwrap());
+    assert_eq!(lib.get_favorite_count().unwrap(), 1);
+    assert_eq!(lib.get_favorite_icons().unwrap()[0].id, a.id);
+    assert!(!lib.toggle_favorite(&a.id).unwrap());
+    assert!(lib.get_favorite_icons().unwrap().is_empty());
+    assert_eq!(
+        lib.toggle_favorite("missing").unwrap_err().code,
+        ErrorCode::NotFound
+    );
+}
+
+#[test]
+fn icon_pages_and_svg_batches() {
+    let lib = library();
+    let pack = lib.create_collection("Pack", None).unwrap();
+    let other = lib.create_collection("Other", None).unwrap();
+    let mut ids = Vec::new();
+    for n in 0..25 {
+        let icon = add_icon(
+            &lib,
+            &pack.id,
+            &format!("icon-{:02}", n),
+            &format!("<svg>{}</svg>", n),
+            &["t"],
+        );
+        ids.push(icon.id);
+    }
+    add_icon(&lib, &other.id, "elsewhere", "<svg/>", &[]);
+
+    let mut listed = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let page = lib
+            .list_icons(Some(&pack.id), false, cursor.as_deref(), 10)
+            .unwrap();
+        assert_eq!(page.total, 25);
+        assert!(page.icons.len() <= 10);
+        listed.extend(page.icons.into_iter().map(|i| (i.name, i.tags)));
+        cursor = page.next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+    assert_eq!(listed.len(), 25);
+    assert_eq!(listed[0], ("icon-00".to_string(), vec!["t".to_string()]));
+    assert_eq!(listed[24].0, "icon-24");
+
+    // A cursor survives the icon it points at being deleted
+    let first = lib.list_icons(None, false, None, 2).unwrap();
+    assert_eq!(first.total, 26);
+    lib.delete_icon(&first.icons[1].id).unwrap();
+    let next = lib
+        .list_icons(None, false, first.next_cursor.as_deref(), 2)
+        .unwrap();
+    assert_eq!(next.icons[0].name, "icon-01");
+
+    lib.toggle_favorite(&ids[5]).unwrap();
+    let favorites = lib.list_icons(None, true, None, 10).unwrap();
+    assert_eq!((favorites.total, favorites.next_cursor), (1, None));
+
+    let err = lib
+        .list_icons(None, false, Some("garbage"), 10)
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidInput);
+
+    let wanted = vec![ids[3].clone(), "missing".to_string(), ids[1].clone()];
+    let svgs = lib.get_icon_svgs(&wanted).unwrap();
+    assert_eq!(svgs.len(), 2);
+    assert_eq!(
+        (svgs[0].id.as_str(), svgs[0].svg_content.as_str()),
+        (ids[3].as_str(), "<svg>3</svg>")
+    );
+    assert_eq!(svgs[1].svg_content, "<svg>1</svg>");
+}
+
+#[test]
+fn icon_move_copy_delete() {
+    let lib = library();
+    let c1 = lib.create_collection("One", None).unwrap();
+    let c2 = lib.create_collection("Two", None).unwrap();
+    let one = add_icon(&lib, &c1.id, "one", "<svg/>", &["x"]);
+    add_icon(&lib, &c1.id, "two", "<svg/>", &[]);
+
+    let moved = lib.move_icon(&one.id, &c2.id).unwrap();
+    assert_eq!(moved.collection_id, c2.id);
+    assert_eq!(collection(&lib, &c1.id).icon_count, 1);
+    assert_eq!(collection(&lib, &c2.id).icon_count, 1);
+
+    let copy = lib.copy_icon(&one.id, &c1.id).unwrap();
+    assert_ne!(copy.id, one.id);
+    assert_eq!(copy.collection_id, c1.id);
+    assert_eq!(copy.tags, vec!["x"]);
+    assert_eq!(collection(&lib, &c1.id).icon_count, 2);
+
+    lib.delete_icon(&copy.id).unwrap();
+    assert_eq!(collection(&lib, &c1.id).icon_count, 1);
+
+    assert_eq!(
+        lib.move_icon(&one.id, "missing").unwrap_err().code,
+        ErrorCode::NotFound
+    );
+    assert_eq!(
+        lib.copy_icon("missing", &c1.id).unwrap_err().code,
+        ErrorCode::NotFound
+    );
+}
+
+#[test]
+fn icon_tags_are_normalized() {
+    let lib = library();
+    let pack = lib.create_collection("Pack", None).unwrap();
+    let one = add_icon(&lib, &pack.id, "one", "<svg/>", &["a,b", "Nav"]);
+    let two = add_icon(&lib, &pack.id, "two", "<svg/>", &["nav", "nav ", "x"]);
+
+    // Tags are case-insensitive and shared; the first spelling wins
+    assert_eq!(icon(&lib, &two.id).tags, vec!["Nav", "x"]);
+    assert_eq!(icon(&lib, &one.id).tags, vec!["a,b", "Nav"]);
+
+    lib.update_icon_tags(&one.id, &[" style / outline ".to_string()])
+        .unwrap();
+    assert_eq!(icon(&lib, &one.id).tags, vec!["style/outline"]);
+}
+
+#[test]
+fn bulk_operations_report_what_changed() {
+    let lib = library();
+    let c1 = lib.create_collection("One", None).unwrap();
+    let c2 = lib.create_collection("Two", None).unwrap();
+    let i1 = add_icon(
+        &lib,
+        &c1.id,
+        "one",
+        "<svg><path fill=\"#f00\"/></svg>",
+        &["x"],
+    );
+    let i2 = add_icon(&lib, &c1.id, "two", "<svg/>", &[]);
+    let i3 = add_icon(&lib, &c1.id, "three", "<svg/>", &["y"]);
+    let ids = vec![
+        i1.id.clone(),
+        i2.id.clone(),
+        "nope".to_string(),
+        i1.id.clone(),
+    ];
+
+    let r = lib.bulk_add_tags(&ids, &["x".into(), "z".into()]).unwrap();
+    assert_eq!((r.processed, r.changed), (2, 2));
+    assert_eq!(r.missing, vec!["nope"]);
+
+    let r = lib.bulk_remove_tags(&ids, &["X".into()]).unwrap();
+    assert_eq!(r.changed, 2);
+
+    let r = lib
+        .bulk_set_favorite(&[i1.id.clone(), i3.id.clone()], true)
+        .unwrap();
+    assert_eq!(r.changed, 2);
+    let r = lib
+        .bulk_set_favorite(&[i1.id.clone(), i2.id.clone()], true)
+        .unwrap();
+    assert_eq!(r.changed, 1);
+
+    let r = lib
+        .bulk_move_icons(&[i1.id.clone(), i2.id.clone()], &c2.id)
+        .unwrap();
+    assert_eq!(r.changed, 2);
+    assert_eq!(collection(&lib, &c1.id).icon_count, 1);
+    assert_eq!(collection(&lib, &c2.id).icon_count, 2);
+    assert!(lib
+        .bulk_move_icons(std::slice::from_ref(&i1.id), "missing")
+        .is_err());
+
+    let r = lib
+        .bulk_copy_icons(&[i1.id.clone(), i3.id.clone()], &c1.id)
+        .unwrap();
+    assert_eq!(r.created_ids.len(), 2);
+    let copy = icon(&lib, &r.created_ids[0]);
+    assert_eq!(copy.name, "one");
+    assert_eq!(copy.tags, vec!["z"]);
+    assert!(copy.favorite);
+    assert_eq!(lib.get_icon_palette(&copy.id).unwrap(), vec!["#ff0000"]);
+
+    let r = lib
+        .bulk_delete_icons(&[i1.id.clone(), i2.id.clone(), i3.id.clone()])
+        .unwrap();
+    assert_eq!(r.changed, 3);
+    assert_eq!(lib.get_total_icon_count().unwrap(), 2);
+}
+
+// ── Tags ─────────────────────────────────────────────────────
+
+#[test]
+fn tag_rename_merge_color_delete() {
+    let lib = library();
+    let pack = lib.create_collection("Pack", None).unwrap();
+    let one = add_icon(&lib, &pack.id, "one", "<svg/>", &["a,b", "Nav"]);
+    add_icon(&lib, &pack.id, "two", "<svg/>", &["nav", "x"]);
+
+    let tags = lib.get_tags().unwrap();
+    assert_eq!(tags.len(), 3);
+    let id = |name: &str| tags.iter().find(|t| t.name == name).unwrap().id;
+    let nav = id("Nav");
+    assert_eq!(tags.iter().find(|t| t.id == nav).unwrap().icon_count, 2);
+
+    lib.rename_tag(nav, "navigation").unwrap();
+    assert_eq!(count(&lib, "navigation"), 2);
+    assert_eq!(count(&lib, "tag:nav"), 0);
+    assert_eq!(
+        lib.rename_tag(nav, " ").unwrap_err().code,
+        ErrorCode::InvalidInput
+    );
+
+    // Renaming onto an existing tag merges them
+    lib.rename_tag(id("x"), "NAVIGATION").unwrap();
+    assert_eq!(lib.get_tags().unwrap().len(), 2);
+
+    lib.set_tag_color(nav, Some("#f00")).unwrap();
+    lib.merge_tags(&[id("a,b")], nav).unwrap();
+    let tags = lib.get_tags().unwrap();
+    assert_eq!(tags.len(), 1);
+    assert_eq!(tags[0].color.as_deref(), Some("#f00"));
+    assert_eq!(tags[0].icon_count, 2);
+
+    lib.delete_tag(nav).unwrap();
+    assert!(lib
+        .get_all_icons()
+        .unwrap()
+        .iter()
+        .all(|i| i.tags.is_empty()));
+
+    lib.update_icon_tags(&one.id, &["z".into()]).unwrap();
+    assert_eq!(lib.get_tag_names().unwrap(), vec!["z"]);
+    lib.delete_icon(&one.id).unwrap();
+    assert!(lib.get_tag_names().unwrap().is_empty());
+}
+
+#[test]
+fn tag_hierarchy() {
+    let lib = library();
+    let pack = lib.create_collection("Pack", None).unwrap();
+    let two = add_icon(
+        &lib,
+        &pack.id,
+        "one",
+        "<svg/>",
+        &["category/navigation", "style/outline"],
+    );
+    add_icon(
+        &lib,
+        &pack.id,
+        "two",
+        "<svg/>",
+        &["category", "style/filled"],
+    );
+    add_icon(&lib, &pack.id, "three", "<svg/>", &["status/deprecated"]);
+
+    // A parent tag matches its children
+    assert_eq!(count(&lib, "tag:category"), 2);
+    assert_eq!(count(&lib, "tag:style"), 2);
+    assert_eq!(count(&lib, "tag:outline"), 1);
+    assert_eq!(count(&lib, "-tag:status"), 2);
+
+    let tree = lib.get_tag_tree().unwrap();
+    let top: Vec<(&str, i64, usize)> = tree
+        .iter()
+        .map(|n| (n.label.as_str(), n.tag.total_count, n.children.len()))
+        .collect();
+    assert_eq!(
+        top,
+        vec![("category", 2, 1), ("status", 1, 1), ("style", 2, 2)]
+    );
+
+    let tags = lib.get_tags().unwrap();
+    let id = |name: &str| tags.iter().find(|t| t.name == name).unwrap().id;
+
+    // Renaming a parent carries its children along
+    lib.rename_tag(id("style"), "look").unwrap();
+    assert_eq!(count(&lib, "tag:look"), 2);
+    assert!(icon(&lib, &two.id)
+        .tags
+        .contains(&"look/outline".to_string()));
+
+    let err = lib
+        .set_tag_parent(id("style"), Some(id("style/outline")))
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::Conflict);
+
+    lib.set_tag_parent(id("status"), Some(id("style"))).unwrap();
+    assert_eq!(count(&lib, "tag:look"), 3);
+
+    // Deleting a parent moves its children to the top level
+    lib.delete_tag(id("style")).unwrap();
+    let names = lib.get_tag_names().unwrap();
+    assert!(names.contains(&"outline".to_string()));
+    assert!(names.contains(&"status/deprecated".to_string()));
+
+    // A tag cannot be merged into its own child
+    assert!(lib
+        .merge_tags(&[id("category")], id("category/navigation"))
+        .is_err());
+}
+
+#[test]
+fn tag_paths_follow_the_stored_casing() {
+    let lib = library();
+    let pack = lib.create_collection("Pack", None).unwrap();
+    add_icon(&lib, &pack.id, "one", "<svg/>", &["Style"]);
+    let two = add_icon(&lib, &pack.id, "two", "<svg/>", &["style/outline"]);
+    assert_eq!(icon(&lib, &two.id).tags, vec!["Style/outline"]);
+
+    let tags = lib.get_tags().unwrap();
+    let style = tags.iter().find(|t| t.name == "Style").unwrap();
+    assert_eq!(style.total_count, 2);
+    lib.rename_tag(style.id, "Look").unwrap();
+    assert_eq!(icon(&lib, &two.id).tags, vec!["Look/outline"]);
+    assert_eq!(count(&lib, "tag:look"), 2);
+    assert_eq!(lib.get_tag_names().unwrap(), vec!["Look", "Look/outline"]);
+
+    // Undo puts the subtag back along with its parent
+    lib.undo().unwrap();
+    assert_eq!(icon(&lib, &two.id).tags, vec!["Style/outline"]);
+    assert_eq!(lib.get_tag_names().unwrap(), vec!["Style", "Style/outline"]);
+}
+
+#[test]
+fn autotag_adds_derived_tags() {
+    let lib = library();
+    let pack = lib.create_collection("Pack", None).unwrap();
+    let sub = lib.create_collection("Sub", Some(&pack.id)).unwrap();
+    add_icon(&lib, &pack.id, "home-outline", "<svg/>", &["mine"]);
+    add_icon(&lib, &sub.id, "user-add", "<svg/>", &[]);
+    let elsewhere = lib.create_collection("Elsewhere", None).unwrap();
+    let skipped = add_icon(&lib, &elsewhere.id, "star", "<svg/>", &[]);
+
+    // Tags come from the name and the folder the file was imported from
+    lib.save_setting("autotag_stop_words", "outline, pack")
+        .unwrap();
+    let result = lib
+        .autotag_icons(Some(std::slice::from_ref(&pack.id)))
+        .unwrap();
+    assert_eq!((result.icons_updated, result.tags_added), (2, 3));
+    assert!(icon(&lib, &skipped.id).tags.is_empty());
+
+    // Nothing is added twice
+    let result = lib
+        .autotag_icons(Some(std::slice::from_ref(&pack.id)))
+        .unwrap();
+    assert_eq!(result.tags_added, 0);
+}
+
+// ── Search ───────────────────────────────────────────────────
+
+#[test]
+fn search_text_and_filters() {
+    let lib = library();
+    let pack = lib.create_collection("Tabler Icons", None).unwrap();
+    let left = add_icon(
+        &lib,
+        &pack.id,
+        "arrow-left",
+        "<svg><title>Go back</title><path stroke=\"currentColor\"/></svg>",
+        &["nav", "arrow"],
+    );
+    add_icon(
+        &lib,
+        &pack.id,
+        "trash",
+        "<svg><path fill=\"red\" stroke=\"none\"/></svg>",
+        &["deprecated", "arrow"],
+    );
+
+    assert_eq!(count(&lib, "lef"), 1);
+    assert_eq!(count(&lib, "back"), 1);
+    assert_eq!(count(&lib, "tabler"), 2);
+    assert_eq!(count(&lib, "tag:arrow -tag:deprecated"), 1);
+    assert_eq!(count(&lib, "stroke:yes"), 1);
+    assert_eq!(count(&lib, "size:<2kb"), 2);
+    assert_eq!(count(&lib, "collection:\"tabler icons\""), 2);
+    assert_eq!(count(&lib, "fav:yes"), 0);
+    assert_eq!(count(&lib, "-trash"), 1);
+    assert_eq!(count(&lib, "added:>=2020-01-01"), 2);
+
+    lib.toggle_favorite(&left.id).unwrap();
+    let favorites = lib.search_icons("", None, true, false, 100, 0).unwrap();
+    assert_eq!(favorites.total, 1);
+    let in_pack = lib
+        .search_icons("", Some(&pack.id), false, false, 1, 1)
+        .unwrap();
+    assert_eq!((in_pack.total, in_pack.icons.len()), (2, 1));
+
+    let err = lib
+        .search_icons("x size:<2kq", None, false, false, 10, 0)
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidQuery);
+    assert_eq!(err.context.position, Some(2));
+}
+
+#[test]
+fn search_falls_back_to_fuzzy_names() {
+    let lib = library();
+    let pack = lib.create_collection("Pack", None).unwrap();
+    for name in [
+        "arrow-left",
+        "ArrowLeft",
+        "arrow_left_24",
+        "arrow-right",
+        "trash",
+    ] {
+        add_icon(&lib, &pack.id, name, "<svg/>", &[]);
+    }
+
+    let results = lib
+        .search_icons("arow lft", None, false, false, 50, 0)
+        .unwrap();
+    assert!(results.fuzzy);
+    let found = names(&lib, "arow lft");
+    assert_eq!(found, vec!["ArrowLeft", "arrow-left", "arrow_left_24"]);
+
+    assert_eq!(names(&lib, "trsh"), vec!["trash"]);
+    // New icons are picked up without reopening
+    add_icon(&lib, &pack.id, "trash-can", "<svg/>", &[]);
+    assert_eq!(count(&lib, "trsh"), 2);
+    assert!(
+        !lib.search_icons("arrow", None, false, false, 50, 0)
+            .unwrap()
+            .fuzzy
+    );
+}
+
+#[test]
+fn search_suggestions() {
+    let lib = library();
+    let pack = lib.create_collection("Tabler Icons", None).unwrap();
+    add_icon(&lib, &pack.id, "a", "<svg/>", &["deprecated", "nav"]);
+
+    let suggestions = lib.suggest_search("x -tag:de").unwrap();
+    assert_eq!(suggestions.len(), 1);
+    assert_eq!(suggestions[0].query, "x -tag:deprecated");
+    let suggestions = lib.suggest_search("collection:ta").unwrap();
+    assert_eq!(suggestions[0].query, "collection:\"Tabler Icons\"");
+}
+
+#[test]
+fn synonyms_expand_search() {
+    let lib = library();
+    let pack = lib.create_collection("Pack", None).unwrap();
+    add_icon(&lib, &pack.id, "trash-can", "<svg/>", &[]);
+    add_icon(&lib, &pack.id, "icon-set", "<svg/>", &[]);
+    let defaults = lib.get_synonym_groups().unwrap().len();
+    assert!(defaults > 0);
+    assert_eq!(count(&lib, "bin"), 1);
+
+    let group = lib
+        .save_synonym_group(
+            None,
+            &["pictogram".into(), "icon".into(), " ".into(), "ICON".into()],
+        )
+        .unwrap()
+        .unwrap();
+    assert_eq!(group.terms, vec!["pictogram", "icon"]);
+    assert_eq!(count(&lib, "pictogram"), 1);
+
+    // A group needs two terms; fewer removes it
+    assert!(lib
+        .save_synonym_group(Some(group.id), &["x".into()])
+        .unwrap()
+        .is_none());
+    assert_eq!(count(&lib, "pictogram"), 0);
+
+    let bin = lib
+        .get_synonym_groups()
+        .unwrap()
+        .into_iter()
+        .find(|g| g.terms.iter().any(|t| t == "bin"))
+        .unwrap();
+    lib.delete_synonym_group(bin.id).unwrap();
+    assert_eq!(count(&lib, "bin"), 0);
+
+    lib.reset_synonyms().unwrap();
+    assert_eq!(lib.get_synonym_groups().unwrap().len(), defaults);
+    assert_eq!(count(&lib, "bin"), 1);
+}
+
+#[test]
+fn similar_icons_and_palettes() {
+    let lib = library();
+    let pack = lib.create_collection("Pack", None).unwrap();
+    let circle = add_icon(
+        &lib,
+        &pack.id,
+        "circle",
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24"><circle cx="12" cy="12" r="10" fill="red"/></svg>"#,
+        &[],
+    );
+    let big = add_icon(
+        &lib,
+        &pack.id,
+        "circle-big",
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100"><circle cx="50" cy="50" r="30" fill="blue"/></svg>"#,
+        &[],
+    );
+    add_icon(
+        &lib,
+        &pack.id,
+        "square",
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24"><rect x="2" y="2" width="20" height="20"/></svg>"#,
+        &[],
+    );
+    let outline = add_icon(
+        &lib,
+        &pack.id,
+        "outline",
+        r#"<svg stroke="currentColor" fill="none"><path d="M0 0"/></svg>"#,
+        &[],
+    );
+    add_icon(&lib, &pack.id, "broken", "not svg", &[]);
+
+    let similar = lib.find_similar(&circle.id, 10).unwrap();
+    assert_eq!(similar[0].icon.id, big.id);
+    assert!(similar.iter().all(|s| s.icon.name != "broken"));
+
+    assert_eq!(lib.get_icon_palette(&circle.id).unwrap(), vec!["#ff0000"]);
+    assert!(lib.get_icon_palette(&outline.id).unwrap().is_empty());
+    assert_eq!(names(&lib, "color:red"), vec!["circle"]);
+    assert!(names(&lib, "mono:yes").contains(&"outline".to_string()));
+}
+
+#[test]
+fn semantic_search_with_a_loaded_model() {
+    let dir = temp_dir("semantic");
+    let lib = Library::open(dir.join("iconbox.db")).unwrap();
+    let pack = lib.create_collection("Pack", None).unwrap();
+    add_icon(&lib, &pack.id, "credit-card", "<svg/>", &[]);
+    add_icon(&lib, &pack.id, "Wallet", "<svg/>", &[]);
+    add_icon(&lib, &pack.id, "home", "<svg/>", &[]);
+
+    assert!(!lib.get_embedding_status().unwrap().loaded);
+    assert_eq!(
+        lib.semantic_search("payment", 10).unwrap_err().code,
+        ErrorCode::Model
+    );
+    let missing = lib
+        .load_embedding_model(&dir.join("missing.vec"))
+        .unwrap_err();
+    assert_eq!(missing.code, ErrorCode::Model);
+
+    let model = dir.join("vectors.txt");
+    std::fs::write(
+        &model,
+        "5 3\npayment 1 0 0\ncredit 0.9 0.1 0\ncard 0.8 0 0.2\nwallet 0.9 0.2 0\nhome 0 1 0\n",
+    )
+    .unwrap();
+    let status = lib.load_embedding_model(&model).unwrap();
+    assert!(status.loaded);
+    assert_eq!((status.dimensions, status.vocabulary), (3, 5));
+
+    let matches = lib.semantic_search("payment", 10).unwrap();
+    let found: Vec<&str> = matches.iter().map(|m| m.icon.name.as_str()).collect();
+    assert_eq!(found.len(), 2);
+    assert!(found.contains(&"credit-card") && found.contains(&"Wallet"));
+    assert_eq!(lib.get_embedding_status().unwrap().indexed, 3);
+
+    // The model path is remembered for the next session
+    drop(lib);
+    let lib = Library::open(dir.join("iconbox.db")).unwrap();
+    assert!(lib.get_embedding_status().unwrap().loaded);
+
+    // A remembered model that fails to load says why, and is only read once
+    let broken = dir.join("broken.vec");
+    let lib = library();
+    lib.save_setting("embedding_model", &broken.to_string_lossy())
+        .unwrap();
+    let status = lib.get_embedding_status().unwrap();
+    assert!(!status.loaded);
+    assert_eq!(
+        status.error.as_ref().map(|e| e.code),
+        Some(ErrorCode::Model)
+    );
+    std::fs::copy(&model, &broken).unwrap();
+    let err = lib.semantic_search("payment", 10).unwrap_err();
+    assert_eq!(Some(err), status.error);
+    let status = lib.load_embedding_model(&broken).unwrap();
+    assert!(status.loaded && status.error.is_none());
+}
+
+// ── Trash ────────────────────────────────────────────────────
+
+#[test]
+fn deleted_icons_can_be_restored() {
+    let lib = library();
+    let pack = lib.create_collection("Pack", None).unwrap();
+    let star = add_icon(
+        &lib,
+        &pack.id,
+        "star",
+        r##"<svg fill="#f00"><path/></svg>"##,
+        &["shape/star"],
+    );
+    lib.toggle_favorite(&star.id).unwrap();
+    let tag = lib
+        .get_tags()
+        .unwrap()
+        .into_iter()
+        .find(|t| t.name == "shape/star")
+        .unwrap();
+    lib.set_tag_color(tag.id, Some("#0f0")).unwrap();
+
+    lib.delete_icon(&star.id).unwrap();
+    assert_eq!(lib.get_total_icon_count().unwrap(), 0);
+    assert_eq!(count(&lib, "star"), 0);
+    let trash = lib.get_trash().unwrap();
+    assert_eq!(trash.len(), 1);
+    assert_eq!(
+        (trash[0].kind.as_str(), trash[0].name.as_str()),
+        ("icon", "star")
+    );
+    assert_eq!(trash[0].location.as_deref(), Some("Pack"));
+    assert!(trash[0].svg_content.is_some());
+    assert!(trash[0].expires_at.is_some());
+
+    let result = lib
+        .restore_from_trash(&[trash[0].id.clone(), "missing".to_string()])
+        .unwrap();
+    assert_eq!(result.changed, 1);
+    assert_eq!(result.missing, vec!["missing"]);
+    assert!(lib.get_trash().unwrap().is_empty());
+
+    // Everything comes back as it was
+    let restored = icon(&lib, &star.id);
+    assert!(restored.favorite);
+    assert_eq!(restored.tags, vec!["shape/star"]);
+    assert_eq!(
+        lib.get_tags()
+            .unwrap()
+            .iter()
+            .find(|t| t.name == "shape/star")
+            .unwrap()
+            .color
+            .as_deref(),
+        Some("#0f0")
+    );
+    assert_eq!(collection(&lib, &pack.id).icon_count, 1);
+    assert_eq!(count(&lib, "tag:shape"), 1);
+    assert_eq!(lib.get_icon_palette(&star.id).unwrap(), vec!["#ff0000"]);
+    assert!(lib.find_similar(&star.id, 5).is_ok());
+}
+
+#[test]
+fn deleted_collection_trees_can_be_restored() {
+    let lib = library();
+    let packs = lib.create_collection("Packs", None).unwrap();
+    let tabler = lib.create_collection("Tabler", Some(&packs.id)).unwrap();
+    let arrows = lib.create_collection("Arrows", Some(&tabler.id)).unwrap();
+    add_icon(&lib, &tabler.id, "home", "<svg/>", &["nav"]);
+    add_icon(&lib, &arrows.id, "arrow-left", "<svg/>", &["nav"]);
+    add_icon(&lib, &arrows.id, "arrow-right", "<svg/>", &[]);
+
+    lib.delete_collection(&tabler.id).unwrap();
+    assert_eq!(lib.get_all_collections().unwrap().len(), 1);
+    assert_eq!(lib.get_total_icon_count().unwrap(), 0);
+    let trash = lib.get_trash().unwrap();
+    assert_eq!(trash.len(), 1);
+    assert_eq!((trash[0].icon_count, trash[0].collection_count), (3, 2));
+
+    lib.restore_from_trash(std::slice::from_ref(&trash[0].id))
+        .unwrap();
+    assert_eq!(
+        collection(&lib, &tabler.id).parent_id.as_deref(),
+        Some(packs.id.as_str())
+    );
+    assert_eq!(
+        collection(&lib, &arrows.id).parent_id.as_deref(),
+        Some(tabler.id.as_str())
+    );
+    assert_eq!(collection(&lib, &packs.id).total_icon_count, 3);
+    assert_eq!(collection(&lib, &arrows.id).icon_count, 2);
+    assert_eq!(count(&lib, "tag:nav"), 2);
+    assert_eq!(count(&lib, "collection:arrows"), 2);
+
+    // With its parent gone, a collection comes back at the top level
+    lib.delete_collection(&arrows.id).unwrap();
+    lib.delete_collection(&packs.id).unwrap();
+    let trash = lib.get_trash().unwrap();
+    let arrows_entry = trash.iter().find(|t| t.name == "Arrows").unwrap();
+    lib.restore_from_trash(std::slice::from_ref(&arrows_entry.id))
+        .unwrap();
+    assert_eq!(collection(&lib, &arrows.id).parent_id, None);
+}
+
+#[test]
+fn icon_needs_its_collection_to_be_restored() {
+    let lib = library();
+    let pack = lib.create_collection("Pack", None).unwrap();
+    let icon = add_icon(&lib, &pack.id, "star", "<svg/>", &[]);
+    lib.delete_icon(&icon.id).unwrap();
+    lib.delete_collection(&pack.id).unwrap();
+
+    let trash = lib.get_trash().unwrap();
+    let icon_entry = trash.iter().find(|t| t.kind == "icon").unwrap();
+    assert_eq!(icon_entry.location, None);
+    let err = lib
+        .restore_from_trash(std::slice::from_ref(&icon_entry.id))
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::Conflict);
+    assert_eq!(lib.get_trash().unwrap().len(), 2);
+
+    // Collection first, then the icon
+    let pack_entry = trash.iter().find(|t| t.kind == "collection").unwrap();
+    lib.restore_from_trash(&[pack_entry.id.clone(), icon_entry.id.clone()])
+        .unwrap();
+    assert_eq!(collection(&lib, &pack.id).icon_count, 1);
+}
+
+#[test]
+fn trash_can_be_emptied_and_purged() {
+    let dir = temp_dir("trash-purge");
+    let path = dir.join("iconbox.db");
+    {
+        let lib = Library::open(&path).unwrap();
+        let pack = lib.create_collection("Pack", None).unwrap();
+        let ids: Vec<String> = (0..3)
+            .map(|n| add_icon(&lib, &pack.id, &format!("icon-{}", n), "<svg/>", &[]).id)
+            .collect();
+        let result = lib.bulk_delete_icons(&ids).unwrap();
+        assert_eq!(result.changed, 3);
+        assert_eq!(lib.get_trash().unwrap().len(), 3);
+
+        let first = lib.get_trash().unwrap()[0].id.clone();
+        lib.delete_from_trash(std::slice::from_ref(&first)).unwrap();
+        assert_eq!(lib.get_trash().unwrap().len(), 2);
+        assert_eq!(lib.get_settings().unwrap().trash_retention_days, 30);
+        lib.save_setting("trash_retention_days", "0").unwrap();
+    }
+
+    // Age the entries past the retention period
+    {
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.execute(
+            "UPDATE trash SET deleted_at = '2000-01-01T00:00:00+00:00'",
+            [],
+        )
+        .unwrap();
+    }
+    {
+        // Kept forever with retention 0
+        let lib = Library::open(&path).unwrap();
+        assert_eq!(lib.get_trash().unwrap().len(), 2);
+        assert_eq!(lib.get_trash().unwrap()[0].expires_at, None);
+        lib.save_setting("trash_retention_days", "7").unwrap();
+    }
+    let lib = Library::open(&path).unwrap();
+    assert!(lib.get_trash().unwrap().is_empty());
+
+    let pack = lib.create_collection("Other", None).unwrap();
+    let icon = add_icon(&lib, &pack.id, "x", "<svg/>", &[]);
+    lib.delete_icon(&icon.id).unwrap();
+    lib.delete_collection(&pack.id).unwrap();
+    assert_eq!(lib.empty_trash().unwrap(), 2);
+    assert!(lib.get_trash().unwrap().is_empty());
+}
+
+// ── History ──────────────────────────────────────────────────
+
+#[test]
+fn edits_can_be_undone_and_redone() {
+    let lib = library();
+    let pack = lib.create_collection("Pack", None).unwrap();
+    let other = lib.create_collection("Other", None).unwrap();
+    let arrow = add_icon(&lib, &pack.id, "arrow", "<svg/>", &["nav"]);
+
+    lib.rename_collection(&pack.id, "Arrows").unwrap();
+    lib.toggle_favorite(&arrow.id).unwrap();
+    lib.update_icon_tags(&arrow.id, &["nav".to_string(), "direction".to_string()])
+        .unwrap();
+    lib.move_icon(&arrow.id, &other.id).unwrap();
+    assert_eq!(
+        lib.get_history().unwrap().undo.as_deref(),
+        Some("Move icon")
+    );
+
+    assert_eq!(lib.undo().unwrap().as_deref(), Some("Move icon"));
+    assert_eq!(icon(&lib, &arrow.id).collection_id, pack.id);
+    assert_eq!(collection(&lib, &pack.id).icon_count, 1);
+    assert_eq!(lib.undo().unwrap().as_deref(), Some("Edit tags"));
+    assert_eq!(icon(&lib, &arrow.id).tags, vec!["nav"]);
+    assert_eq!(count(&lib, "tag:direction"), 0);
+    assert!(!lib
+        .get_tag_names()
+        .unwrap()
+        .contains(&"direction".to_string()));
+    lib.undo().unwrap();
+    assert!(!icon(&lib, &arrow.id).favorite);
+    lib.undo().unwrap();
+    assert_eq!(collection(&lib, &pack.id).name, "Pack");
+
+    let history = lib.get_history().unwrap();
+    assert_eq!(history.undo.as_deref(), Some("Create collection"));
+    assert_eq!(history.redo.as_deref(), Some("Rename collection"));
+    assert_eq!(lib.redo().unwrap().as_deref(), Some("Rename collection"));
+    lib.redo().unwrap();
+    lib.redo().unwrap();
+    assert_eq!(collection(&lib, &pack.id).name, "Arrows");
+    assert!(icon(&lib, &arrow.id).favorite);
+    assert_eq!(icon(&lib, &arrow.id).tags, vec!["nav", "direction"]);
+    assert_eq!(count(&lib, "tag:direction"), 1);
+
+    // A new edit drops what was left to redo
+    lib.set_tag_color(1, Some("#ff0000")).unwrap();
+    assert_eq!(lib.get_history().unwrap().redo, None);
+    assert_eq!(lib.redo().unwrap(), None);
+
+    // Undoing a create removes the collection again; the icon added to it
+    // outside the history keeps it from being dropped, so it goes to the trash
+    while lib.undo().unwrap().is_some() {}
+    assert!(lib.get_all_collections().unwrap().is_empty());
+    assert_eq!(lib.get_trash().unwrap().len(), 1);
+}
+
+#[test]
+fn bulk_actions_undo_in_one_step() {
+    let lib = library();
+    let pack = lib.create_collection("Pack", None).unwrap();
+    let other = lib.create_collection("Other", None).unwrap();
+    let ids: Vec<String> = (0..4)
+        .map(|n| add_icon(&lib, &pack.id, &format!("icon-{}", n), "<svg/>", &[]).id)
+        .collect();
+
+    lib.bulk_add_tags(&ids, &["ui".to_string()]).unwrap();
+    lib.bulk_move_icons(&ids[..2], &other.id).unwrap();
+    lib.bulk_copy_icons(&ids[2..], &other.id).unwrap();
+    lib.bulk_delete_icons(&ids[..1]).unwrap();
+    assert_eq!(collection(&lib, &other.id).icon_count, 3);
+
+    assert_eq!(lib.undo().unwrap().as_deref(), Some("Delete icons"));
+    assert!(lib.get_trash().unwrap().is_empty());
+    assert_eq!(collection(&lib, &other.id).icon_count, 4);
+    assert_eq!(lib.undo().unwrap().as_deref(), Some("Copy icons"));
+    assert_eq!(collection(&lib, &other.id).icon_count, 2);
+    assert_eq!(lib.get_trash().unwrap().len(), 2);
+    lib.undo().unwrap();
+    assert_eq!(collection(&lib, &pack.id).icon_count, 4);
+    assert_eq!(collection(&lib, &other.id).icon_count, 0);
+    lib.undo().unwrap();
+    assert_eq!(count(&lib, "tag:ui"), 0);
+
+    for _ in 0..4 {
+        lib.redo().unwrap();
+    }
+    assert_eq!(count(&lib, "tag:ui"), 5);
+    assert_eq!(collection(&lib, &pack.id).icon_count, 2);
+    assert_eq!(collection(&lib, &other.id).icon_count, 3);
+    assert_eq!(lib.get_trash().unwrap().len(), 1);
+}
+
+#[test]
+fn tag_edits_can_be_undone() {
+    let lib = library();
+    let pack = lib.create_collection("Pack", None).unwrap();
+    let a = add_icon(&lib, &pack.id, "a", "<svg/>", &["nav/arrow", "ui"]);
+    let b = add_icon(&lib, &pack.id, "b", "<svg/>", &["direction"]);
+    let tag_id = |name: &str| {
+        lib.get_tags()
+            .unwrap()
+            .into_iter()
+            .find(|t| t.name == name)
+            .map(|t| t.id)
+    };
+
+    lib.merge_tags(&[tag_id("nav").unwrap()], tag_id("direction").unwrap())
+        .unwrap();
+    assert_eq!(icon(&lib, &a.id).tags, vec!["direction/arrow", "ui"]);
+    lib.delete_tag(tag_id("ui").unwrap()).unwrap();
+    lib.rename_tag(tag_id("direction").unwrap(), "way").unwrap();
+    assert_eq!(icon(&lib, &b.id).tags, vec!["way"]);
+
+    lib.undo().unwrap();
+    assert_eq!(icon(&lib, &b.id).tags, vec!["direction"]);
+    lib.undo().unwrap();
+    assert_eq!(icon(&lib, &a.id).tags, vec!["direction/arrow", "ui"]);
+    lib.undo().unwrap();
+    assert_eq!(icon(&lib, &a.id).tags, vec!["nav/arrow", "ui"]);
+    assert_eq!(icon(&lib, &b.id).tags, vec!["direction"]);
+    assert_eq!(count(&lib, "tag:nav"), 1);
+    assert_eq!(tag_id("direction/arrow"), None);
+
+    lib.redo().unwrap();
+    lib.redo().unwrap();
+    assert_eq!(icon(&lib, &a.id).tags, vec!["direction/arrow"]);
+}
+
+#[test]
+fn collection_merge_and_delete_can_be_undone() {
+    let lib = library();
+    let source = lib.create_collection("Source", None).unwrap();
+    let target = lib.create_collection("Target", None).unwrap();
+    let sub = lib.create_collection("Sub", Some(&source.id)).unwrap();
+    let kept = add_icon(&lib, &target.id, "home", "<svg>h</svg>", &["a"]);
+    let dup = add_icon(&lib, &source.id, "home", "<svg>h</svg>", &["b"]);
+    let clash = add_icon(&lib, &source.id, "home", "<svg>other</svg>", &[]);
+    add_icon(&lib, &sub.id, "deep", "<svg/>", &[]);
+
+    let result = lib.merge_collections(&source.id, &target.id).unwrap();
+    assert_eq!(result.duplicates_merged, 1);
+    assert_eq!(lib.get_trash().unwrap().len(), 1);
+
+    lib.undo().unwrap();
+    assert_eq!(collection(&lib, &source.id).icon_count, 2);
+    assert_eq!(
+        collection(&lib, &sub.id).parent_id.as_deref(),
+        Some(source.id.as_str())
+    );
+    assert_eq!(icon(&lib, &dup.id).tags, vec!["b"]);
+    assert_eq!(icon(&lib, &clash.id).name, "home");
+    assert_eq!(icon(&lib, &kept.id).tags, vec!["a"]);
+    assert!(lib.get_trash().unwrap().is_empty());
+
+    lib.redo().unwrap();
+    assert!(lib
+        .get_all_collections()
+        .unwrap()
+        .iter()
+        .all(|c| c.id != source.id));
+    assert_eq!(collection(&lib, &target.id).total_icon_count, 3);
+
+    lib.delete_collection(&target.id).unwrap();
+    assert!(lib.get_all_collections().unwrap().is_empty());
+    assert_eq!(lib.undo().unwrap().as_deref(), Some("Delete collection"));
+    assert_eq!(collection(&lib, &target.id).total_icon_count, 3);
+    assert_eq!(count(&lib, "deep"), 1);
+}
+
+#[test]
+fn undo_stops_when_the_trash_was_emptied() {
+    let lib = library();
+    let pack = lib.create_collection("Pack", None).unwrap();
+    let arrow = add_icon(&lib, &pack.id, "arrow", "<svg/>", &[]);
+    lib.rename_collection(&pack.id, "Icons").unwrap();
+    lib.delete_icon(&arrow.id).unwrap();
+    lib.empty_trash().unwrap();
+
+    let err = lib.undo().unwrap_err();
+    assert_eq!(err.code, ErrorCode::Conflict);
+    assert!(err.message.starts_with("Cannot undo \"Delete icon\""));
+    // Everything before it is dropped too, since it could depend on the icon
+    assert_eq!(lib.get_history().unwrap().undo, None);
+    assert_eq!(collection(&lib, &pack.id).name, "Icons");
+}
+
+// ── Revisions ────────────────────────────────────────────────
+
+const RED_CIRCLE: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24">
+  <circle cx="12" cy="12" r="10" fill="red"/>
+</svg>"#;
+const BLUE_CIRCLE: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24"><circle cx="12" cy="12" r="10" fill="blue"/></svg>"#;
+
+#[test]
+fn svg_changes_are_kept_as_revisions() {
+    let lib = library();
+    let pack = lib.create_collection("Pack", None).unwrap();
+    let circle = add_icon(&lib, &pack.id, "circle", RED_CIRCLE, &[]);
+
+    let updated = lib
+        .update_icon_svg(&circle.id, BLUE_CIRCLE, "optimize")
+        .unwrap();
+    assert_eq!(updated.svg_content, BLUE_CIRCLE);
+    assert_eq!(updated.file_size, BLUE_CIRCLE.len() as i64);
+    assert_eq!(lib.get_icon_palette(&circle.id).unwrap(), vec!["#0000ff"]);
+    // Saving the same artwork again changes nothing
+    lib.update_icon_svg(&circle.id, BLUE_CIRCLE, "edit")
+        .unwrap();
+
+    let revisions = lib.get_icon_revisions(&circle.id).unwrap();
+    let reasons: Vec<&str> = revisions.iter().map(|r| r.reason.as_str()).collect();
+    assert_eq!(reasons, vec!["optimize", "import"]);
+    assert!(revisions[0].current && !revisions[1].current);
+    assert_eq!(revisions[1].svg_content, RED_CIRCLE);
+
+    let err = lib
+        .update_icon_svg(&circle.id, BLUE_CIRCLE, "magic")
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidInput);
+    let err = lib
+        .update_icon_svg(&circle.id, "not svg", "edit")
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidInput);
+    let err = lib.get_icon_revisions("missing").unwrap_err();
+    assert_eq!(err.code, ErrorCode::NotFound);
+}
+
+#[test]
+fn revisions_can_be_compared_and_restored() {
+    let lib = library();
+    let pack = lib.create_collection("Pack", None).unwrap();
+    let circle = add_icon(&lib, &pack.id, "circle", RED_CIRCLE, &[]);
+    lib.update_icon_svg(&circle.id, BLUE_CIRCLE, "edit")
+        .unwrap();
+    let revisions = lib.get_icon_revisions(&circle.id).unwrap();
+    let (old, new) = (revisions[1].id, revisions[0].id);
+
+    // Formatting does not matter, only the changed element shows up
+    let diff = lib.diff_icon_revisions(old, new).unwrap();
+    let changed: Vec<(&str, &str)> = diff
+        .lines
+        .iter()
+        .filter(|l| l.kind != "same")
+        .map(|l| (l.kind.as_str(), l.text.as_str()))
+        .collect();
+    assert_eq!(
+        changed,
+        vec![
+            ("removed", r#"<circle cx="12" cy="12" r="10" fill="red"/>"#),
+            ("added", r#"<circle cx="12" cy="12" r="10" fill="blue"/>"#),
+        ]
+    );
+    // Same shape in another color
+    assert_eq!(diff.visual_distance, Some(0.0));
+
+    let restored = lib.restore_icon_revision(old).unwrap();
+    assert_eq!(restored.svg_content, RED_CIRCLE);
+    let revisions = lib.get_icon_revisions(&circle.id).unwrap();
+    assert_eq!(revisions.len(), 3);
+    assert_eq!(revisions[0].reason, "restore");
+    assert!(revisions[0].current);
+
+    let other = add_icon(&lib, &pack.id, "other", BLUE_CIRCLE, &[]);
+    let other_revision = lib.get_icon_revisions(&other.id).unwrap()[0].id;
+    let err = lib.diff_icon_revisions(old, other_revision).unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidInput);
+    assert_eq!(
+        lib.restore_icon_revision(9999).unwrap_err().code,
+        ErrorCode::NotFound
+    );
+}
+
+#[test]
+fn svg_edits_can_be_undone() {
+    let lib = library();
+    let pack = lib.create_collection("Pack", None).unwrap();
+    let circle = add_icon(&lib, &pack.id, "circle", RED_CIRCLE, &[]);
+    lib.update_icon_svg(&circle.id, BLUE_CIRCLE, "optimize")
+        .unwrap();
+
+    assert_eq!(lib.undo().unwrap().as_deref(), Some("Optimize icon"));
+    assert_eq!(icon(&lib, &circle.id).svg_content, RED_CIRCLE);
+    assert_eq!(names(&lib, "color:red"), vec!["circle"]);
+    // The undone version stays in the list
+    let revisions = lib.get_icon_revisions(&circle.id).unwrap();
+    assert_eq!(revisions.len(), 2);
+    assert!(revisions[1].current);
+
+    lib.redo().unwrap();
+    assert_eq!(icon(&lib, &circle.id).svg_content, BLUE_CIRCLE);
+    assert!(lib.get_icon_revisions(&circle.id).unwrap()[0].current);
+}
+
+#[test]
+fn revisions_follow_icons_through_copies_and_the_trash() {
+    let lib = library();
+    let pack = lib.create_collection("Pack", None).unwrap();
+    let other = lib.create_collection("Other", None).unwrap();
+    let circle = add_icon(&lib, &pack.id, "circle", RED_CIRCLE, &[]);
+    lib.update_icon_svg(&circle.id, BLUE_CIRCLE, "sync")
+        .unwrap();
+
+    let copy = lib.copy_icon(&circle.id, &other.id).unwrap();
+    let copied = lib.get_icon_revisions(&copy.id).unwrap();
+    assert_eq!(copied.len(), 2);
+    assert_eq!(copied[0].reason, "sync");
+    assert!(copied[0].current);
+
+    lib.delete_icon(&circle.id).unwrap();
+    let trash_id = lib.get_trash().unwrap()[0].id.clone();
+    lib.restore_from_trash(&[trash_id]).unwrap();
+    let revisions = lib.get_icon_revisions(&circle.id).unwrap();
+    assert_eq!(revisions.len(), 2);
+    assert!(revisions[0].current);
+
+    // Deleting for good takes the revisions along
+    let first = revisions[1].id;
+    lib.delete_icon(&circle.id).unwrap();
+    lib.empty_trash().unwrap();
+    assert_eq!(
+        lib.restore_icon_revision(first).unwrap_err().code,
+        ErrorCode::NotFound
+    );
+}
+
+// ── Backups ──────────────────────────────────────────────────
+
+#[test]
+fn backups_are_verified_copies_and_rotate() {
+    let dir = temp_dir("backups");
+    let lib = Library::open(dir.join("iconbox.db")).unwrap();
+    let pack = lib.create_collection("Pack", None).unwrap();
+    let arrow = add_icon(&lib, &pack.id, "arrow", "<svg/>", &["nav"]);
+    lib.save_setting("backup_keep", "2").unwrap();
+    let backup_dir = lib.backup_dir().unwrap().unwrap();
+    assert_eq!(backup_dir, dir.join("backups"));
+    // Upgrade copies share the folder but are not rotated
+    std::fs::create_dir_all(&backup_dir).unwrap();
+    std::fs::write(backup_dir.join("iconbox-v1-20240101-000000.db"), "").unwrap();
+
+    let first = lib.backup_now().unwrap();
+    assert!(first.name.starts_with("iconbox-backup-"));
+    Library::verify(std::path::Path::new(&first.path)).unwrap();
+    let copy = Library::open_read_only(&first.path).unwrap();
+    assert_eq!(icon(&copy, &arrow.id).tags, vec!["nav"]);
+
+    lib.backup_now().unwrap();
+    let last = lib.backup_now().unwrap();
+    let backups = lib.get_backups().unwrap();
+    let names: Vec<&str> = backups.iter().map(|b| b.name.as_str()).collect();
+    assert_eq!(names.len(), 3);
+    assert_eq!(names[0], last.name);
+    assert!(!names.contains(&first.name.as_str()));
+    assert!(names.contains(&"iconbox-v1-20240101-000000.db"));
+}
+
+#[test]
+fn scheduled_backups_follow_the_settings() {
+    let lib = library();
+    // Nowhere to write to until a folder is chosen
+    assert_eq!(lib.backup_if_due().unwrap().map(|b| b.name), None);
+    let err = lib.backup_now().unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidInput);
+
+    let dir = temp_dir("backup-schedule");
+    lib.save_setting("backup_folder", &dir.to_string_lossy())
+        .unwrap();
+    let settings = lib.get_settings().unwrap();
+    assert_eq!(
+        settings.backup_folder,
+        Some(dir.to_string_lossy().to_string())
+    );
+    assert_eq!(settings.backup_interval_hours, 24);
+
+    lib.save_setting("backup_interval_hours", "0").unwrap();
+    assert!(lib.backup_if_due().unwrap().is_none());
+    lib.save_setting("backup_interval_hours", "24").unwrap();
+    assert!(lib.backup_if_due().unwrap().is_some());
+    // Not due again until a day has passed
+    assert!(lib.backup_if_due().unwrap().is_none());
+    assert_eq!(lib.get_backups().unwrap().len(), 1);
+}
+
+// ── Archives ─────────────────────────────────────────────────
+
+#[test]
+fn archives_round_trip_into_an_empty_library() {
+    let dir = temp_dir("archive");
+    let lib = library();
+    let pack = lib.create_collection("Pack", None).unwrap();
+    let arrows = lib
+        .create_collection("Arrows/Left", Some(&pack.id))
+        .unwrap();
+    let home = add_icon(
+        &lib,
+        &pack.id,
+        "home",
+        "<svg><path d=\"M1\"/></svg>",
+        &["ui/nav"],
+    );
+    add_icon(
+        &lib,
+        &arrows.id,
+        "left",
+        "<svg><path d=\"M2\"/></svg>",
+        &["ui/arrows"],
+    );
+    lib.toggle_favorite(&home.id).unwrap();
+    let ui = lib
+        .get_tags()
+        .unwrap()
+        .into_iter()
+        .find(|t| t.name == "ui")
+        .unwrap();
+    lib.set_tag_color(ui.id, Some("#0af")).unwrap();
+    lib.save_smart_collection(None, "Navigation", "tag:ui/nav")
+        .unwrap();
+
+    let path = dir.join("library.zip");
+    let exported = lib.export_archive(&path, None).unwrap();
+    assert_eq!((exported.collections, exported.icons), (2, 2));
+    assert!(!dir.join("library.partial").exists());
+
+    let copy = library();
+    let imported = copy.import_archive(&path, None, "skip").unwrap();
+    assert_eq!(imported.collections_created, 2);
+    assert_eq!(imported.icons_added, 2);
+    assert_eq!(imported.smart_collections_created, 1);
+
+    let collections = copy.get_all_collections().unwrap();
+    let pack_copy = collections.iter().find(|c| c.name == "Pack").unwrap();
+    let arrows_copy = collections
+        .iter()
+        .find(|c| c.name == "Arrows/Left")
+        .unwrap();
+    assert_eq!(pack_copy.color, pack.color);
+    assert_eq!(
+        arrows_copy.parent_id.as_deref(),
+        Some(pack_copy.id.as_str())
+    );
+    assert_eq!(pack_copy.total_icon_count, 2);
+    assert_eq!(collections.iter().filter(|c| c.query.is_some()).count(), 1);
+    let icons = copy.get_all_icons().unwrap();
+    let home_copy = icons.iter().find(|i| i.name == "home").unwrap();
+    assert!(home_copy.favorite);
+    assert_eq!(home_copy.tags, vec!["ui/nav"]);
+    assert_eq!(home_copy.svg_content, home.svg_content);
+    assert_eq!(home_copy.created_at, home.created_at);
+    let tags = copy.get_tags().unwrap();
+    assert_eq!(
+        tags.iter()
+            .find(|t| t.name == "ui")
+            .unwrap()
+            .color
+            .as_deref(),
+        Some("#0af")
+    );
+
+    // A selection takes its subcollections along, but not the rest
+    let partial = dir.join("arrows.zip");
+    let exported = lib
+        .export_archive(&partial, Some(std::slice::from_ref(&arrows.id)))
+        .unwrap();
+    assert_eq!((exported.collections, exported.icons), (1, 1));
+    let err = lib
+        .export_archive(&partial, Some(&["missing".to_string()]))
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::NotFound);
+}
+
+#[test]
+fn archive_imports_merge_with_conflict_handling() {
+    let dir = temp_dir("archive-merge");
+    let lib = library();
+    let pack = lib.create_collection("Pack", None).unwrap();
+    let same = add_icon(&lib, &pack.id, "same", "<svg>1</svg>", &["a"]);
+    add_icon(&lib, &pack.id, "changed", "<svg>2</svg>", &[]);
+    let path = dir.join("pack.zip");
+    lib.export_archive(&path, None).unwrap();
+    lib.toggle_favorite(&same.id).unwrap();
+
+    let other = library();
+    let pack2 = other.create_collection("PACK", None).unwrap();
+    let kept = add_icon(&other, &pack2.id, "same", "<svg>1</svg>", &["b"]);
+    let changed = add_icon(&other, &pack2.id, "Changed", "<svg>other</svg>", &[]);
+
+    // The preview changes nothing
+    let preview = other.preview_archive_import(&path, None, "skip").unwrap();
+    assert_eq!(preview.collections_merged, 1);
+    assert_eq!(preview.duplicates_merged, 1);
+    assert_eq!(preview.icons_skipped, 1);
+    assert_eq!(other.get_all_icons().unwrap().len(), 2);
+    assert_eq!(icon(&other, &kept.id).tags, vec!["b"]);
+
+    let result = other.import_archive(&path, None, "skip").unwrap();
+    assert_eq!((result.collections_created, result.icons_added), (0, 0));
+    assert_eq!(icon(&other, &kept.id).tags, vec!["b", "a"]);
+    assert_eq!(icon(&other, &changed.id).svg_content, "<svg>other</svg>");
+
+    let result = other.import_archive(&path, None, "keep_both").unwrap();
+    assert_eq!(result.icons_renamed, 1);
+    assert!(other
+        .get_all_icons()
+        .unwrap()
+        .iter()
+        .any(|i| i.name == "changed (2)"));
+    other.undo().unwrap();
+    assert_eq!(other.get_all_icons().unwrap().len(), 2);
+
+    let result = other.import_archive(&path, None, "replace").unwrap();
+    assert_eq!(result.icons_replaced, 1);
+    assert_eq!(icon(&other, &changed.id).svg_content, "<svg>2</svg>");
+    assert_eq!(other.get_icon_revisions(&changed.id).unwrap().len(), 2);
+    other.undo().unwrap();
+    assert_eq!(icon(&other, &changed.id).svg_content, "<svg>other</svg>");
+
+    // Imported under another collection, everything is new and one undo
+    // takes it all away again
+    let inbox = other.create_collection("Inbox", None).unwrap();
+    let result = other
+        .import_archive(&path, Some(&inbox.id), "skip")
+        .unwrap();
+    assert_eq!((result.collections_created, result.icons_added), (1, 2));
+    assert_eq!(collection(&other, &inbox.id).total_icon_count, 2);
+    other.undo().unwrap();
+    assert_eq!(collection(&other, &inbox.id).total_icon_count, 0);
+
+    let err = other.import_archive(&path, None, "overwrite").unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidInput);
+}
+
+#[test]
+fn damaged_archives_are_refused() {
+    let dir = temp_dir("archive-damaged");
+    let lib = library();
+    let not_zip = dir.join("notes.zip");
+    std::fs::write(&not_zip, "just text").unwrap();
+    let err = lib.import_archive(&not_zip, None, "skip").unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidInput);
+
+    let pack = lib.create_collection("Pack", None).unwrap();
+    add_icon(&lib, &pack.id, "one", "<svg>one</svg>", &[]);
+    let path = dir.join("pack.zip");
+    lib.export_archive(&path, None).unwrap();
+    // Flip the first byte of the SVG's data, right after its local header
+    let mut data = std::fs::read(&path).unwrap();
+    let name = b"icons/Pack/one.svg";
+    let at: Vec<usize> = (0..data.len())
+        .filter(|&i| data[i..].starts_with(name))
+        .collect();
+    data[at[at.len() - 2] + name.len()] ^= 0xff;
+    std::fs::write(&path, data).unwrap();
+    assert!(lib.import_archive(&path, None, "skip").is_err());
+    assert_eq!(lib.get_all_icons().unwrap().len(), 1);
+}
+
+// ── Import and settings ──────────────────────────────────────
+
+#[test]
+fn import_folder_creates_a_tagged_collection() {
+    let dir = temp_dir("import");
+    let folder = dir.join("Tabler Icons");
+    std::fs::create_dir_all(&folder).unwrap();
+    std::fs::write(
+        folder.join("arrow-left.svg"),
+        "<svg><title>Go back</title></svg>",
+    )
+    .unwrap();
+    std::fs::write(folder.join("home-outline.svg"), "<svg/>").unwrap();
+    std::fs::write(folder.join("notes.txt"), "not an icon").unwrap();
+
+    let lib = library();
+    let parent = lib.create_collection("Packs", None).unwrap();
+    let result = lib.import_folder(&folder, Some(&parent.id)).unwrap();
+    assert!(result.failed.is_empty());
+    let imported = result.collection;
+    assert_eq!(imported.name, "Tabler Icons");
+    assert_eq!(imported.parent_id.as_deref(), Some(parent.id.as_str()));
+    assert_eq!(imported.icon_count, 2);
+    assert_eq!(collection(&lib, &parent.id).total_icon_count, 2);
+
+    let icons = lib.get_icons_by_collection(&imported.id).unwrap();
+    let arrow = icons.iter().find(|i| i.name == "arrow-left").unwrap();
+    assert!(arrow.tags.contains(&"arrow".to_string()));
+    assert!(arrow.tags.contains(&"tabler".to_string()));
+    assert!(arrow.file_size > 0);
+    assert_eq!(count(&lib, "back"), 1);
+
+    // Unreadable files are reported, the rest are still imported
+    std::fs::write(folder.join("broken.svg"), [0xff, 0xfe, 0x00]).unwrap();
+    let result = lib.import_folder(&folder, None).unwrap();
+    assert_eq!(result.collection.icon_count, 2);
+    assert_eq!(result.failed.len(), 1);
+    assert!(result.failed[0].path.ends_with("broken.svg"));
+    assert_eq!(result.failed[0].error.code, ErrorCode::Io);
+
+    // Undone as one edit, which moves the whole collection to the trash
+    assert_eq!(lib.undo().unwrap().as_deref(), Some("Import folder"));
+    let collections = lib.get_all_collections().unwrap();
+    assert!(collections.iter().all(|c| c.id != result.collection.id));
+    assert_eq!(lib.get_trash().unwrap()[0].icon_count, 2);
+}
+
+#[test]
+fn settings_round_trip() {
+    let lib = library();
+    let defaults = lib.get_settings().unwrap();
+    assert!(!defaults.autotag_stop_words.is_empty());
+
+    lib.save_setting("theme", "light").unwrap();
+    lib.save_setting("icon_size", "64").unwrap();
+    lib.save_setting("tint_color", "#ff0000").unwrap();
+    let settings = lib.get_settings().unwrap();
+    assert_eq!(settings.theme, "light");
+    assert_eq!(settings.icon_size, 64);
+    assert_eq!(settings.tint_color.as_deref(), Some("#ff0000"));
+}
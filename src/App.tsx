@@ -1,31 +1,54 @@
-import { useState, useEffect, useCallback } from "react";
+import { useState, useEffect, useCallback, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import Sidebar from "./components/Sidebar";
 import IconGrid from "./components/IconGrid";
 import SearchBar from "./components/SearchBar";
 import DetailPanel from "./components/DetailPanel";
 import SettingsModal from "./components/SettingsModal";
-import { Icon, Collection, Settings, ViewMode } from "./types";
+import BulkActionBar from "./components/BulkActionBar";
+import LibraryRecovery from "./components/LibraryRecovery";
+import { errorMessage, hasCode } from "./errors";
+import { ArchiveExportResult, AutotagResult, BulkResult, EmbeddingStatus, Icon, IconPage, IconSummary, IconSvg, ImportResult, MergeResult, Collection, SearchResults, SearchSuggestion, SemanticMatch, Settings, StartupStatus, TagNode, ViewMode } from "./types";
 
 function App() {
+  // Library
+  const [startup, setStartup] = useState<StartupStatus | null>(null);
+  const libraryOpen = startup !== null && startup.state !== "failed";
+
   // Data
   const [collections, setCollections] = useState<Collection[]>([]);
-  const [icons, setIcons] = useState<Icon[]>([]);
-  const [filteredIcons, setFilteredIcons] = useState<Icon[]>([]);
+  const [icons, setIcons] = useState<(IconSummary | Icon)[]>([]);
+  const [filteredIcons, setFilteredIcons] = useState<(IconSummary | Icon)[]>([]);
+  // Bumped when artwork changes so the grid refetches it
+  const [svgEpoch, setSvgEpoch] = useState(0);
+  const iconLoad = useRef(0);
   const [totalIconCount, setTotalIconCount] = useState(0);
   const [favoriteCount, setFavoriteCount] = useState(0);
+  const [tagTree, setTagTree] = useState<TagNode[]>([]);
 
   // UI state
   const [activeCollection, setActiveCollection] = useState<string | null>(null);
   const [viewMode, setViewMode] = useState<ViewMode>("all");
   const [searchQuery, setSearchQuery] = useState("");
-  const [selectedIcon, setSelectedIcon] = useState<Icon | null>(null);
+  const [searchError, setSearchError] = useState<string | null>(null);
+  const [suggestions, setSuggestions] = useState<SearchSuggestion[]>([]);
+  const [embedding, setEmbedding] = useState<EmbeddingStatus | null>(null);
+  const [semantic, setSemantic] = useState(false);
+  const [selectedIcon, setSelectedIcon] = useState<IconSummary | null>(null);
+  // Multi-selection; selectedIcon is the anchor for Shift-click ranges
+  const [selectedIds, setSelectedIds] = useState<Set<string>>(new Set());
   const [detailIcon, setDetailIcon] = useState<Icon | null>(null);
   const [showSettings, setShowSettings] = useState(false);
+  const [notice, setNotice] = useState<string | null>(null);
   const [settings, setSettings] = useState<Settings>({
     theme: "light",
     icon_size: 64,
     tint_color: null,
+    autotag_stop_words: "",
+    trash_retention_days: 30,
+    backup_folder: null,
+    backup_interval_hours: 24,
+    backup_keep: 10,
   });
 
   // ── Data loading ───────────────────────────────────────────
@@ -52,6 +75,14 @@ function App() {
     }
   }, []);
 
+  const loadTagTree = useCallback(async () => {
+    try {
+      setTagTree(await invoke<TagNode[]>("get_tag_tree"));
+    } catch (err) {
+      console.error("Failed to load tags:", err);
+    }
+  }, []);
+
   const loadSettings = useCallback(async () => {
     try {
       const s = await invoke<Settings>("get_settings");
@@ -62,58 +93,178 @@ function App() {
   }, []);
 
   const loadIcons = useCallback(async () => {
+    // A newer load (e.g. after switching views) supersedes this one
+    const load = ++iconLoad.current;
     try {
-      let iconList: Icon[];
-      if (viewMode === "all") {
-        iconList = await invoke<Icon[]>("get_all_icons");
-      } else if (viewMode === "favorites") {
-        iconList = await invoke<Icon[]>("get_favorite_icons");
-      } else if (activeCollection) {
-        iconList = await invoke<Icon[]>("get_icons", { collectionId: activeCollection });
-      } else {
-        iconList = [];
+      if (viewMode === "smart") {
+        const smart = collections.find((c) => c.id === activeCollection);
+        const iconList = smart?.query
+          ? (await invoke<SearchResults>("search_icons", { query: smart.query, exact: true, limit: 10000 })).icons
+          : [];
+        if (load === iconLoad.current) setIcons(iconList);
+        return;
+      }
+      if (viewMode === "collection" && !activeCollection) {
+        setIcons([]);
+        return;
       }
-      setIcons(iconList);
+      // Page through the listing without artwork; the grid fetches the
+      // SVGs of the cards in view
+      let iconList: IconSummary[] = [];
+      let cursor: string | null = null;
+      do {
+        const page: IconPage = await invoke<IconPage>("list_icons", {
+          collectionId: viewMode === "collection" ? activeCollection : null,
+          favoritesOnly: viewMode === "favorites",
+          cursor,
+          limit: 500,
+        });
+        if (load !== iconLoad.current) return;
+        iconList = iconList.concat(page.icons);
+        cursor = page.next_cursor;
+        setIcons(iconList);
+      } while (cursor);
     } catch (err) {
       console.error("Failed to load icons:", err);
     }
-  }, [viewMode, activeCollection]);
+  }, [viewMode, activeCollection, collections]);
+
+  const loadEmbeddingStatus = useCallback(async () => {
+    try {
+      setEmbedding(await invoke<EmbeddingStatus>("get_embedding_status"));
+    } catch (err) {
+      console.error("Failed to get embedding status:", err);
+    }
+  }, []);
+
+  // Find out whether the library opened before loading anything from it
+  useEffect(() => {
+    invoke<StartupStatus>("get_startup_status")
+      .then(setStartup)
+      .catch((err) => console.error("Failed to get startup status:", err));
+  }, []);
 
   // Initial load
   useEffect(() => {
+    if (!libraryOpen) return;
     loadCollections();
     loadCounts();
     loadSettings();
-  }, [loadCollections, loadCounts, loadSettings]);
+    loadTagTree();
+    loadEmbeddingStatus();
+  }, [libraryOpen, loadCollections, loadCounts, loadSettings, loadTagTree, loadEmbeddingStatus]);
 
   // Reload icons when view changes
   useEffect(() => {
-    loadIcons();
-  }, [loadIcons]);
+    if (libraryOpen) loadIcons();
+  }, [libraryOpen, loadIcons]);
 
-  // Filter icons by search
+  // Filter icons by search (full-text search runs in the backend)
   useEffect(() => {
-    if (searchQuery.trim()) {
-      const q = searchQuery.toLowerCase();
-      setFilteredIcons(
-        icons.filter(
-          (icon) =>
-            icon.name.toLowerCase().includes(q) ||
-            icon.tags.some((tag) => tag.toLowerCase().includes(q))
-        )
-      );
-    } else {
+    if (!searchQuery.trim()) {
       setFilteredIcons(icons);
+      setSearchError(null);
+      setSuggestions([]);
+      return;
     }
-  }, [searchQuery, icons]);
 
-  // Keep detailIcon in sync with data
+    let cancelled = false;
+    const timer = setTimeout(async () => {
+      if (semantic && embedding?.loaded) {
+        // Ranked over the whole library, then narrowed to what is on screen
+        setSuggestions([]);
+        try {
+          const matches = await invoke<SemanticMatch[]>("semantic_search", { query: searchQuery, limit: 500 });
+          const inView = new Set(icons.map((i) => i.id));
+          if (!cancelled) {
+            setFilteredIcons(matches.map((m) => m.icon).filter((i) => inView.has(i.id)));
+            setSearchError(null);
+          }
+        } catch (err) {
+          if (!cancelled) setSearchError(errorMessage(err));
+        }
+        return;
+      }
+      invoke<SearchSuggestion[]>("suggest_search", { query: searchQuery })
+        .then((s) => !cancelled && setSuggestions(s))
+        .catch(() => !cancelled && setSuggestions([]));
+      // Inside a smart collection the typed text narrows its saved query
+      const smartQuery =
+        viewMode === "smart" ? collections.find((c) => c.id === activeCollection)?.query : null;
+      try {
+        const res = await invoke<SearchResults>("search_icons", {
+          query: smartQuery ? `${smartQuery} ${searchQuery}` : searchQuery,
+          collectionId: viewMode === "collection" ? activeCollection : null,
+          favoritesOnly: viewMode === "favorites",
+          // A smart collection's icons are exactly what its query matches
+          exact: !!smartQuery,
+          limit: 500,
+        });
+        if (!cancelled) {
+          setFilteredIcons(res.icons);
+          setSearchError(null);
+        }
+      } catch (err) {
+        // Malformed queries come back as a readable message
+        if (!cancelled) setSearchError(errorMessage(err));
+      }
+    }, 150);
+
+    return () => {
+      cancelled = true;
+      clearTimeout(timer);
+    };
+  }, [searchQuery, icons, viewMode, activeCollection, collections, semantic, embedding]);
+
+  // Undo / redo anywhere except while typing, where the field handles it
+  const replayHistory = useCallback(
+    async (command: "undo" | "redo") => {
+      try {
+        const label = await invoke<string | null>(command);
+        if (!label) return;
+        setNotice(`${command === "undo" ? "Undone" : "Redone"}: ${label}`);
+      } catch (err) {
+        setNotice(errorMessage(err));
+      }
+      // The entry may have put back an earlier revision of the artwork
+      setSvgEpoch((e) => e + 1);
+      loadIcons();
+      loadCounts();
+      loadCollections();
+      loadTagTree();
+    },
+    [loadIcons, loadCounts, loadCollections, loadTagTree]
+  );
+
   useEffect(() => {
-    if (detailIcon) {
-      const updated = icons.find((i) => i.id === detailIcon.id);
-      if (updated) setDetailIcon(updated);
-    }
-  }, [icons, detailIcon]);
+    if (!libraryOpen) return;
+    const handler = (e: KeyboardEvent) => {
+      const target = e.target as HTMLElement;
+      if (target.closest("input, textarea, [contenteditable]")) return;
+      if (!(e.ctrlKey || e.metaKey)) return;
+      const key = e.key.toLowerCase();
+      if (key === "z" || key === "y") {
+        e.preventDefault();
+        replayHistory(key === "y" || e.shiftKey ? "redo" : "undo");
+      }
+    };
+    window.addEventListener("keydown", handler);
+    return () => window.removeEventListener("keydown", handler);
+  }, [libraryOpen, replayHistory]);
+
+  useEffect(() => {
+    if (!notice) return;
+    const timer = setTimeout(() => setNotice(null), 4000);
+    return () => clearTimeout(timer);
+  }, [notice]);
+
+  // Keep detailIcon in sync with data; listings may not carry the artwork
+  useEffect(() => {
+    setDetailIcon((prev) => {
+      const updated = prev && icons.find((i) => i.id === prev.id);
+      return prev && updated ? { ...prev, ...updated } : prev;
+    });
+  }, [icons]);
 
   // ── Handlers ───────────────────────────────────────────────
 
@@ -121,35 +272,100 @@ function App() {
     setViewMode(mode);
     setActiveCollection(null);
     setDetailIcon(null);
-    setSelectedIcon(null);
+    clearSelection();
   }
 
   function handleSelectCollection(id: string) {
-    setViewMode("collection");
+    const collection = collections.find((c) => c.id === id);
+    setViewMode(collection?.query ? "smart" : "collection");
     setActiveCollection(id);
     setDetailIcon(null);
-    setSelectedIcon(null);
+    clearSelection();
+  }
+
+  // The detail panel shows the artwork, which listings leave out
+  async function showDetail(icon: IconSummary | Icon) {
+    if ("svg_content" in icon) {
+      setDetailIcon(icon);
+      return;
+    }
+    try {
+      const [svg] = await invoke<IconSvg[]>("get_icon_svgs", { iconIds: [icon.id] });
+      if (svg) setDetailIcon({ ...icon, svg_content: svg.svg_content });
+    } catch (err) {
+      console.error("Failed to load icon artwork:", err);
+    }
   }
 
-  function handleSelectIcon(icon: Icon) {
+  function handleSelectIcon(icon: IconSummary, e: React.MouseEvent) {
+    if (e.ctrlKey || e.metaKey) {
+      setSelectedIds((prev) => {
+        const next = new Set(prev);
+        if (next.has(icon.id)) next.delete(icon.id);
+        else next.add(icon.id);
+        return next;
+      });
+      setSelectedIcon(icon);
+      return;
+    }
+    if (e.shiftKey && selectedIcon) {
+      const from = filteredIcons.findIndex((i) => i.id === selectedIcon.id);
+      const to = filteredIcons.findIndex((i) => i.id === icon.id);
+      if (from !== -1 && to !== -1) {
+        const range = filteredIcons.slice(Math.min(from, to), Math.max(from, to) + 1);
+        setSelectedIds(new Set(range.map((i) => i.id)));
+        return;
+      }
+    }
+    setSelectedIds(new Set([icon.id]));
     setSelectedIcon(icon);
-    setDetailIcon(icon);
+    showDetail(icon);
   }
 
-  function handleOpenDetail(icon: Icon) {
-    setDetailIcon(icon);
+  function handleOpenDetail(icon: IconSummary | Icon) {
+    showDetail(icon);
     setSelectedIcon(icon);
+    setSelectedIds(new Set([icon.id]));
+  }
+
+  function clearSelection() {
+    setSelectedIds(new Set());
+    setSelectedIcon(null);
+  }
+
+  async function handleBulk(command: string, args: Record<string, unknown> = {}) {
+    try {
+      const res = await invoke<BulkResult>(command, { iconIds: [...selectedIds], ...args });
+      if (res.missing.length > 0) {
+        console.warn(`${res.missing.length} selected icons no longer exist`);
+      }
+      if (command === "bulk_delete_icons" || command === "bulk_move_icons") {
+        if (detailIcon && selectedIds.has(detailIcon.id)) setDetailIcon(null);
+        clearSelection();
+      }
+      await loadIcons();
+      await loadCounts();
+      await loadCollections();
+      await loadTagTree();
+    } catch (err) {
+      console.error(`Failed to ${command.replace(/_/g, " ")}:`, err);
+    }
   }
 
   async function handleImportFolder(parentId?: string) {
     try {
-      const col = await invoke<Collection>("import_folder", { parentId: parentId || null });
+      const res = await invoke<ImportResult>("import_folder", { parentId: parentId || null });
       await loadCollections();
       await loadCounts();
       setViewMode("collection");
-      setActiveCollection(col.id);
+      setActiveCollection(res.collection.id);
+      if (res.failed.length > 0) {
+        const names = res.failed.map((f) => f.path.split(/[\\/]/).pop()).join(", ");
+        setNotice(`${res.failed.length} file(s) could not be imported: ${names}`);
+        console.warn("Files left out of the import:", res.failed);
+      }
     } catch (err) {
-      console.error("Failed to import folder:", err);
+      if (!hasCode(err, "cancelled")) console.error("Failed to import folder:", err);
     }
   }
 
@@ -162,6 +378,18 @@ function App() {
     }
   }
 
+  async function handleSaveSmartCollection(id: string | null, name: string, query: string) {
+    try {
+      const col = await invoke<Collection>("save_smart_collection", { id, name, query });
+      await loadCollections();
+      setViewMode("smart");
+      setActiveCollection(col.id);
+      if (!id) setSearchQuery("");
+    } catch (err) {
+      console.error("Failed to save smart collection:", err);
+    }
+  }
+
   async function handleRenameCollection(id: string, newName: string) {
     try {
       await invoke("rename_collection", { id, newName });
@@ -186,11 +414,67 @@ function App() {
     }
   }
 
+  async function handleAutotag(collectionIds: string[] | null) {
+    try {
+      const res = await invoke<AutotagResult>("run_autotag", { collectionIds });
+      console.info(`Auto-tagging added ${res.tags_added} tags to ${res.icons_updated} icons`);
+      await loadIcons();
+      await loadTagTree();
+    } catch (err) {
+      console.error("Failed to run auto-tagging:", err);
+    }
+  }
+
+  async function handleCollectionChange(command: string, args: Record<string, unknown>) {
+    try {
+      await invoke(command, args);
+      await loadCollections();
+    } catch (err) {
+      console.error(`Failed to ${command.replace(/_/g, " ")}:`, err);
+    }
+  }
+
+  async function handleMergeCollection(sourceId: string, targetId: string) {
+    try {
+      const res = await invoke<MergeResult>("merge_collections", { sourceId, targetId });
+      console.info(
+        `Merged ${res.icons_moved} icons (${res.icons_renamed} renamed, ${res.duplicates_merged} duplicates combined)`
+      );
+      if (activeCollection === sourceId) handleSelectCollection(targetId);
+      await loadCollections();
+      await loadCounts();
+      await loadIcons();
+    } catch (err) {
+      console.error("Failed to merge collections:", err);
+    }
+  }
+
+  async function handleExportCollection(id: string) {
+    try {
+      const res = await invoke<ArchiveExportResult>("export_archive", { collectionIds: [id] });
+      console.info(`Exported ${res.icons} icons to ${res.path}`);
+    } catch (err) {
+      if (!hasCode(err, "cancelled")) console.error("Failed to export collection:", err);
+    }
+  }
+
+  async function handleDropIcons(collectionId: string, iconIds: string[], copy: boolean) {
+    try {
+      await invoke<BulkResult>(copy ? "bulk_copy_icons" : "bulk_move_icons", { iconIds, collectionId });
+      if (!copy) clearSelection();
+      await loadIcons();
+      await loadCollections();
+    } catch (err) {
+      console.error(`Failed to ${copy ? "copy" : "move"} icons:`, err);
+    }
+  }
+
   async function handleToggleFavorite(iconId: string) {
     try {
       await invoke("toggle_favorite", { iconId });
       await loadIcons();
       await loadCounts();
+      await loadCollections(); // smart collection counts
     } catch (err) {
       console.error("Failed to toggle favorite:", err);
     }
@@ -200,18 +484,28 @@ function App() {
     try {
       await invoke("update_icon_tags", { iconId, tags });
       await loadIcons();
+      await loadTagTree();
+      await loadCollections(); // smart collection counts
     } catch (err) {
       console.error("Failed to update tags:", err);
     }
   }
 
+  async function handleArtworkChanged(icon: Icon) {
+    setDetailIcon(icon);
+    setSvgEpoch((e) => e + 1);
+    await loadIcons();
+    await loadCollections(); // smart collection counts
+  }
+
   async function handleDeleteIcon(iconId: string) {
     try {
       await invoke("delete_icon", { iconId });
       if (detailIcon?.id === iconId) setDetailIcon(null);
-      if (selectedIcon?.id === iconId) setSelectedIcon(null);
+      if (selectedIcon?.id === iconId) clearSelection();
       await loadIcons();
       await loadCounts();
+      await loadTagTree();
       await loadCollections(); // refresh counts
     } catch (err) {
       console.error("Failed to delete icon:", err);
@@ -232,6 +526,10 @@ function App() {
   function getBreadcrumb(): string[] {
     if (viewMode === "all") return ["All Icons"];
     if (viewMode === "favorites") return ["Favorites"];
+    if (viewMode === "smart") {
+      const smart = collections.find((c) => c.id === activeCollection);
+      return ["Smart Collections", smart?.name ?? ""];
+    }
 
     const crumbs: string[] = [];
     let current = collections.find((c) => c.id === activeCollection);
@@ -246,6 +544,10 @@ function App() {
 
   // ── Render ─────────────────────────────────────────────────
 
+  if (startup?.state === "failed") {
+    return <LibraryRecovery status={startup} onStatus={setStartup} />;
+  }
+
   return (
     <div className="flex h-screen bg-[var(--bg-primary)] p-2">
       {/* Left Sidebar */}
@@ -259,8 +561,20 @@ function App() {
         onSelectView={handleSelectView}
         onImportFolder={handleImportFolder}
         onCreateCollection={handleCreateCollection}
+        currentQuery={searchQuery}
+        onSaveSmartCollection={handleSaveSmartCollection}
         onRenameCollection={handleRenameCollection}
         onDeleteCollection={handleDeleteCollection}
+        onAutotagCollection={(id) => handleAutotag([id])}
+        onDropIcons={handleDropIcons}
+        onMoveCollection={(id, parentId) => handleCollectionChange("move_collection", { id, parentId })}
+        onReorderCollections={(parentId, orderedIds) =>
+          handleCollectionChange("reorder_collections", { parentId, orderedIds })
+        }
+        onMergeCollection={handleMergeCollection}
+        onExportCollection={handleExportCollection}
+        tagTree={tagTree}
+        onSelectTag={(path) => setSearchQuery(`tag:${path.includes(" ") ? `"${path}"` : path}`)}
         onOpenSettings={() => setShowSettings(true)}
       />
 
@@ -272,13 +586,45 @@ function App() {
             value={searchQuery}
             onChange={setSearchQuery}
             iconCount={filteredIcons.length}
+            error={searchError}
+            suggestions={suggestions}
+            semantic={embedding?.loaded ? semantic : undefined}
+            onSemanticChange={setSemantic}
           />
         </header>
 
+        {startup?.state === "read_only" && (
+          <div className="h-8 px-6 flex items-center text-[12px] text-[var(--text-secondary)] border-b border-[var(--border)] bg-[var(--bg-secondary)] flex-shrink-0">
+            The library is open read-only; changes cannot be saved.
+          </div>
+        )}
+
+        {notice && (
+          <div className="h-8 px-6 flex items-center text-[12px] text-[var(--text-secondary)] border-b border-[var(--border)] bg-[var(--bg-secondary)] flex-shrink-0">
+            {notice}
+          </div>
+        )}
+
+        {/* Actions for a multi-selection */}
+        {selectedIds.size > 1 && (
+          <BulkActionBar
+            count={selectedIds.size}
+            collections={collections.filter((c) => !c.query)}
+            onFavorite={(favorite) => handleBulk("bulk_set_favorite", { favorite })}
+            onAddTags={(tags) => handleBulk("bulk_add_tags", { tags })}
+            onRemoveTags={(tags) => handleBulk("bulk_remove_tags", { tags })}
+            onMove={(collectionId) => handleBulk("bulk_move_icons", { collectionId })}
+            onCopy={(collectionId) => handleBulk("bulk_copy_icons", { collectionId })}
+            onDelete={() => handleBulk("bulk_delete_icons")}
+            onClear={clearSelection}
+          />
+        )}
+
         {/* Icon Grid */}
         <IconGrid
           icons={filteredIcons}
-          selectedIcon={selectedIcon}
+          svgEpoch={svgEpoch}
+          selectedIds={selectedIds}
           onSelectIcon={handleSelectIcon}
           onOpenDetail={handleOpenDetail}
           tintColor={settings.tint_color}
@@ -310,11 +656,13 @@ function App() {
           icon={detailIcon}
           onClose={() => {
             setDetailIcon(null);
-            setSelectedIcon(null);
+            clearSelection();
           }}
           onToggleFavorite={handleToggleFavorite}
           onUpdateTags={handleUpdateTags}
           onDeleteIcon={handleDeleteIcon}
+          onSelectIcon={handleOpenDetail}
+          onArtworkChanged={handleArtworkChanged}
           tintColor={settings.tint_color}
         />
       )}
@@ -324,6 +672,28 @@ function App() {
         <SettingsModal
           settings={settings}
           onSave={handleSaveSetting}
+          onAutotagAll={() => handleAutotag(null)}
+          onTagsChanged={() => {
+            loadIcons();
+            loadTagTree();
+            loadCollections();
+          }}
+          onEmbeddingModelLoaded={setEmbedding}
+          onTrashRestored={() => {
+            loadIcons();
+            loadCollections();
+            loadCounts();
+            loadTagTree();
+          }}
+          // Start over on the restored file, as on launch
+          onLibraryReplaced={() => window.location.reload()}
+          onArchiveImported={() => {
+            setSvgEpoch((e) => e + 1);
+            loadIcons();
+            loadCollections();
+            loadCounts();
+            loadTagTree();
+          }}
           onClose={() => setShowSettings(false)}
         />
       )}
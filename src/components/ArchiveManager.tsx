@@ -0,0 +1,154 @@
+import { useState, useEffect } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { ArchiveConflictPolicy, ArchiveExportResult, ArchiveImportResult } from "../types";
+import { errorMessage, hasCode } from "../errors";
+
+interface ArchiveManagerProps {
+  /** Called after an archive was imported into the library */
+  onImported: () => void;
+}
+
+const CONFLICT_OPTIONS: { policy: ArchiveConflictPolicy; label: string }[] = [
+  { policy: "skip", label: "Keep mine" },
+  { policy: "replace", label: "Use the archive's" },
+  { policy: "keep_both", label: "Keep both" },
+];
+
+const buttonClass =
+  "px-2 py-1 bg-[var(--bg-tertiary)] text-[12px] text-[var(--text-secondary)] rounded hover:bg-[var(--bg-hover)] disabled:opacity-40";
+
+function describe(result: ArchiveImportResult) {
+  const parts = [
+    `${result.icons_added} new icons`,
+    `${result.collections_created} new collections`,
+    result.collections_merged > 0 && `${result.collections_merged} merged collections`,
+    result.duplicates_merged > 0 && `${result.duplicates_merged} already here`,
+    result.icons_replaced > 0 && `${result.icons_replaced} replaced`,
+    result.icons_renamed > 0 && `${result.icons_renamed} renamed`,
+    result.icons_skipped > 0 && `${result.icons_skipped} skipped`,
+    result.smart_collections_created > 0 && `${result.smart_collections_created} smart collections`,
+  ];
+  return parts.filter(Boolean).join(" · ");
+}
+
+export default function ArchiveManager({ onImported }: ArchiveManagerProps) {
+  const [archivePath, setArchivePath] = useState<string | null>(null);
+  const [policy, setPolicy] = useState<ArchiveConflictPolicy>("skip");
+  const [preview, setPreview] = useState<ArchiveImportResult | null>(null);
+  const [busy, setBusy] = useState(false);
+  const [message, setMessage] = useState<string | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  // Preview again whenever the archive or the conflict policy changes
+  useEffect(() => {
+    if (!archivePath) return;
+    let cancelled = false;
+    setPreview(null);
+    invoke<ArchiveImportResult>("preview_archive_import", { path: archivePath, parentId: null, onConflict: policy })
+      .then((p) => !cancelled && setPreview(p))
+      .catch((err) => {
+        if (cancelled) return;
+        setError(errorMessage(err));
+        setArchivePath(null);
+      });
+    return () => {
+      cancelled = true;
+    };
+  }, [archivePath, policy]);
+
+  async function run(action: () => Promise<void>) {
+    setBusy(true);
+    setError(null);
+    setMessage(null);
+    try {
+      await action();
+    } catch (err) {
+      if (!hasCode(err, "cancelled")) setError(errorMessage(err));
+    } finally {
+      setBusy(false);
+    }
+  }
+
+  const hasConflicts = preview && preview.icons_skipped + preview.icons_replaced + preview.icons_renamed > 0;
+
+  return (
+    <div>
+      <div className="flex items-center gap-2">
+        <span className="flex-1 text-[11px] text-[var(--text-muted)]">
+          A zip of SVG folders with tags, favorites and colors
+        </span>
+        <button
+          onClick={() => run(async () => {
+            const res = await invoke<ArchiveExportResult>("export_archive", { collectionIds: null });
+            setMessage(`Exported ${res.icons} icons in ${res.collections} collections`);
+          })}
+          disabled={busy}
+          className={buttonClass}
+        >
+          Export library…
+        </button>
+        <button
+          onClick={() => run(async () => {
+            setArchivePath(await invoke<string>("choose_archive"));
+          })}
+          disabled={busy}
+          className={buttonClass}
+        >
+          Import archive…
+        </button>
+      </div>
+
+      {archivePath && (
+        <div className="mt-2 p-2 border border-[var(--border)] rounded-lg text-[12px]">
+          <div className="truncate text-[var(--text-primary)]" title={archivePath}>
+            {archivePath.split(/[\\/]/).pop()}
+          </div>
+          <div className="mt-0.5 text-[11px] text-[var(--text-muted)]">
+            {preview ? describe(preview) : "Reading…"}
+          </div>
+          <div className="flex items-center gap-2 mt-2">
+            {hasConflicts || policy !== "skip" ? (
+              <label className="flex-1 flex items-center gap-1.5 text-[11px] text-[var(--text-muted)]">
+                Same name, different icon
+                <select
+                  value={policy}
+                  onChange={(e) => setPolicy(e.target.value as ArchiveConflictPolicy)}
+                  className="bg-[var(--bg-tertiary)] rounded px-1 py-0.5 text-[12px] text-[var(--text-secondary)] focus:outline-none"
+                >
+                  {CONFLICT_OPTIONS.map((o) => (
+                    <option key={o.policy} value={o.policy}>
+                      {o.label}
+                    </option>
+                  ))}
+                </select>
+              </label>
+            ) : (
+              <span className="flex-1" />
+            )}
+            <button onClick={() => setArchivePath(null)} disabled={busy} className={buttonClass}>
+              Cancel
+            </button>
+            <button
+              onClick={() => run(async () => {
+                const res = await invoke<ArchiveImportResult>("import_archive", {
+                  path: archivePath,
+                  parentId: null,
+                  onConflict: policy,
+                });
+                setArchivePath(null);
+                setMessage(`Imported ${describe(res)}`);
+                onImported();
+              })}
+              disabled={busy || !preview}
+              className="px-2 py-1 bg-[var(--bg-tertiary)] text-[12px] text-[var(--accent)] rounded hover:bg-[var(--bg-hover)] disabled:opacity-40"
+            >
+              Import
+            </button>
+          </div>
+        </div>
+      )}
+      {message && <p className="mt-2 text-[12px] text-[var(--text-muted)]">{message}</p>}
+      {error && <p className="mt-2 text-[12px] text-red-500">{error}</p>}
+    </div>
+  );
+}
@@ -0,0 +1,148 @@
+import { useState, useEffect, useCallback } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { BackupStatus, DbBackup } from "../types";
+import { errorMessage, hasCode } from "../errors";
+
+interface BackupManagerProps {
+  intervalHours: number;
+  keep: number;
+  onSave: (key: string, value: string) => void;
+  /** Called after a restore replaced the open library */
+  onLibraryReplaced: () => void;
+}
+
+const INTERVAL_OPTIONS = [
+  { hours: 1, label: "Every hour" },
+  { hours: 6, label: "Every 6 hours" },
+  { hours: 24, label: "Daily" },
+  { hours: 168, label: "Weekly" },
+  { hours: 0, label: "Off" },
+];
+
+const KEEP_OPTIONS = [3, 5, 10, 20, 50];
+
+const selectClass =
+  "bg-[var(--bg-tertiary)] rounded px-1 py-0.5 text-[12px] text-[var(--text-secondary)] focus:outline-none";
+
+function formatSize(bytes: number) {
+  return bytes < 1024 * 1024 ? `${Math.round(bytes / 1024)} KB` : `${(bytes / 1024 / 1024).toFixed(1)} MB`;
+}
+
+export default function BackupManager({ intervalHours, keep, onSave, onLibraryReplaced }: BackupManagerProps) {
+  const [status, setStatus] = useState<BackupStatus | null>(null);
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const loadStatus = useCallback(async () => {
+    try {
+      setStatus(await invoke<BackupStatus>("get_backup_status"));
+    } catch (err) {
+      console.error("Failed to load backups:", err);
+    }
+  }, []);
+
+  useEffect(() => {
+    loadStatus();
+  }, [loadStatus]);
+
+  async function run(action: () => Promise<void>) {
+    setBusy(true);
+    setError(null);
+    try {
+      await action();
+    } catch (err) {
+      if (!hasCode(err, "cancelled")) setError(errorMessage(err));
+    } finally {
+      setBusy(false);
+    }
+  }
+
+  function handleRestore(backup: DbBackup) {
+    const when = new Date(backup.modified).toLocaleString();
+    if (!confirm(`Replace the library with the backup from ${when}? The current library is kept next to it.`)) return;
+    run(async () => {
+      await invoke("restore_library_backup", { backupPath: backup.path });
+      onLibraryReplaced();
+    });
+  }
+
+  const lastError = error ?? (status?.last_error ? `Last scheduled backup failed: ${status.last_error.message}` : null);
+
+  return (
+    <div>
+      <div className="flex items-center gap-2 mb-2">
+        <span className="flex-1 min-w-0 truncate text-[11px] text-[var(--text-muted)]" title={status?.folder ?? ""}>
+          {status?.folder ?? "No backup folder"}
+        </span>
+        <button
+          onClick={() => run(async () => {
+            await invoke("choose_backup_folder");
+            await loadStatus();
+          })}
+          disabled={busy}
+          className="px-2 py-1 bg-[var(--bg-tertiary)] text-[12px] text-[var(--text-secondary)] rounded hover:bg-[var(--bg-hover)] disabled:opacity-40"
+        >
+          Change…
+        </button>
+        <button
+          onClick={() => run(async () => {
+            await invoke("backup_library_now");
+            await loadStatus();
+          })}
+          disabled={busy}
+          className="px-2 py-1 bg-[var(--bg-tertiary)] text-[12px] text-[var(--accent)] rounded hover:bg-[var(--bg-hover)] disabled:opacity-40"
+        >
+          Back up now
+        </button>
+      </div>
+
+      {status && status.backups.length > 0 ? (
+        <div className="max-h-40 overflow-y-auto border border-[var(--border)] rounded-lg divide-y divide-[var(--border)]">
+          {status.backups.map((backup) => (
+            <div key={backup.path} className="flex items-center gap-2 px-2 py-1.5 text-[13px]">
+              <div className="flex-1 min-w-0">
+                <div className="truncate text-[var(--text-primary)]">{new Date(backup.modified).toLocaleString()}</div>
+                <div className="truncate text-[11px] text-[var(--text-muted)]">
+                  {backup.name} · {formatSize(backup.size)}
+                </div>
+              </div>
+              <button
+                onClick={() => handleRestore(backup)}
+                disabled={busy}
+                className="px-2 py-0.5 text-[12px] text-[var(--accent)] rounded hover:bg-[var(--bg-hover)] disabled:opacity-40"
+              >
+                Restore
+              </button>
+            </div>
+          ))}
+        </div>
+      ) : (
+        <p className="text-[12px] text-[var(--text-muted)]">No backups yet</p>
+      )}
+      {lastError && <p className="mt-2 text-[12px] text-red-500">{lastError}</p>}
+
+      <div className="flex justify-between items-center mt-2 text-[11px] text-[var(--text-muted)]">
+        <label className="flex items-center gap-1.5">
+          Back up
+          <select value={intervalHours} onChange={(e) => onSave("backup_interval_hours", e.target.value)} className={selectClass}>
+            {INTERVAL_OPTIONS.map((o) => (
+              <option key={o.hours} value={o.hours}>
+                {o.label}
+              </option>
+            ))}
+          </select>
+        </label>
+        <label className="flex items-center gap-1.5">
+          Keep
+          <select value={keep} onChange={(e) => onSave("backup_keep", e.target.value)} className={selectClass}>
+            {KEEP_OPTIONS.map((n) => (
+              <option key={n} value={n}>
+                {n} backups
+              </option>
+            ))}
+          </select>
+        </label>
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,121 @@
+import { useState } from "react";
+import { Collection } from "../types";
+
+interface BulkActionBarProps {
+  count: number;
+  /** Regular collections, offered as move/copy targets */
+  collections: Collection[];
+  onFavorite: (favorite: boolean) => void;
+  onAddTags: (tags: string[]) => void;
+  onRemoveTags: (tags: string[]) => void;
+  onMove: (collectionId: string) => void;
+  onCopy: (collectionId: string) => void;
+  onDelete: () => void;
+  onClear: () => void;
+}
+
+const buttonClass =
+  "px-2 py-1 text-[12px] text-[var(--text-secondary)] rounded hover:bg-[var(--bg-hover)] hover:text-[var(--text-primary)] disabled:opacity-40";
+
+export default function BulkActionBar({
+  count,
+  collections,
+  onFavorite,
+  onAddTags,
+  onRemoveTags,
+  onMove,
+  onCopy,
+  onDelete,
+  onClear,
+}: BulkActionBarProps) {
+  const [tagInput, setTagInput] = useState("");
+  const [targetId, setTargetId] = useState("");
+
+  const tags = tagInput
+    .split(",")
+    .map((t) => t.trim())
+    .filter(Boolean);
+
+  return (
+    <div className="h-10 px-6 flex items-center gap-2 border-b border-[var(--border)] bg-[var(--bg-secondary)] flex-shrink-0">
+      <span className="text-[12px] font-medium text-[var(--text-primary)] mr-2">{count} selected</span>
+
+      <button onClick={() => onFavorite(true)} className={buttonClass}>
+        Favorite
+      </button>
+      <button onClick={() => onFavorite(false)} className={buttonClass}>
+        Unfavorite
+      </button>
+
+      <div className="w-px h-5 bg-[var(--border)]" />
+
+      <input
+        value={tagInput}
+        onChange={(e) => setTagInput(e.target.value)}
+        onKeyDown={(e) => {
+          if (e.key === "Enter" && tags.length > 0) {
+            onAddTags(tags);
+            setTagInput("");
+          }
+        }}
+        placeholder="tag, style/outline..."
+        className="w-40 bg-white border border-[var(--border)] rounded px-2 py-0.5 text-[12px] focus:outline-none focus:border-[var(--accent)]"
+      />
+      <button
+        onClick={() => {
+          onAddTags(tags);
+          setTagInput("");
+        }}
+        disabled={tags.length === 0}
+        className={buttonClass}
+      >
+        Add tags
+      </button>
+      <button
+        onClick={() => {
+          onRemoveTags(tags);
+          setTagInput("");
+        }}
+        disabled={tags.length === 0}
+        className={buttonClass}
+      >
+        Remove tags
+      </button>
+
+      <div className="w-px h-5 bg-[var(--border)]" />
+
+      <select
+        value={targetId}
+        onChange={(e) => setTargetId(e.target.value)}
+        className="max-w-[10rem] bg-white border border-[var(--border)] rounded px-1.5 py-0.5 text-[12px] focus:outline-none"
+      >
+        <option value="">Collection...</option>
+        {collections.map((c) => (
+          <option key={c.id} value={c.id}>
+            {c.name}
+          </option>
+        ))}
+      </select>
+      <button onClick={() => onMove(targetId)} disabled={!targetId} className={buttonClass}>
+        Move
+      </button>
+      <button onClick={() => onCopy(targetId)} disabled={!targetId} className={buttonClass}>
+        Copy
+      </button>
+
+      <div className="flex-1" />
+
+      <button
+        onClick={onDelete}
+        className="px-2 py-1 text-[12px] text-red-500 rounded hover:bg-red-50"
+      >
+        Delete
+      </button>
+      <button onClick={onClear} title="Clear selection" className={buttonClass}>
+        <svg className="w-3.5 h-3.5" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+          <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M6 18L18 6M6 6l12 12" />
+        </svg>
+      </button>
+    </div>
+  );
+}
@@ -1,5 +1,7 @@
 import { useState, useRef, useEffect } from "react";
-import { Icon } from "../types";
+import { invoke } from "@tauri-apps/api/core";
+import { Icon, SimilarIcon } from "../types";
+import RevisionHistory from "./RevisionHistory";
 
 interface DetailPanelProps {
   icon: Icon;
@@ -7,6 +9,9 @@ interface DetailPanelProps {
   onToggleFavorite: (iconId: string) => void;
   onUpdateTags: (iconId: string, tags: string[]) => void;
   onDeleteIcon: (iconId: string) => void;
+  onSelectIcon?: (icon: Icon) => void;
+  /** Called with the updated icon after its artwork changed here */
+  onArtworkChanged: (icon: Icon) => void;
   tintColor: string | null;
 }
 
@@ -16,14 +21,28 @@ export default function DetailPanel({
   onToggleFavorite,
   onUpdateTags,
   onDeleteIcon,
+  onSelectIcon,
+  onArtworkChanged,
   tintColor: _tintColor,
 }: DetailPanelProps) {
   const [tagInput, setTagInput] = useState("");
   const [copied, setCopied] = useState(false);
+  const [similar, setSimilar] = useState<SimilarIcon[]>([]);
+  const [palette, setPalette] = useState<string[]>([]);
   const tagInputRef = useRef<HTMLInputElement>(null);
 
   useEffect(() => {
     setCopied(false);
+    let cancelled = false;
+    invoke<SimilarIcon[]>("find_similar", { iconId: icon.id, limit: 12 })
+      .then((s) => !cancelled && setSimilar(s))
+      .catch((err) => console.error("Failed to find similar icons:", err));
+    invoke<string[]>("get_icon_palette", { iconId: icon.id })
+      .then((p) => !cancelled && setPalette(p))
+      .catch((err) => console.error("Failed to load palette:", err));
+    return () => {
+      cancelled = true;
+    };
   }, [icon.id]);
 
   function handleCopySvg() {
@@ -150,7 +169,7 @@ export default function DetailPanel({
             <button
               onClick={() => onDeleteIcon(icon.id)}
               className="py-2 px-3 rounded-lg text-sm border border-[var(--border)] text-red-500 hover:bg-red-50 hover:border-red-200 transition-all"
-              title="Delete icon"
+              title="Move to trash"
             >
               <svg className="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
                 <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={1.5} d="M19 7l-.867 12.142A2 2 0 0116.138 21H7.862a2 2 0 01-1.995-1.858L5 7m5 4v6m4-6v6m1-10V4a1 1 0 00-1-1h-4a1 1 0 00-1 1v3M4 7h16" />
@@ -199,6 +218,46 @@ export default function DetailPanel({
           </div>
         </div>
 
+        {/* Colors */}
+        <div className="px-4 mt-5">
+          <h4 className="text-[11px] font-semibold text-[var(--text-muted)] uppercase tracking-wider mb-2">Colors</h4>
+          {palette.length > 0 ? (
+            <div className="flex flex-wrap gap-1.5">
+              {palette.map((color) => (
+                <span
+                  key={color}
+                  title={color}
+                  className="w-5 h-5 rounded border border-[var(--border)]"
+                  style={{ backgroundColor: color }}
+                />
+              ))}
+            </div>
+          ) : (
+            <span className="text-[12px] text-[var(--text-muted)]">Monochrome (uses current color)</span>
+          )}
+        </div>
+
+        {/* Similar */}
+        {similar.length > 0 && (
+          <div className="px-4 mt-5">
+            <h4 className="text-[11px] font-semibold text-[var(--text-muted)] uppercase tracking-wider mb-2">Similar</h4>
+            <div className="grid grid-cols-6 gap-1.5">
+              {similar.map(({ icon: s, distance }) => (
+                <button
+                  key={s.id}
+                  onClick={() => onSelectIcon?.(s)}
+                  title={`${s.name} (${Math.round((1 - distance) * 100)}% match)`}
+                  className="aspect-square p-1.5 rounded border border-[var(--border)] bg-white hover:border-[var(--accent)] [&>div>svg]:w-full [&>div>svg]:h-full"
+                >
+                  <div className="w-full h-full" dangerouslySetInnerHTML={{ __html: s.svg_content }} />
+                </button>
+              ))}
+            </div>
+          </div>
+        )}
+
+        <RevisionHistory icon={icon} onRestored={onArtworkChanged} />
+
         {/* Details */}
         <div className="px-4 mt-5 pb-4">
           <h4 className="text-[11px] font-semibold text-[var(--text-muted)] uppercase tracking-wider mb-2">Details</h4>
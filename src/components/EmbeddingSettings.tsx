@@ -0,0 +1,62 @@
+import { useState, useEffect } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { errorMessage, hasCode } from "../errors";
+import { EmbeddingStatus } from "../types";
+
+interface EmbeddingSettingsProps {
+  /** Called with the new status after a model was loaded */
+  onLoaded: (status: EmbeddingStatus) => void;
+}
+
+export default function EmbeddingSettings({ onLoaded }: EmbeddingSettingsProps) {
+  const [status, setStatus] = useState<EmbeddingStatus | null>(null);
+  const [loading, setLoading] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    invoke<EmbeddingStatus>("get_embedding_status")
+      .then(setStatus)
+      .catch((err) => console.error("Failed to get embedding status:", err));
+  }, []);
+
+  async function handleChoose() {
+    setLoading(true);
+    setError(null);
+    try {
+      const res = await invoke<EmbeddingStatus>("choose_embedding_model");
+      setStatus(res);
+      onLoaded(res);
+    } catch (err) {
+      if (!hasCode(err, "cancelled")) setError(errorMessage(err));
+    } finally {
+      setLoading(false);
+    }
+  }
+
+  const problem = error ?? status?.error?.message ?? null;
+
+  return (
+    <div>
+      <div className="flex items-center gap-2">
+        <div className="flex-1 min-w-0">
+          <div className="truncate text-[12px] text-[var(--text-primary)]" title={status?.model_path ?? ""}>
+            {status?.model_path?.split(/[\\/]/).pop() ?? "No model"}
+          </div>
+          <div className="text-[11px] text-[var(--text-muted)]">
+            {status?.loaded
+              ? `${status.vocabulary.toLocaleString()} words · ${status.dimensions} dimensions · ${status.indexed} icons indexed`
+              : "Load a fastText or GloVe word-vector file to search by meaning"}
+          </div>
+        </div>
+        <button
+          onClick={handleChoose}
+          disabled={loading}
+          className="px-2 py-1 bg-[var(--bg-tertiary)] text-[12px] text-[var(--text-secondary)] rounded hover:bg-[var(--bg-hover)] disabled:opacity-40"
+        >
+          {loading ? "Loading…" : "Choose model…"}
+        </button>
+      </div>
+      {problem && <p className="mt-2 text-[12px] text-red-500">{problem}</p>}
+    </div>
+  );
+}
@@ -1,32 +1,57 @@
-import { Icon } from "../types";
+import { useEffect, useRef } from "react";
+import { IconSummary } from "../types";
+
+/** Drag data type carrying a JSON array of icon ids */
+export const ICON_DRAG_TYPE = "application/x-iconbox-icons";
 
 interface IconCardProps {
-  icon: Icon;
+  icon: IconSummary;
+  /** Artwork, once the grid has fetched it */
+  svg: string | undefined;
+  /** Watches the card and asks for its artwork when it scrolls into view */
+  observe: (el: Element) => () => void;
   isSelected: boolean;
-  onSelect: () => void;
+  /** Receives the click so Ctrl/Shift can extend the selection */
+  onSelect: (e: React.MouseEvent) => void;
   onDoubleClick: () => void;
+  /** Icons carried by a drag that starts on this card */
+  dragIds: string[];
   tintColor: string | null;
   iconSize: number;
 }
 
 export default function IconCard({
   icon,
+  svg,
+  observe,
   isSelected,
   onSelect,
   onDoubleClick,
+  dragIds,
   tintColor: _tintColor,
   iconSize,
 }: IconCardProps) {
+  const ref = useRef<HTMLDivElement>(null);
+
+  useEffect(() => {
+    if (svg !== undefined || !ref.current) return;
+    return observe(ref.current);
+  }, [svg, observe]);
+
   const handleDragStart = (e: React.DragEvent) => {
     // Set SVG content for drag to external apps
-    e.dataTransfer.setData("text/plain", icon.svg_content);
-    e.dataTransfer.setData("text/html", icon.svg_content);
-    e.dataTransfer.setData("image/svg+xml", icon.svg_content);
-    e.dataTransfer.effectAllowed = "copy";
+    if (svg) {
+      e.dataTransfer.setData("text/plain", svg);
+      e.dataTransfer.setData("text/html", svg);
+      e.dataTransfer.setData("image/svg+xml", svg);
+    }
+    // Icon ids for dropping onto a collection in the sidebar
+    e.dataTransfer.setData(ICON_DRAG_TYPE, JSON.stringify(dragIds));
+    e.dataTransfer.effectAllowed = "copyMove";
 
     // Create a drag image from the SVG
     const div = document.createElement("div");
-    div.innerHTML = icon.svg_content;
+    div.innerHTML = svg ?? "";
     div.style.width = "48px";
     div.style.height = "48px";
     div.style.position = "absolute";
@@ -40,6 +65,8 @@ export default function IconCard({
 
   return (
     <div
+      ref={ref}
+      data-icon-id={icon.id}
       onClick={onSelect}
       onDoubleClick={onDoubleClick}
       draggable
@@ -57,7 +84,7 @@ export default function IconCard({
           width: iconSize,
           height: iconSize,
         }}
-        dangerouslySetInnerHTML={{ __html: icon.svg_content }}
+        dangerouslySetInnerHTML={{ __html: svg ?? "" }}
       />
 
       {/* Name */}
@@ -1,23 +1,106 @@
-import { Icon } from "../types";
+import { useState, useEffect, useCallback, useRef } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { Icon, IconSummary, IconSvg } from "../types";
 import IconCard from "./IconCard";
 
 interface IconGridProps {
-  icons: Icon[];
-  selectedIcon: Icon | null;
-  onSelectIcon: (icon: Icon) => void;
-  onOpenDetail: (icon: Icon) => void;
+  /** Listed icons; search results already carry their artwork */
+  icons: (IconSummary | Icon)[];
+  /** Bump to drop cached artwork after icons were redrawn */
+  svgEpoch: number;
+  selectedIds: Set<string>;
+  onSelectIcon: (icon: IconSummary, e: React.MouseEvent) => void;
+  onOpenDetail: (icon: IconSummary) => void;
   tintColor: string | null;
   iconSize: number;
 }
 
+/** Cards coming into view within this long are fetched together */
+const SVG_BATCH_DELAY_MS = 30;
+
 export default function IconGrid({
   icons,
-  selectedIcon,
+  svgEpoch,
+  selectedIds,
   onSelectIcon,
   onOpenDetail,
   tintColor,
   iconSize,
 }: IconGridProps) {
+  const [svgs, setSvgs] = useState<Map<string, string>>(new Map());
+  const requested = useRef(new Set<string>());
+  const pending = useRef(new Set<string>());
+  const timer = useRef<ReturnType<typeof setTimeout> | null>(null);
+  const epoch = useRef(svgEpoch);
+
+  useEffect(() => {
+    epoch.current = svgEpoch;
+    requested.current.clear();
+    setSvgs(new Map());
+  }, [svgEpoch]);
+
+  const flush = useCallback(() => {
+    timer.current = null;
+    const ids = [...pending.current];
+    pending.current.clear();
+    const requestEpoch = epoch.current;
+    invoke<IconSvg[]>("get_icon_svgs", { iconIds: ids })
+      .then((res) => {
+        if (requestEpoch !== epoch.current) return;
+        setSvgs((prev) => {
+          const next = new Map(prev);
+          for (const r of res) next.set(r.id, r.svg_content);
+          return next;
+        });
+      })
+      .catch((err) => {
+        console.error("Failed to load icon artwork:", err);
+        for (const id of ids) requested.current.delete(id);
+      });
+  }, []);
+
+  const requestSvg = useCallback(
+    (id: string) => {
+      if (requested.current.has(id)) return;
+      requested.current.add(id);
+      pending.current.add(id);
+      if (!timer.current) timer.current = setTimeout(flush, SVG_BATCH_DELAY_MS);
+    },
+    [flush]
+  );
+
+  // One observer for the whole grid; cards register while their artwork is
+  // missing. Created on first use since the cards' effects run before ours.
+  const observer = useRef<IntersectionObserver | null>(null);
+  const observe = useCallback(
+    (el: Element) => {
+      if (!observer.current) {
+        observer.current = new IntersectionObserver(
+          (entries) => {
+            for (const entry of entries) {
+              const id = (entry.target as HTMLElement).dataset.iconId;
+              if (entry.isIntersecting && id) requestSvg(id);
+            }
+          },
+          { rootMargin: "200px" }
+        );
+      }
+      const current = observer.current;
+      current.observe(el);
+      return () => current.unobserve(el);
+    },
+    [requestSvg]
+  );
+
+  useEffect(
+    () => () => {
+      observer.current?.disconnect();
+      observer.current = null;
+      if (timer.current) clearTimeout(timer.current);
+    },
+    []
+  );
+
   if (icons.length === 0) {
     return (
       <div className="flex-1 flex items-center justify-center text-[var(--text-muted)]">
@@ -57,9 +140,12 @@ export default function IconGrid({
           <IconCard
             key={icon.id}
             icon={icon}
-            isSelected={selectedIcon?.id === icon.id}
-            onSelect={() => onSelectIcon(icon)}
+            svg={"svg_content" in icon ? icon.svg_content : svgs.get(icon.id)}
+            observe={observe}
+            isSelected={selectedIds.has(icon.id)}
+            onSelect={(e) => onSelectIcon(icon, e)}
             onDoubleClick={() => onOpenDetail(icon)}
+            dragIds={selectedIds.has(icon.id) ? [...selectedIds] : [icon.id]}
             tintColor={tintColor}
             iconSize={iconSize}
           />
@@ -0,0 +1,104 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { ErrorCode, StartupStatus } from "../types";
+import { errorMessage } from "../errors";
+
+interface LibraryRecoveryProps {
+  status: StartupStatus;
+  onStatus: (status: StartupStatus) => void;
+}
+
+const PROBLEMS: Partial<Record<ErrorCode, string>> = {
+  corrupt: "The library file is damaged.",
+  locked: "The library is in use by another program.",
+  read_only: "The library file cannot be written.",
+  too_new: "The library was saved by a newer version of IconBox.",
+};
+
+const buttonClass =
+  "px-3 py-1.5 text-[13px] rounded-lg border border-[var(--border)] text-[var(--text-primary)] hover:bg-[var(--bg-hover)] disabled:opacity-40";
+
+function formatSize(bytes: number) {
+  return bytes < 1024 * 1024 ? `${Math.round(bytes / 1024)} KB` : `${(bytes / 1024 / 1024).toFixed(1)} MB`;
+}
+
+export default function LibraryRecovery({ status, onStatus }: LibraryRecoveryProps) {
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  async function run(command: string, args?: Record<string, unknown>) {
+    setBusy(true);
+    setError(null);
+    try {
+      onStatus(await invoke<StartupStatus>(command, args));
+    } catch (err) {
+      setError(errorMessage(err));
+    } finally {
+      setBusy(false);
+    }
+  }
+
+  return (
+    <div className="flex h-screen items-center justify-center bg-[var(--bg-primary)]">
+      <div className="w-full max-w-lg mx-4 bg-white rounded-2xl shadow-xl border border-[var(--border)] overflow-hidden">
+        <div className="px-6 py-4 border-b border-[var(--border)]">
+          <h2 className="text-base font-semibold text-[var(--text-primary)]">Cannot open the icon library</h2>
+          <p className="mt-1 text-[13px] text-[var(--text-secondary)]">
+            {(status.error && PROBLEMS[status.error.code]) ?? "The library could not be opened."}
+          </p>
+        </div>
+
+        <div className="px-6 py-5 space-y-5">
+          {status.error && (
+            <p className="text-[12px] text-[var(--text-muted)] break-words">{status.error.message}</p>
+          )}
+          <p className="text-[11px] text-[var(--text-muted)] break-all">{status.db_path}</p>
+
+          <div className="flex flex-wrap gap-2">
+            <button onClick={() => run("retry_open_library")} disabled={busy} className={buttonClass}>
+              Try again
+            </button>
+            {status.error?.code !== "too_new" && (
+              <button onClick={() => run("open_library_read_only")} disabled={busy} className={buttonClass}>
+                Open read-only
+              </button>
+            )}
+            <button onClick={() => run("start_fresh_library")} disabled={busy} className={buttonClass}>
+              Start with an empty library
+            </button>
+          </div>
+
+          {status.backups.length > 0 && (
+            <div>
+              <label className="text-sm font-medium text-[var(--text-primary)] mb-2 block">Restore a backup</label>
+              <div className="max-h-48 overflow-y-auto space-y-1">
+                {status.backups.map((b) => (
+                  <div key={b.path} className="flex items-center gap-3 px-2 py-1.5 rounded-lg hover:bg-[var(--bg-hover)]">
+                    <div className="flex-1 min-w-0">
+                      <div className="text-[12px] text-[var(--text-primary)] truncate">{b.name}</div>
+                      <div className="text-[11px] text-[var(--text-muted)]">
+                        {new Date(b.modified).toLocaleString()} · {formatSize(b.size)}
+                      </div>
+                    </div>
+                    <button
+                      onClick={() => run("restore_library_backup", { backupPath: b.path })}
+                      disabled={busy}
+                      className={buttonClass}
+                    >
+                      Restore
+                    </button>
+                  </div>
+                ))}
+              </div>
+            </div>
+          )}
+
+          <p className="text-[11px] text-[var(--text-muted)]">
+            Restoring or starting over keeps the current file next to the new one.
+          </p>
+          {error && <p className="text-[12px] text-red-500 break-words">{error}</p>}
+        </div>
+      </div>
+    </div>
+  );
+}
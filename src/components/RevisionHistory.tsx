@@ -0,0 +1,137 @@
+import { useState, useEffect } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { Icon, IconRevision, RevisionDiff } from "../types";
+import { errorMessage } from "../errors";
+
+interface RevisionHistoryProps {
+  icon: Icon;
+  /** Called with the updated icon after an old version is restored */
+  onRestored: (icon: Icon) => void;
+}
+
+const REASON_LABELS: Record<string, string> = {
+  import: "Imported",
+  optimize: "Optimized",
+  edit: "Edited",
+  sync: "Synced",
+  restore: "Restored",
+};
+
+export default function RevisionHistory({ icon, onRestored }: RevisionHistoryProps) {
+  const [revisions, setRevisions] = useState<IconRevision[]>([]);
+  const [diff, setDiff] = useState<RevisionDiff | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  // Reload when the artwork changes, e.g. after an undo
+  useEffect(() => {
+    let cancelled = false;
+    setDiff(null);
+    setError(null);
+    invoke<IconRevision[]>("get_icon_revisions", { iconId: icon.id })
+      .then((r) => !cancelled && setRevisions(r))
+      .catch((err) => console.error("Failed to load revisions:", err));
+    return () => {
+      cancelled = true;
+    };
+  }, [icon.id, icon.svg_content]);
+
+  async function handleCompare(revision: IconRevision) {
+    const current = revisions.find((r) => r.current);
+    if (!current || revision.id === diff?.from.id) {
+      setDiff(null);
+      return;
+    }
+    setError(null);
+    try {
+      setDiff(await invoke<RevisionDiff>("diff_icon_revisions", { fromId: revision.id, toId: current.id }));
+    } catch (err) {
+      setError(errorMessage(err));
+    }
+  }
+
+  async function handleRestore(revisionId: number) {
+    setError(null);
+    try {
+      onRestored(await invoke<Icon>("restore_icon_revision", { revisionId }));
+    } catch (err) {
+      setError(errorMessage(err));
+    }
+  }
+
+  if (revisions.length < 2) return null;
+
+  return (
+    <div className="px-4 mt-5">
+      <h4 className="text-[11px] font-semibold text-[var(--text-muted)] uppercase tracking-wider mb-2">History</h4>
+      <div className="max-h-40 overflow-y-auto border border-[var(--border)] rounded-lg divide-y divide-[var(--border)]">
+        {revisions.map((revision) => (
+          <button
+            key={revision.id}
+            onClick={() => !revision.current && handleCompare(revision)}
+            disabled={revision.current}
+            className={`w-full flex items-center gap-2 px-2 py-1.5 text-left text-[12px] ${
+              diff?.from.id === revision.id ? "bg-[var(--bg-hover)]" : "hover:bg-[var(--bg-hover)] disabled:hover:bg-transparent"
+            }`}
+          >
+            <div
+              className="w-5 h-5 flex-shrink-0 p-0.5 rounded bg-white [&>svg]:w-full [&>svg]:h-full"
+              dangerouslySetInnerHTML={{ __html: revision.svg_content }}
+            />
+            <span className="flex-1 min-w-0 truncate text-[var(--text-primary)]">
+              {REASON_LABELS[revision.reason] ?? revision.reason}
+              <span className="text-[var(--text-muted)]"> · {new Date(revision.created_at).toLocaleString()}</span>
+            </span>
+            {revision.current && (
+              <span className="px-1.5 rounded-full bg-[var(--bg-tertiary)] text-[10px] text-[var(--text-secondary)]">Current</span>
+            )}
+          </button>
+        ))}
+      </div>
+
+      {diff && (
+        <div className="mt-2 space-y-2">
+          <div className="grid grid-cols-2 gap-2 text-[11px] text-[var(--text-muted)] text-center">
+            {[diff.from, diff.to].map((revision, i) => (
+              <div key={revision.id}>
+                <div
+                  className="p-2 mb-1 rounded border border-[var(--border)] bg-white [&>svg]:w-full [&>svg]:h-12"
+                  dangerouslySetInnerHTML={{ __html: revision.svg_content }}
+                />
+                {i === 0 ? "Selected" : "Current"}
+              </div>
+            ))}
+          </div>
+          {diff.visual_distance !== null && (
+            <p className="text-[11px] text-[var(--text-muted)]">
+              Looks {Math.round((1 - diff.visual_distance) * 100)}% alike
+            </p>
+          )}
+          <pre className="max-h-40 overflow-auto p-1.5 rounded bg-[var(--bg-tertiary)] text-[10px] leading-4 font-mono">
+            {diff.lines.map((line, i) => (
+              <div
+                key={i}
+                className={
+                  line.kind === "added"
+                    ? "text-green-700 bg-green-50"
+                    : line.kind === "removed"
+                      ? "text-red-600 bg-red-50"
+                      : "text-[var(--text-muted)]"
+                }
+              >
+                {line.kind === "added" ? "+ " : line.kind === "removed" ? "- " : "  "}
+                {line.text}
+              </div>
+            ))}
+          </pre>
+          <button
+            onClick={() => handleRestore(diff.from.id)}
+            className="w-full py-1.5 rounded-lg text-[12px] font-medium border border-[var(--border)] text-[var(--text-secondary)] hover:bg-[var(--bg-hover)]"
+          >
+            Restore this version
+          </button>
+        </div>
+      )}
+      {error && <p className="mt-2 text-[12px] text-red-500">{error}</p>}
+    </div>
+  );
+}
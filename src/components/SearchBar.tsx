@@ -1,10 +1,25 @@
+import { SearchSuggestion } from "../types";
+
 interface SearchBarProps {
   value: string;
   onChange: (value: string) => void;
   iconCount: number;
+  error?: string | null;
+  suggestions?: SearchSuggestion[];
+  /** Whether to search by meaning; the toggle is hidden when undefined */
+  semantic?: boolean;
+  onSemanticChange?: (semantic: boolean) => void;
 }
 
-export default function SearchBar({ value, onChange, iconCount }: SearchBarProps) {
+export default function SearchBar({
+  value,
+  onChange,
+  iconCount,
+  error,
+  suggestions = [],
+  semantic,
+  onSemanticChange,
+}: SearchBarProps) {
   return (
     <div className="flex items-center gap-4 w-full">
       <div className="relative flex-1 max-w-md">
@@ -25,7 +40,7 @@ export default function SearchBar({ value, onChange, iconCount }: SearchBarProps
           type="text"
           value={value}
           onChange={(e) => onChange(e.target.value)}
-          placeholder="Search icons..."
+          placeholder="Search icons... (try tag:arrow fav:yes size:<2kb)"
           className="w-full pl-10 pr-10 py-2 bg-[var(--bg-tertiary)] border border-transparent rounded-lg text-sm text-[var(--text-primary)] placeholder:text-[var(--text-muted)] focus:outline-none focus:border-[var(--accent)] focus:bg-white transition-all"
         />
         {value && (
@@ -38,10 +53,45 @@ export default function SearchBar({ value, onChange, iconCount }: SearchBarProps
             </svg>
           </button>
         )}
+        {suggestions.length > 0 && (
+          <ul className="absolute left-0 right-0 top-full mt-1 z-20 py-1 bg-[var(--bg-primary)] border border-[var(--border)] rounded-lg shadow-lg max-h-64 overflow-y-auto">
+            {suggestions.map((s) => (
+              <li key={s.query}>
+                <button
+                  onMouseDown={(e) => {
+                    e.preventDefault();
+                    onChange(s.query);
+                  }}
+                  className="w-full px-3 py-1.5 flex items-center justify-between text-left text-sm text-[var(--text-primary)] hover:bg-[var(--bg-tertiary)]"
+                >
+                  <span>{s.label}</span>
+                  <span className="text-xs text-[var(--text-muted)]">{s.detail}</span>
+                </button>
+              </li>
+            ))}
+          </ul>
+        )}
       </div>
-      <span className="text-xs text-[var(--text-muted)] whitespace-nowrap">
-        {iconCount} {iconCount === 1 ? "icon" : "icons"}
-      </span>
+      {semantic !== undefined && onSemanticChange && (
+        <button
+          onClick={() => onSemanticChange(!semantic)}
+          title="Match icons whose names mean something similar"
+          className={`px-2 py-1 text-xs rounded whitespace-nowrap transition-colors ${
+            semantic
+              ? "bg-[var(--accent)] text-white"
+              : "bg-[var(--bg-tertiary)] text-[var(--text-secondary)] hover:bg-[var(--bg-hover)]"
+          }`}
+        >
+          By meaning
+        </button>
+      )}
+      {error ? (
+        <span className="text-xs text-red-500 truncate">{error}</span>
+      ) : (
+        <span className="text-xs text-[var(--text-muted)] whitespace-nowrap">
+          {iconCount} {iconCount === 1 ? "icon" : "icons"}
+        </span>
+      )}
     </div>
   );
 }
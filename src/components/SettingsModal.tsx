@@ -1,9 +1,21 @@
 import { useState, useEffect } from "react";
-import { Settings } from "../types";
+import { EmbeddingStatus, Settings } from "../types";
+import EmbeddingSettings from "./EmbeddingSettings";
+import SynonymsEditor from "./SynonymsEditor";
+import TagManager from "./TagManager";
+import TrashManager from "./TrashManager";
+import BackupManager from "./BackupManager";
+import ArchiveManager from "./ArchiveManager";
 
 interface SettingsModalProps {
   settings: Settings;
   onSave: (key: string, value: string) => void;
+  onTagsChanged: () => void;
+  onEmbeddingModelLoaded: (status: EmbeddingStatus) => void;
+  onTrashRestored: () => void;
+  onLibraryReplaced: () => void;
+  onArchiveImported: () => void;
+  onAutotagAll: () => void;
   onClose: () => void;
 }
 
@@ -23,10 +35,12 @@ const PRESET_COLORS = [
 
 const ICON_SIZES = [32, 48, 64, 80, 96];
 
-export default function SettingsModal({ settings, onSave, onClose }: SettingsModalProps) {
+export default function SettingsModal({ settings, onSave, onTagsChanged, onEmbeddingModelLoaded, onTrashRestored, onLibraryReplaced, onArchiveImported, onAutotagAll, onClose }: SettingsModalProps) {
   const [iconSize, setIconSize] = useState(settings.icon_size);
   const [tintColor, setTintColor] = useState<string | null>(settings.tint_color);
   const [customColor, setCustomColor] = useState(settings.tint_color || "#6366f1");
+  const [stopWords, setStopWords] = useState(settings.autotag_stop_words);
+  const [retentionDays, setRetentionDays] = useState(settings.trash_retention_days);
 
   // Close on Escape
   useEffect(() => {
@@ -67,7 +81,7 @@ export default function SettingsModal({ settings, onSave, onClose }: SettingsMod
           </button>
         </div>
 
-        <div className="px-6 py-5 space-y-6">
+        <div className="px-6 py-5 space-y-6 max-h-[70vh] overflow-y-auto">
           {/* Icon Size */}
           <div>
             <label className="text-sm font-medium text-[var(--text-primary)] mb-3 block">
@@ -132,6 +146,91 @@ export default function SettingsModal({ settings, onSave, onClose }: SettingsMod
               <span className="text-[12px] text-[var(--text-muted)] font-mono">{customColor}</span>
             </div>
           </div>
+
+          {/* Auto-tagging */}
+          <div>
+            <label className="text-sm font-medium text-[var(--text-primary)] mb-3 block">
+              Auto-tagging Stop Words
+            </label>
+            <textarea
+              value={stopWords}
+              onChange={(e) => setStopWords(e.target.value)}
+              onBlur={() => stopWords !== settings.autotag_stop_words && onSave("autotag_stop_words", stopWords)}
+              rows={2}
+              className="w-full px-2 py-1.5 bg-[var(--bg-tertiary)] border border-transparent rounded-lg text-[12px] text-[var(--text-primary)] focus:outline-none focus:border-[var(--accent)]"
+            />
+            <div className="flex justify-between items-center mt-2">
+              <span className="text-[11px] text-[var(--text-muted)]">
+                Tags come from file names, folders and SVG metadata
+              </span>
+              <button
+                onClick={onAutotagAll}
+                className="px-2 py-1 bg-[var(--bg-tertiary)] text-[12px] text-[var(--text-secondary)] rounded hover:bg-[var(--bg-hover)]"
+              >
+                Re-tag library
+              </button>
+            </div>
+          </div>
+
+          {/* Synonyms */}
+          <div>
+            <label className="text-sm font-medium text-[var(--text-primary)] mb-3 block">
+              Search synonyms
+            </label>
+            <SynonymsEditor />
+          </div>
+
+          {/* Semantic search */}
+          <div>
+            <label className="text-sm font-medium text-[var(--text-primary)] mb-3 block">
+              Semantic search
+            </label>
+            <EmbeddingSettings onLoaded={onEmbeddingModelLoaded} />
+          </div>
+
+          {/* Tags */}
+          <div>
+            <label className="text-sm font-medium text-[var(--text-primary)] mb-3 block">
+              Tags
+            </label>
+            <TagManager onChanged={onTagsChanged} />
+          </div>
+
+          {/* Trash */}
+          <div>
+            <label className="text-sm font-medium text-[var(--text-primary)] mb-3 block">
+              Trash
+            </label>
+            <TrashManager
+              retentionDays={retentionDays}
+              onRetentionChange={(days) => {
+                setRetentionDays(days);
+                onSave("trash_retention_days", days.toString());
+              }}
+              onRestored={onTrashRestored}
+            />
+          </div>
+
+          {/* Backups */}
+          <div>
+            <label className="text-sm font-medium text-[var(--text-primary)] mb-3 block">
+              Backups
+            </label>
+            <BackupManager
+              intervalHours={settings.backup_interval_hours}
+              keep={settings.backup_keep}
+              onSave={onSave}
+              onLibraryReplaced={onLibraryReplaced}
+            />
+          </div>
+
+          {/* Export & import */}
+          <div>
+            <label className="text-sm font-medium text-[var(--text-primary)] mb-3 block">
+              Export &amp; import
+            </label>
+            <ArchiveManager onImported={onArchiveImported} />
+          </div>
         </div>
 
         {/* Footer */}
@@ -1,5 +1,9 @@
 import { useState, useRef, useEffect } from "react";
-import { Collection, ViewMode } from "../types";
+import { Collection, TagNode, ViewMode } from "../types";
+import { ICON_DRAG_TYPE } from "./IconCard";
+
+/** Drag data type carrying a collection id */
+const COLLECTION_DRAG_TYPE = "application/x-iconbox-collection";
 
 interface SidebarProps {
   collections: Collection[];
@@ -11,8 +15,23 @@ interface SidebarProps {
   onSelectView: (mode: ViewMode) => void;
   onImportFolder: (parentId?: string) => void;
   onCreateCollection: (name: string, parentId?: string) => void;
+  /** Current search bar text, offered for saving as a smart collection */
+  currentQuery: string;
+  onSaveSmartCollection: (id: string | null, name: string, query: string) => void;
   onRenameCollection: (id: string, newName: string) => void;
   onDeleteCollection: (id: string) => void;
+  onAutotagCollection: (id: string) => void;
+  /** Icons dropped onto a collection; copy when Ctrl/Alt was held */
+  onDropIcons: (collectionId: string, iconIds: string[], copy: boolean) => void;
+  /** Reparents a collection; null moves it to the top level */
+  onMoveCollection: (id: string, parentId: string | null) => void;
+  onReorderCollections: (parentId: string | null, orderedIds: string[]) => void;
+  onMergeCollection: (sourceId: string, targetId: string) => void;
+  /** Exports a collection and its subcollections as an archive */
+  onExportCollection: (id: string) => void;
+  tagTree: TagNode[];
+  /** Called with a tag's full path, e.g. "category/navigation" */
+  onSelectTag: (path: string) => void;
   onOpenSettings: () => void;
 }
 
@@ -32,8 +51,18 @@ export default function Sidebar({
   onSelectView,
   onImportFolder,
   onCreateCollection,
+  currentQuery,
+  onSaveSmartCollection,
   onRenameCollection,
   onDeleteCollection,
+  onAutotagCollection,
+  onDropIcons,
+  onMoveCollection,
+  onReorderCollections,
+  onMergeCollection,
+  onExportCollection,
+  tagTree,
+  onSelectTag,
   onOpenSettings,
 }: SidebarProps) {
   const [expandedIds, setExpandedIds] = useState<Set<string>>(new Set());
@@ -42,9 +71,17 @@ export default function Sidebar({
   const [newName, setNewName] = useState("");
   const [renamingId, setRenamingId] = useState<string | null>(null);
   const [renameValue, setRenameValue] = useState("");
+  const [isCreatingSmart, setIsCreatingSmart] = useState(false);
+  const [smartName, setSmartName] = useState("");
+  const [editingQueryId, setEditingQueryId] = useState<string | null>(null);
+  const [queryValue, setQueryValue] = useState("");
+  const [dropTargetId, setDropTargetId] = useState<string | null>(null);
+  const [expandedTagIds, setExpandedTagIds] = useState<Set<number>>(new Set());
   const [contextMenu, setContextMenu] = useState<{ x: number; y: number; colId: string } | null>(null);
+  const [pickingMergeTarget, setPickingMergeTarget] = useState(false);
   const inputRef = useRef<HTMLInputElement>(null);
   const renameRef = useRef<HTMLInputElement>(null);
+  const smartRef = useRef<HTMLInputElement>(null);
 
   useEffect(() => {
     if (isCreating && inputRef.current) inputRef.current.focus();
@@ -54,6 +91,10 @@ export default function Sidebar({
     if (renamingId && renameRef.current) renameRef.current.focus();
   }, [renamingId]);
 
+  useEffect(() => {
+    if ((isCreatingSmart || editingQueryId) && smartRef.current) smartRef.current.focus();
+  }, [isCreatingSmart, editingQueryId]);
+
   // Close context menu on click outside
   useEffect(() => {
     const handler = () => setContextMenu(null);
@@ -61,19 +102,29 @@ export default function Sidebar({
     return () => window.removeEventListener("click", handler);
   }, []);
 
+  // Manually ordered collections first, the rest by name
+  function sortCollections(items: Collection[]): Collection[] {
+    return [...items].sort(
+      (a, b) =>
+        (a.sort_order ?? Infinity) - (b.sort_order ?? Infinity) || a.name.localeCompare(b.name)
+    );
+  }
+
   // Build tree structure
   function buildTree(items: Collection[], parentId: string | null = null): TreeNode[] {
-    return items
-      .filter((c) => c.parent_id === parentId)
-      .map((c) => ({
-        collection: c,
-        children: buildTree(items, c.id),
-        expanded: expandedIds.has(c.id),
-      }))
-      .sort((a, b) => a.collection.name.localeCompare(b.collection.name));
+    return sortCollections(items.filter((c) => c.parent_id === parentId)).map((c) => ({
+      collection: c,
+      children: buildTree(items, c.id),
+      expanded: expandedIds.has(c.id),
+    }));
   }
 
-  const tree = buildTree(collections);
+  const folders = collections.filter((c) => !c.query);
+  const smartCollections = collections
+    .filter((c) => c.query)
+    .sort((a, b) => a.name.localeCompare(b.name));
+  const tree = buildTree(folders);
+  const contextIsSmart = !!collections.find((c) => c.id === contextMenu?.colId)?.query;
 
   function toggleExpand(id: string) {
     setExpandedIds((prev) => {
@@ -101,6 +152,23 @@ export default function Sidebar({
     }
   }
 
+  function handleCreateSmart() {
+    if (smartName.trim() && currentQuery.trim()) {
+      onSaveSmartCollection(null, smartName.trim(), currentQuery.trim());
+    }
+    setSmartName("");
+    setIsCreatingSmart(false);
+  }
+
+  function handleEditQuery() {
+    const col = collections.find((c) => c.id === editingQueryId);
+    if (col && queryValue.trim() && queryValue.trim() !== col.query) {
+      onSaveSmartCollection(col.id, col.name, queryValue.trim());
+    }
+    setEditingQueryId(null);
+    setQueryValue("");
+  }
+
   function startCreateSubfolder(parentId: string) {
     setCreatingParentId(parentId);
     setIsCreating(true);
@@ -111,6 +179,66 @@ export default function Sidebar({
   function handleContextMenu(e: React.MouseEvent, colId: string) {
     e.preventDefault();
     setContextMenu({ x: e.clientX, y: e.clientY, colId });
+    setPickingMergeTarget(false);
+  }
+
+  // Drops: icons move (or copy) into a collection, collections reparent.
+  // An empty colId is the top level.
+  function handleDragOver(e: React.DragEvent, colId: string) {
+    const types = e.dataTransfer.types;
+    if (types.includes(ICON_DRAG_TYPE) && colId) {
+      e.dataTransfer.dropEffect = e.ctrlKey || e.altKey ? "copy" : "move";
+    } else if (types.includes(COLLECTION_DRAG_TYPE)) {
+      e.dataTransfer.dropEffect = "move";
+    } else {
+      return;
+    }
+    e.preventDefault();
+    setDropTargetId(colId);
+  }
+
+  function handleDrop(e: React.DragEvent, colId: string) {
+    setDropTargetId(null);
+    const icons = e.dataTransfer.getData(ICON_DRAG_TYPE);
+    const collectionId = e.dataTransfer.getData(COLLECTION_DRAG_TYPE);
+    if (icons && colId) {
+      e.preventDefault();
+      onDropIcons(colId, JSON.parse(icons), e.ctrlKey || e.altKey);
+    } else if (collectionId && collectionId !== colId) {
+      e.preventDefault();
+      onMoveCollection(collectionId, colId || null);
+    }
+  }
+
+  function siblingsOf(col: Collection): Collection[] {
+    return sortCollections(folders.filter((c) => c.parent_id === col.parent_id));
+  }
+
+  function handleShift(colId: string, delta: number) {
+    const col = folders.find((c) => c.id === colId);
+    if (!col) return;
+    const ids = siblingsOf(col).map((c) => c.id);
+    const from = ids.indexOf(colId);
+    const to = from + delta;
+    if (to < 0 || to >= ids.length) return;
+    [ids[from], ids[to]] = [ids[to], ids[from]];
+    onReorderCollections(col.parent_id, ids);
+  }
+
+  // Ids of a collection and everything below it, which it can't merge into
+  function subtreeIds(colId: string): Set<string> {
+    const ids = new Set([colId]);
+    let grew = true;
+    while (grew) {
+      grew = false;
+      for (const c of folders) {
+        if (c.parent_id && ids.has(c.parent_id) && !ids.has(c.id)) {
+          ids.add(c.id);
+          grew = true;
+        }
+      }
+    }
+    return ids;
   }
 
   // Recursive tree item renderer
@@ -126,11 +254,19 @@ export default function Sidebar({
             onSelectCollection(node.collection.id);
           }}
           onContextMenu={(e) => handleContextMenu(e, node.collection.id)}
+          draggable={renamingId !== node.collection.id}
+          onDragStart={(e) => {
+            e.dataTransfer.setData(COLLECTION_DRAG_TYPE, node.collection.id);
+            e.dataTransfer.effectAllowed = "move";
+          }}
+          onDragOver={(e) => handleDragOver(e, node.collection.id)}
+          onDragLeave={() => setDropTargetId(null)}
+          onDrop={(e) => handleDrop(e, node.collection.id)}
           className={`w-full flex items-center gap-2 py-1.5 pr-3 text-[13px] rounded-md transition-colors group ${
             isActive
               ? "bg-[var(--accent-light)] text-[var(--accent)] font-medium"
               : "text-[var(--text-secondary)] hover:bg-[var(--bg-hover)] hover:text-[var(--text-primary)]"
-          }`}
+          } ${dropTargetId === node.collection.id ? "ring-2 ring-[var(--accent)]" : ""}`}
           style={{ paddingLeft }}
         >
           {/* Expand/collapse toggle */}
@@ -189,9 +325,16 @@ export default function Sidebar({
             <span className="flex-1 truncate text-left">{node.collection.name}</span>
           )}
 
-          {/* Count */}
-          <span className="text-[11px] text-[var(--text-muted)] flex-shrink-0">
-            {node.collection.icon_count}
+          {/* Count, including subcollections */}
+          <span
+            className="text-[11px] text-[var(--text-muted)] flex-shrink-0"
+            title={
+              hasChildren
+                ? `${node.collection.icon_count} here, ${node.collection.total_icon_count} with subfolders`
+                : undefined
+            }
+          >
+            {node.collection.total_icon_count}
           </span>
         </button>
 
@@ -232,6 +375,54 @@ export default function Sidebar({
     );
   }
 
+  function toggleTagExpanded(id: number) {
+    setExpandedTagIds((prev) => {
+      const next = new Set(prev);
+      if (next.has(id)) next.delete(id);
+      else next.add(id);
+      return next;
+    });
+  }
+
+  function renderTagNode(node: TagNode, depth: number) {
+    const hasChildren = node.children.length > 0;
+    const isExpanded = expandedTagIds.has(node.id);
+    return (
+      <div key={node.id}>
+        <button
+          onClick={() => onSelectTag(node.name)}
+          title={node.name}
+          className="w-full flex items-center gap-1.5 py-1 pr-3 text-[13px] rounded-md text-[var(--text-secondary)] hover:bg-[var(--bg-hover)] hover:text-[var(--text-primary)] transition-colors"
+          style={{ paddingLeft: `${12 + depth * 16}px` }}
+        >
+          <span
+            onClick={(e) => {
+              e.stopPropagation();
+              if (hasChildren) toggleTagExpanded(node.id);
+            }}
+            className={`w-3 h-3 flex items-center justify-center flex-shrink-0 ${hasChildren ? "" : "invisible"}`}
+          >
+            <svg
+              className={`w-2.5 h-2.5 transition-transform ${isExpanded ? "rotate-90" : ""}`}
+              fill="none"
+              stroke="currentColor"
+              viewBox="0 0 24 24"
+            >
+              <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={3} d="M9 5l7 7-7 7" />
+            </svg>
+          </span>
+          <span
+            className="w-2 h-2 rounded-full flex-shrink-0"
+            style={{ backgroundColor: node.color || "var(--text-muted)" }}
+          />
+          <span className="flex-1 truncate text-left">{node.label}</span>
+          <span className="text-[11px] text-[var(--text-muted)] flex-shrink-0">{node.total_count}</span>
+        </button>
+        {hasChildren && isExpanded && node.children.map((child) => renderTagNode(child, depth + 1))}
+      </div>
+    );
+  }
+
   return (
     <aside className="w-60 bg-[var(--bg-sidebar)] border-r border-[var(--border)] flex flex-col select-none">
       {/* Logo */}
@@ -285,8 +476,15 @@ export default function Sidebar({
       {/* Divider */}
       <div className="mx-3 my-2 border-t border-[var(--border)]" />
 
-      {/* Collections header */}
-      <div className="px-4 pb-1 flex items-center justify-between">
+      {/* Collections header (dropping a collection here moves it to the top level) */}
+      <div
+        onDragOver={(e) => handleDragOver(e, "")}
+        onDragLeave={() => setDropTargetId(null)}
+        onDrop={(e) => handleDrop(e, "")}
+        className={`px-4 pb-1 flex items-center justify-between rounded ${
+          dropTargetId === "" ? "ring-2 ring-[var(--accent)]" : ""
+        }`}
+      >
         <span className="text-[11px] font-semibold text-[var(--text-muted)] uppercase tracking-wider">
           Collections
         </span>
@@ -344,12 +542,119 @@ export default function Sidebar({
 
         {tree.map((node) => renderTreeNode(node))}
 
-        {collections.length === 0 && !isCreating && (
+        {folders.length === 0 && !isCreating && (
           <div className="px-3 py-4 text-center">
             <p className="text-[12px] text-[var(--text-muted)]">No collections yet</p>
             <p className="text-[11px] text-[var(--text-muted)] mt-1">Import a folder to begin</p>
           </div>
         )}
+
+        {/* Smart collections */}
+        <div className="px-2 pt-3 pb-1 flex items-center justify-between">
+          <span className="text-[11px] font-semibold text-[var(--text-muted)] uppercase tracking-wider">
+            Smart Collections
+          </span>
+          <button
+            onClick={() => setIsCreatingSmart(true)}
+            disabled={!currentQuery.trim()}
+            title={currentQuery.trim() ? "Save current search" : "Type a search to save it"}
+            className="p-1 rounded text-[var(--text-muted)] hover:text-[var(--text-primary)] hover:bg-[var(--bg-hover)] disabled:opacity-40 disabled:hover:bg-transparent"
+          >
+            <svg className="w-3.5 h-3.5" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+              <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M12 4v16m8-8H4" />
+            </svg>
+          </button>
+        </div>
+
+        {isCreatingSmart && (
+          <div className="px-3 py-1 mb-1">
+            <input
+              ref={smartRef}
+              type="text"
+              value={smartName}
+              onChange={(e) => setSmartName(e.target.value)}
+              onKeyDown={(e) => {
+                if (e.key === "Enter") handleCreateSmart();
+                if (e.key === "Escape") setIsCreatingSmart(false);
+              }}
+              onBlur={handleCreateSmart}
+              placeholder="Smart collection name..."
+              className="w-full bg-white border border-[var(--accent)] rounded px-2 py-1 text-[13px] focus:outline-none"
+            />
+          </div>
+        )}
+
+        {smartCollections.map((col) => {
+          const isActive = viewMode === "smart" && activeCollection === col.id;
+          return (
+            <div key={col.id}>
+              <button
+                onClick={() => onSelectCollection(col.id)}
+                onContextMenu={(e) => handleContextMenu(e, col.id)}
+                title={col.query ?? undefined}
+                className={`w-full flex items-center gap-2 py-1.5 pl-3 pr-3 text-[13px] rounded-md transition-colors ${
+                  isActive
+                    ? "bg-[var(--accent-light)] text-[var(--accent)] font-medium"
+                    : "text-[var(--text-secondary)] hover:bg-[var(--bg-hover)] hover:text-[var(--text-primary)]"
+                }`}
+              >
+                {/* Funnel icon */}
+                <svg className="w-4 h-4 flex-shrink-0" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                  <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={1.5} d="M3 4h18l-7 8.5V19l-4 2v-8.5L3 4z" />
+                </svg>
+
+                {renamingId === col.id ? (
+                  <input
+                    ref={renameRef}
+                    value={renameValue}
+                    onChange={(e) => setRenameValue(e.target.value)}
+                    onKeyDown={(e) => {
+                      if (e.key === "Enter") handleRename();
+                      if (e.key === "Escape") setRenamingId(null);
+                    }}
+                    onBlur={handleRename}
+                    onClick={(e) => e.stopPropagation()}
+                    className="flex-1 min-w-0 bg-white border border-[var(--accent)] rounded px-1.5 py-0.5 text-[13px] focus:outline-none"
+                  />
+                ) : (
+                  <span className="flex-1 truncate text-left">{col.name}</span>
+                )}
+
+                <span className="text-[11px] text-[var(--text-muted)] flex-shrink-0">{col.icon_count}</span>
+              </button>
+
+              {editingQueryId === col.id && (
+                <div className="pl-9 pr-3 py-1">
+                  <input
+                    ref={smartRef}
+                    type="text"
+                    value={queryValue}
+                    onChange={(e) => setQueryValue(e.target.value)}
+                    onKeyDown={(e) => {
+                      if (e.key === "Enter") handleEditQuery();
+                      if (e.key === "Escape") setEditingQueryId(null);
+                    }}
+                    onBlur={handleEditQuery}
+                    placeholder="tag:nav size:<2kb ..."
+                    className="w-full bg-white border border-[var(--accent)] rounded px-2 py-1 text-[12px] font-mono focus:outline-none"
+                  />
+                </div>
+              )}
+            </div>
+          );
+        })}
+
+        {/* Tags */}
+        {tagTree.length > 0 && (
+          <>
+            <div className="px-2 pt-3 pb-1">
+              <span className="text-[11px] font-semibold text-[var(--text-muted)] uppercase tracking-wider">
+                Tags
+              </span>
+            </div>
+            {tagTree.map((node) => renderTagNode(node, 0))}
+          </>
+        )}
       </div>
 
       {/* Footer */}
@@ -377,29 +682,134 @@ export default function Sidebar({
           className="fixed bg-white rounded-lg shadow-lg border border-[var(--border)] py-1 z-50 min-w-[160px]"
           style={{ left: contextMenu.x, top: contextMenu.y }}
         >
-          <button
-            onClick={() => {
-              startCreateSubfolder(contextMenu.colId);
-            }}
-            className="w-full flex items-center gap-2 px-3 py-1.5 text-[13px] text-[var(--text-primary)] hover:bg-[var(--bg-hover)] transition-colors"
-          >
-            <svg className="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
-              <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={1.5} d="M9 13h6m-3-3v6m-9 1V7a2 2 0 012-2h6l2 2h6a2 2 0 012 2v8a2 2 0 01-2 2H5a2 2 0 01-2-2z" />
-            </svg>
-            New Subfolder
-          </button>
-          <button
-            onClick={() => {
-              onImportFolder(contextMenu.colId);
-              setContextMenu(null);
-            }}
-            className="w-full flex items-center gap-2 px-3 py-1.5 text-[13px] text-[var(--text-primary)] hover:bg-[var(--bg-hover)] transition-colors"
-          >
-            <svg className="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
-              <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={1.5} d="M4 16v1a3 3 0 003 3h10a3 3 0 003-3v-1m-4-8l-4-4m0 0L8 8m4-4v12" />
-            </svg>
-            Import Into...
-          </button>
+          {contextIsSmart ? (
+            <button
+              onClick={() => {
+                const col = collections.find((c) => c.id === contextMenu.colId);
+                if (col) {
+                  setEditingQueryId(col.id);
+                  setQueryValue(col.query ?? "");
+                }
+                setContextMenu(null);
+              }}
+              className="w-full flex items-center gap-2 px-3 py-1.5 text-[13px] text-[var(--text-primary)] hover:bg-[var(--bg-hover)] transition-colors"
+            >
+              <svg className="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={1.5} d="M3 4h18l-7 8.5V19l-4 2v-8.5L3 4z" />
+              </svg>
+              Edit Query
+            </button>
+          ) : (
+            <>
+              <button
+                onClick={() => {
+                  startCreateSubfolder(contextMenu.colId);
+                }}
+                className="w-full flex items-center gap-2 px-3 py-1.5 text-[13px] text-[var(--text-primary)] hover:bg-[var(--bg-hover)] transition-colors"
+              >
+                <svg className="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                  <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={1.5} d="M9 13h6m-3-3v6m-9 1V7a2 2 0 012-2h6l2 2h6a2 2 0 012 2v8a2 2 0 01-2 2H5a2 2 0 01-2-2z" />
+                </svg>
+                New Subfolder
+              </button>
+              <button
+                onClick={() => {
+                  onImportFolder(contextMenu.colId);
+                  setContextMenu(null);
+                }}
+                className="w-full flex items-center gap-2 px-3 py-1.5 text-[13px] text-[var(--text-primary)] hover:bg-[var(--bg-hover)] transition-colors"
+              >
+                <svg className="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                  <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={1.5} d="M4 16v1a3 3 0 003 3h10a3 3 0 003-3v-1m-4-8l-4-4m0 0L8 8m4-4v12" />
+                </svg>
+                Import Into...
+              </button>
+              <button
+                onClick={() => {
+                  onAutotagCollection(contextMenu.colId);
+                  setContextMenu(null);
+                }}
+                className="w-full flex items-center gap-2 px-3 py-1.5 text-[13px] text-[var(--text-primary)] hover:bg-[var(--bg-hover)] transition-colors"
+              >
+                <svg className="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                  <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={1.5} d="M7 7h.01M7 3h5c.512 0 1.024.195 1.414.586l7 7a2 2 0 010 2.828l-7 7a2 2 0 01-2.828 0l-7-7A1.994 1.994 0 013 12V7a4 4 0 014-4z" />
+                </svg>
+                Auto-tag Icons
+              </button>
+              <button
+                onClick={() => {
+                  onExportCollection(contextMenu.colId);
+                  setContextMenu(null);
+                }}
+                className="w-full flex items-center gap-2 px-3 py-1.5 text-[13px] text-[var(--text-primary)] hover:bg-[var(--bg-hover)] transition-colors"
+              >
+                <svg className="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                  <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={1.5} d="M4 16v1a3 3 0 003 3h10a3 3 0 003-3v-1m-4-4l-4 4m0 0l-4-4m4 4V4" />
+                </svg>
+                Export...
+              </button>
+              <div className="mx-2 my-1 border-t border-[var(--border)]" />
+              <button
+                onClick={() => {
+                  handleShift(contextMenu.colId, -1);
+                  setContextMenu(null);
+                }}
+                className="w-full flex items-center gap-2 px-3 py-1.5 text-[13px] text-[var(--text-primary)] hover:bg-[var(--bg-hover)] transition-colors"
+              >
+                <svg className="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                  <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={1.5} d="M5 15l7-7 7 7" />
+                </svg>
+                Move Up
+              </button>
+              <button
+                onClick={() => {
+                  handleShift(contextMenu.colId, 1);
+                  setContextMenu(null);
+                }}
+                className="w-full flex items-center gap-2 px-3 py-1.5 text-[13px] text-[var(--text-primary)] hover:bg-[var(--bg-hover)] transition-colors"
+              >
+                <svg className="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                  <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={1.5} d="M19 9l-7 7-7-7" />
+                </svg>
+                Move Down
+              </button>
+              <button
+                onClick={(e) => {
+                  // Keep the menu open to pick the target
+                  e.stopPropagation();
+                  setPickingMergeTarget(true);
+                }}
+                className="w-full flex items-center gap-2 px-3 py-1.5 text-[13px] text-[var(--text-primary)] hover:bg-[var(--bg-hover)] transition-colors"
+              >
+                <svg className="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                  <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={1.5} d="M8 7h12m0 0l-4-4m4 4l-4 4M16 17H4m0 0l4 4m-4-4l4-4" />
+                </svg>
+                Merge Into...
+              </button>
+              {pickingMergeTarget && (
+                <div className="max-h-48 overflow-y-auto border-t border-[var(--border)] mt-1 pt-1">
+                  {sortCollections(folders)
+                    .filter((c) => !subtreeIds(contextMenu.colId).has(c.id))
+                    .map((c) => (
+                      <button
+                        key={c.id}
+                        onClick={() => {
+                          onMergeCollection(contextMenu.colId, c.id);
+                          setContextMenu(null);
+                        }}
+                        className="w-full flex items-center gap-2 pl-9 pr-3 py-1 text-[12px] text-[var(--text-secondary)] hover:bg-[var(--bg-hover)] hover:text-[var(--text-primary)] transition-colors"
+                      >
+                        <span
+                          className="w-2 h-2 rounded-full flex-shrink-0"
+                          style={{ backgroundColor: c.color || "#6366f1" }}
+                        />
+                        <span className="truncate">{c.name}</span>
+                      </button>
+                    ))}
+                </div>
+              )}
+            </>
+          )}
           <div className="mx-2 my-1 border-t border-[var(--border)]" />
           <button
             onClick={() => {
@@ -0,0 +1,113 @@
+import { useState, useEffect, useCallback } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { errorMessage } from "../errors";
+import { SynonymGroup } from "../types";
+
+function parseTerms(text: string) {
+  return text
+    .split(",")
+    .map((t) => t.trim())
+    .filter(Boolean);
+}
+
+export default function SynonymsEditor() {
+  const [groups, setGroups] = useState<SynonymGroup[]>([]);
+  const [drafts, setDrafts] = useState<Record<number, string>>({});
+  const [newTerms, setNewTerms] = useState("");
+  const [error, setError] = useState<string | null>(null);
+
+  const loadGroups = useCallback(async () => {
+    try {
+      setGroups(await invoke<SynonymGroup[]>("get_synonym_groups"));
+      setDrafts({});
+    } catch (err) {
+      console.error("Failed to load synonyms:", err);
+    }
+  }, []);
+
+  useEffect(() => {
+    loadGroups();
+  }, [loadGroups]);
+
+  async function run(command: string, args: Record<string, unknown> = {}) {
+    setError(null);
+    try {
+      await invoke(command, args);
+      await loadGroups();
+    } catch (err) {
+      setError(errorMessage(err));
+    }
+  }
+
+  function handleBlur(group: SynonymGroup) {
+    const draft = drafts[group.id];
+    if (draft === undefined || draft === group.terms.join(", ")) return;
+    // A group left with fewer than two terms is deleted by the backend
+    run("save_synonym_group", { id: group.id, terms: parseTerms(draft) });
+  }
+
+  async function handleAdd() {
+    const terms = parseTerms(newTerms);
+    if (terms.length < 2) {
+      setError("A synonym group needs at least two terms");
+      return;
+    }
+    await run("save_synonym_group", { id: null, terms });
+    setNewTerms("");
+  }
+
+  return (
+    <div>
+      <div className="max-h-48 overflow-y-auto border border-[var(--border)] rounded-lg divide-y divide-[var(--border)]">
+        {groups.map((group) => (
+          <div key={group.id} className="flex items-center gap-2 px-2 py-1">
+            <input
+              type="text"
+              value={drafts[group.id] ?? group.terms.join(", ")}
+              onChange={(e) => setDrafts({ ...drafts, [group.id]: e.target.value })}
+              onBlur={() => handleBlur(group)}
+              onKeyDown={(e) => e.key === "Enter" && e.currentTarget.blur()}
+              className="flex-1 min-w-0 bg-transparent text-[13px] text-[var(--text-primary)] focus:outline-none"
+            />
+            <button
+              onClick={() => run("delete_synonym_group", { id: group.id })}
+              title="Delete group"
+              className="text-[var(--text-muted)] hover:text-red-500"
+            >
+              <svg className="w-3.5 h-3.5" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M6 18L18 6M6 6l12 12" />
+              </svg>
+            </button>
+          </div>
+        ))}
+        <div className="flex items-center gap-2 px-2 py-1">
+          <input
+            type="text"
+            value={newTerms}
+            onChange={(e) => setNewTerms(e.target.value)}
+            onKeyDown={(e) => e.key === "Enter" && handleAdd()}
+            placeholder="New group, e.g. trash, bin, delete"
+            className="flex-1 min-w-0 bg-transparent text-[13px] text-[var(--text-primary)] placeholder:text-[var(--text-muted)] focus:outline-none"
+          />
+          <button
+            onClick={handleAdd}
+            disabled={!newTerms.trim()}
+            className="px-2 py-0.5 text-[12px] text-[var(--accent)] rounded hover:bg-[var(--bg-hover)] disabled:opacity-40"
+          >
+            Add
+          </button>
+        </div>
+      </div>
+      {error && <p className="mt-2 text-[12px] text-red-500">{error}</p>}
+      <div className="flex justify-between items-center mt-2">
+        <span className="text-[11px] text-[var(--text-muted)]">Searching any term also finds the others in its group</span>
+        <button
+          onClick={() => run("reset_synonyms")}
+          className="px-2 py-1 bg-[var(--bg-tertiary)] text-[12px] text-[var(--text-secondary)] rounded hover:bg-[var(--bg-hover)]"
+        >
+          Reset to defaults
+        </button>
+      </div>
+    </div>
+  );
+}
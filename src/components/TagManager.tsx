@@ -0,0 +1,141 @@
+import { useState, useEffect, useCallback } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { Tag } from "../types";
+
+interface TagManagerProps {
+  /** Called after any change so icon lists can pick up renamed tags */
+  onChanged: () => void;
+}
+
+export default function TagManager({ onChanged }: TagManagerProps) {
+  const [tags, setTags] = useState<Tag[]>([]);
+  const [selected, setSelected] = useState<Set<number>>(new Set());
+  const [renamingId, setRenamingId] = useState<number | null>(null);
+  const [renameValue, setRenameValue] = useState("");
+
+  const loadTags = useCallback(async () => {
+    try {
+      setTags(await invoke<Tag[]>("get_tags"));
+    } catch (err) {
+      console.error("Failed to load tags:", err);
+    }
+  }, []);
+
+  useEffect(() => {
+    loadTags();
+  }, [loadTags]);
+
+  async function run(command: string, args: Record<string, unknown>) {
+    try {
+      await invoke(command, args);
+      await loadTags();
+      onChanged();
+    } catch (err) {
+      console.error(`Failed to ${command.replace(/_/g, " ")}:`, err);
+    }
+  }
+
+  function handleRename() {
+    const tag = tags.find((t) => t.id === renamingId);
+    if (tag && renameValue.trim() && renameValue.trim() !== tag.name) {
+      // A name with "/" moves the tag; renaming onto an existing tag merges the two
+      run("rename_tag", { id: tag.id, newName: renameValue.trim() });
+    }
+    setRenamingId(null);
+  }
+
+  function handleMerge() {
+    const chosen = tags.filter((t) => selected.has(t.id));
+    if (chosen.length < 2) return;
+    // Keep the most used tag's name and color
+    const target = chosen.reduce((a, b) => (b.total_count > a.total_count ? b : a));
+    run("merge_tags", {
+      sourceIds: chosen.filter((t) => t.id !== target.id).map((t) => t.id),
+      targetId: target.id,
+    });
+    setSelected(new Set());
+  }
+
+  function toggleSelected(id: number) {
+    setSelected((prev) => {
+      const next = new Set(prev);
+      if (next.has(id)) next.delete(id);
+      else next.add(id);
+      return next;
+    });
+  }
+
+  if (tags.length === 0) {
+    return <p className="text-[12px] text-[var(--text-muted)]">No tags yet</p>;
+  }
+
+  return (
+    <div>
+      <div className="max-h-48 overflow-y-auto border border-[var(--border)] rounded-lg divide-y divide-[var(--border)]">
+        {tags.map((tag) => (
+          <div key={tag.id} className="flex items-center gap-2 px-2 py-1.5 text-[13px]">
+            <input
+              type="checkbox"
+              checked={selected.has(tag.id)}
+              onChange={() => toggleSelected(tag.id)}
+              className="accent-[var(--accent)]"
+            />
+            <input
+              type="color"
+              value={tag.color || "#9ca3af"}
+              onChange={(e) => run("set_tag_color", { id: tag.id, color: e.target.value })}
+              title="Tag color"
+              className="w-4 h-4 rounded cursor-pointer border-0 p-0"
+            />
+            {renamingId === tag.id ? (
+              <input
+                autoFocus
+                value={renameValue}
+                onChange={(e) => setRenameValue(e.target.value)}
+                onKeyDown={(e) => {
+                  if (e.key === "Enter") handleRename();
+                  if (e.key === "Escape") setRenamingId(null);
+                }}
+                onBlur={handleRename}
+                className="flex-1 min-w-0 bg-white border border-[var(--accent)] rounded px-1.5 py-0.5 text-[13px] focus:outline-none"
+              />
+            ) : (
+              <span
+                className="flex-1 truncate text-[var(--text-primary)] cursor-text"
+                onDoubleClick={() => {
+                  setRenamingId(tag.id);
+                  setRenameValue(tag.name);
+                }}
+                title="Double-click to rename"
+              >
+                {tag.name}
+              </span>
+            )}
+            <span className="text-[11px] text-[var(--text-muted)]">{tag.total_count}</span>
+            <button
+              onClick={() => run("delete_tag", { id: tag.id })}
+              title="Delete tag from all icons"
+              className="text-[var(--text-muted)] hover:text-red-500"
+            >
+              <svg className="w-3.5 h-3.5" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M6 18L18 6M6 6l12 12" />
+              </svg>
+            </button>
+          </div>
+        ))}
+      </div>
+      <div className="flex justify-between items-center mt-2">
+        <span className="text-[11px] text-[var(--text-muted)]">
+          Double-click to rename; use "/" to nest, e.g. style/outline
+        </span>
+        <button
+          onClick={handleMerge}
+          disabled={selected.size < 2}
+          className="px-2 py-1 bg-[var(--bg-tertiary)] text-[12px] text-[var(--text-secondary)] rounded hover:bg-[var(--bg-hover)] disabled:opacity-40"
+        >
+          Merge selected
+        </button>
+      </div>
+    </div>
+  );
+}
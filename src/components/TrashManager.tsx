@@ -0,0 +1,125 @@
+import { useState, useEffect, useCallback } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { TrashItem } from "../types";
+import { errorMessage } from "../errors";
+
+interface TrashManagerProps {
+  retentionDays: number;
+  onRetentionChange: (days: number) => void;
+  /** Called after a restore so icon lists and the sidebar pick it up */
+  onRestored: () => void;
+}
+
+const RETENTION_OPTIONS = [
+  { days: 7, label: "7 days" },
+  { days: 30, label: "30 days" },
+  { days: 90, label: "90 days" },
+  { days: 0, label: "Until emptied" },
+];
+
+export default function TrashManager({ retentionDays, onRetentionChange, onRestored }: TrashManagerProps) {
+  const [items, setItems] = useState<TrashItem[]>([]);
+  const [error, setError] = useState<string | null>(null);
+
+  const loadTrash = useCallback(async () => {
+    try {
+      setItems(await invoke<TrashItem[]>("get_trash"));
+    } catch (err) {
+      console.error("Failed to load trash:", err);
+    }
+  }, []);
+
+  useEffect(() => {
+    loadTrash();
+  }, [loadTrash]);
+
+  async function run(command: string, args: Record<string, unknown> = {}) {
+    setError(null);
+    try {
+      await invoke(command, args);
+      await loadTrash();
+      if (command === "restore_from_trash") onRestored();
+    } catch (err) {
+      // e.g. restoring an icon whose collection is still in the trash
+      setError(errorMessage(err));
+    }
+  }
+
+  function describe(item: TrashItem) {
+    const parts = [];
+    if (item.kind === "collection") {
+      parts.push(`${item.icon_count} icon${item.icon_count === 1 ? "" : "s"}`);
+    }
+    if (item.location) parts.push(`from ${item.location}`);
+    parts.push(`deleted ${new Date(item.deleted_at).toLocaleDateString()}`);
+    return parts.join(" · ");
+  }
+
+  return (
+    <div>
+      {items.length === 0 ? (
+        <p className="text-[12px] text-[var(--text-muted)]">Trash is empty</p>
+      ) : (
+        <div className="max-h-48 overflow-y-auto border border-[var(--border)] rounded-lg divide-y divide-[var(--border)]">
+          {items.map((item) => (
+            <div key={item.id} className="flex items-center gap-2 px-2 py-1.5 text-[13px]">
+              {item.svg_content ? (
+                <div
+                  className="w-5 h-5 flex-shrink-0 [&>svg]:w-full [&>svg]:h-full"
+                  dangerouslySetInnerHTML={{ __html: item.svg_content }}
+                />
+              ) : (
+                <svg className="w-5 h-5 flex-shrink-0 text-[var(--text-muted)]" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                  <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={1.5} d="M3 7v10a2 2 0 002 2h14a2 2 0 002-2V9a2 2 0 00-2-2h-6l-2-2H5a2 2 0 00-2 2z" />
+                </svg>
+              )}
+              <div className="flex-1 min-w-0">
+                <div className="truncate text-[var(--text-primary)]">{item.name}</div>
+                <div className="truncate text-[11px] text-[var(--text-muted)]">{describe(item)}</div>
+              </div>
+              <button
+                onClick={() => run("restore_from_trash", { trashIds: [item.id] })}
+                className="px-2 py-0.5 text-[12px] text-[var(--accent)] rounded hover:bg-[var(--bg-hover)]"
+              >
+                Restore
+              </button>
+              <button
+                onClick={() => run("delete_from_trash", { trashIds: [item.id] })}
+                title="Delete permanently"
+                className="text-[var(--text-muted)] hover:text-red-500"
+              >
+                <svg className="w-3.5 h-3.5" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                  <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M6 18L18 6M6 6l12 12" />
+                </svg>
+              </button>
+            </div>
+          ))}
+        </div>
+      )}
+      {error && <p className="mt-2 text-[12px] text-red-500">{error}</p>}
+      <div className="flex justify-between items-center mt-2">
+        <label className="flex items-center gap-1.5 text-[11px] text-[var(--text-muted)]">
+          Keep deleted items
+          <select
+            value={retentionDays}
+            onChange={(e) => onRetentionChange(Number(e.target.value))}
+            className="bg-[var(--bg-tertiary)] rounded px-1 py-0.5 text-[12px] text-[var(--text-secondary)] focus:outline-none"
+          >
+            {RETENTION_OPTIONS.map((o) => (
+              <option key={o.days} value={o.days}>
+                {o.label}
+              </option>
+            ))}
+          </select>
+        </label>
+        <button
+          onClick={() => run("empty_trash")}
+          disabled={items.length === 0}
+          className="px-2 py-1 bg-[var(--bg-tertiary)] text-[12px] text-red-500 rounded hover:bg-[var(--bg-hover)] disabled:opacity-40"
+        >
+          Empty trash
+        </button>
+      </div>
+    </div>
+  );
+}
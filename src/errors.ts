@@ -0,0 +1,14 @@
+import { AppError, ErrorCode } from "./types";
+
+export function isAppError(err: unknown): err is AppError {
+  return typeof err === "object" && err !== null && "code" in err && "message" in err;
+}
+
+/** Readable text for anything a command rejected with */
+export function errorMessage(err: unknown): string {
+  return isAppError(err) ? err.message : String(err);
+}
+
+export function hasCode(err: unknown, code: ErrorCode): boolean {
+  return isAppError(err) && err.code === code;
+}
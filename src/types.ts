@@ -10,22 +10,286 @@ export interface Icon {
   favorite: boolean;
 }
 
+/** An icon without its SVG body, as listed by `list_icons` */
+export interface IconSummary {
+  id: string;
+  name: string;
+  path: string;
+  tags: string[];
+  collection_id: string;
+  created_at: string;
+  file_size: number;
+  favorite: boolean;
+}
+
+export interface IconPage {
+  icons: IconSummary[];
+  /** Icons in the whole listing, not just this page */
+  total: number;
+  /** Pass back to `list_icons` for the next page; null on the last page */
+  next_cursor: string | null;
+}
+
+export interface IconSvg {
+  id: string;
+  svg_content: string;
+}
+
 export interface Collection {
   id: string;
   name: string;
   parent_id: string | null;
+  /** Icons directly in this collection */
   icon_count: number;
   color: string;
   created_at: string;
+  /** Saved search of a smart collection; null for regular collections */
+  query: string | null;
+  /** Manual position among siblings; null ones follow, sorted by name */
+  sort_order: number | null;
+  /** Icons in this collection and all its subcollections */
+  total_icon_count: number;
+}
+
+export interface SearchResults {
+  icons: Icon[];
+  total: number;
+  /** Typo-tolerant name matches, returned when exact terms found nothing */
+  fuzzy: boolean;
+}
+
+export interface SearchSuggestion {
+  label: string;
+  detail: string;
+  query: string;
+}
+
+export interface SimilarIcon {
+  icon: Icon;
+  /** 0 for identical artwork, 1 for completely different */
+  distance: number;
+}
+
+export interface SemanticMatch {
+  icon: Icon;
+  /** Cosine similarity between the query and the icon's text */
+  score: number;
+}
+
+export interface EmbeddingStatus {
+  model_path: string | null;
+  loaded: boolean;
+  dimensions: number;
+  vocabulary: number;
+  indexed: number;
+  /** Why the model at model_path could not be loaded */
+  error: AppError | null;
+}
+
+export interface Tag {
+  id: number;
+  /** Full path, e.g. "category/navigation" */
+  name: string;
+  parent_id: number | null;
+  color: string | null;
+  /** Icons with exactly this tag */
+  icon_count: number;
+  /** Icons with this tag or any of its subtags */
+  total_count: number;
+}
+
+export interface TagNode extends Tag {
+  /** Last segment of the path */
+  label: string;
+  children: TagNode[];
+}
+
+export interface SynonymGroup {
+  id: number;
+  terms: string[];
 }
 
 export interface Settings {
   theme: string;
   icon_size: number;
   tint_color: string | null;
+  /** Words never used as automatic tags, comma-separated */
+  autotag_stop_words: string;
+  /** Days deleted items stay in the trash; 0 keeps them until emptied */
+  trash_retention_days: number;
+  /** Where backups go; null for `backups` next to the library */
+  backup_folder: string | null;
+  /** Hours between scheduled backups; 0 turns them off */
+  backup_interval_hours: number;
+  /** Backups kept before the oldest are deleted */
+  backup_keep: number;
+}
+
+/** A deleted icon, or a deleted collection with everything that was in it */
+export interface TrashItem {
+  id: string;
+  kind: "icon" | "collection";
+  name: string;
+  /** Collection it was deleted from, if that still exists */
+  location: string | null;
+  deleted_at: string;
+  /** When it will be purged; null if kept until emptied */
+  expires_at: string | null;
+  icon_count: number;
+  collection_count: number;
+  svg_content: string | null;
+}
+
+/** Labels of the edits undo and redo would replay next */
+export interface HistoryState {
+  undo: string | null;
+  redo: string | null;
+}
+
+/** Why an icon's artwork changed */
+export type RevisionReason = "import" | "optimize" | "edit" | "sync" | "restore";
+
+export interface IconRevision {
+  id: number;
+  icon_id: string;
+  svg_content: string;
+  reason: RevisionReason;
+  created_at: string;
+  file_size: number;
+  /** The artwork the icon currently shows */
+  current: boolean;
+}
+
+export interface DiffLine {
+  kind: "same" | "added" | "removed";
+  text: string;
+}
+
+export interface RevisionDiff {
+  from: IconRevision;
+  to: IconRevision;
+  lines: DiffLine[];
+  /** 0 for identical artwork, 1 for completely different */
+  visual_distance: number | null;
+}
+
+export interface AutotagResult {
+  icons_updated: number;
+  tags_added: number;
+}
+
+export interface ImportFailure {
+  path: string;
+  error: AppError;
+}
+
+export interface ImportResult {
+  collection: Collection;
+  failed: ImportFailure[];
+}
+
+export interface MergeResult {
+  icons_moved: number;
+  icons_renamed: number;
+  duplicates_merged: number;
+  collections_merged: number;
+}
+
+/** Summary returned by export_archive */
+export interface ArchiveExportResult {
+  path: string;
+  collections: number;
+  icons: number;
+}
+
+/** What to do with an imported icon whose name is taken by a different one */
+export type ArchiveConflictPolicy = "skip" | "replace" | "keep_both";
+
+/** Summary returned by import_archive and preview_archive_import */
+export interface ArchiveImportResult {
+  collections_created: number;
+  collections_merged: number;
+  icons_added: number;
+  icons_replaced: number;
+  icons_renamed: number;
+  icons_skipped: number;
+  duplicates_merged: number;
+  smart_collections_created: number;
+}
+
+/** Summary returned by the bulk_* icon commands */
+export interface BulkResult {
+  processed: number;
+  changed: number;
+  /** Requested ids that matched no icon */
+  missing: string[];
+  /** Ids of icons created by bulk_copy_icons */
+  created_ids: string[];
+}
+
+export type ErrorCode =
+  | "not_found"
+  | "invalid_input"
+  | "invalid_query"
+  | "conflict"
+  | "cancelled"
+  | "not_open"
+  | "corrupt"
+  | "locked"
+  | "read_only"
+  | "too_new"
+  | "disk_full"
+  | "io"
+  | "model"
+  | "database"
+  | "internal";
+
+/** What every command rejects with */
+export interface AppError {
+  code: ErrorCode;
+  /** English fallback text */
+  message: string;
+  context: {
+    icon_id?: string;
+    collection_id?: string;
+    tag_id?: number;
+    path?: string;
+    /** Zero-based character offset in a search query */
+    position?: number;
+  };
+}
+
+export interface DbBackup {
+  path: string;
+  name: string;
+  size: number;
+  modified: string;
+}
+
+export interface BackupStatus {
+  folder: string | null;
+  /** Newest first */
+  backups: DbBackup[];
+  /** Why the last scheduled backup failed */
+  last_error: AppError | null;
+}
+
+/** How the library was opened, from get_startup_status and the recovery commands */
+export interface StartupStatus {
+  state: "ready" | "read_only" | "failed";
+  /** Why opening failed */
+  error: AppError | null;
+  db_path: string;
+  schema_version: number | null;
+  supported_version: number;
+  /** Copy taken before the schema was upgraded on this start */
+  migration_backup: string | null;
+  /** Where the previous library file was moved by a restore or fresh start */
+  set_aside: string | null;
+  backups: DbBackup[];
 }
 
-export type ViewMode = "all" | "favorites" | "collection";
+export type ViewMode = "all" | "favorites" | "collection" | "smart";
 
 export interface ContextMenuState {
   x: number;